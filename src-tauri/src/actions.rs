@@ -0,0 +1,203 @@
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::commands::{self, AppState, TranslateOptions, TranslationResult};
+
+/// Something the user can trigger from a global hotkey or the tray menu
+#[derive(Debug, Clone, PartialEq)]
+pub enum Action {
+    /// Translate the selection and show it in the overlay
+    Translate,
+    /// Translate the selection and paste the result over it
+    TranslateAndReplace,
+    /// Explain the grammar of the selected Japanese sentence
+    ExplainGrammar,
+    /// Check the politeness and phrasing of a selected Japanese draft
+    ReviewDraft,
+    /// Translate the selection into a fixed language, skipping detection
+    TranslateTo(String),
+    /// Retranslate the last captured text in the opposite direction
+    SwapDirection,
+    /// Retranslate the last captured text, treating it as the given language
+    RetranslateAs(String),
+    /// Show the most recent translation again
+    ShowLastResult,
+    /// Show or hide the overlay window
+    ToggleOverlay,
+    /// Open the translation history view
+    OpenHistory,
+    /// Open the glossary view
+    OpenGlossary,
+    /// Open the personal word list
+    OpenVocabulary,
+    /// Practise words from the word list that are due for review
+    PracticeWords,
+}
+
+/// Runs an action. Long-running work is spawned on the async runtime so
+/// hotkey and menu handlers return immediately.
+pub fn dispatch(app: &AppHandle, action: Action) {
+    println!("[DEBUG] Dispatching action: {:?}", action);
+
+    match action {
+        Action::Translate => spawn_translation(app, TranslateOptions::default()),
+        Action::TranslateAndReplace => spawn_translation(
+            app,
+            TranslateOptions {
+                replace: true,
+                ..Default::default()
+            },
+        ),
+        Action::TranslateTo(lang) => spawn_translation(
+            app,
+            TranslateOptions {
+                target_lang: Some(lang),
+                ..Default::default()
+            },
+        ),
+        Action::SwapDirection => retranslate_last(app, |last| TranslateOptions {
+            source_lang: Some(last.target_lang.clone()),
+            target_lang: Some(last.source_lang.clone()),
+            ..Default::default()
+        }),
+        Action::RetranslateAs(lang) => retranslate_last(app, move |_| TranslateOptions {
+            source_lang: Some(lang.clone()),
+            ..Default::default()
+        }),
+        Action::ExplainGrammar => spawn_explain(app),
+        Action::ReviewDraft => spawn_review(app),
+        Action::ShowLastResult => show_last_result(app),
+        Action::ToggleOverlay => toggle_overlay(app),
+        Action::OpenHistory => open_view(app, "open-history"),
+        Action::OpenGlossary => open_view(app, "open-glossary"),
+        Action::OpenVocabulary => open_view(app, "open-vocabulary"),
+        Action::PracticeWords => open_view(app, "open-practice"),
+    }
+}
+
+/// Runs the translation workflow in the background
+fn spawn_translation(app: &AppHandle, options: TranslateOptions) {
+    let app_clone = app.clone();
+    tauri::async_runtime::spawn(async move {
+        // Access state through app handle directly
+        let state_guard = app_clone.state::<AppState>();
+        let result = commands::run_translation(app_clone.clone(), state_guard, options).await;
+        if let Err(e) = result {
+            eprintln!("❌ Translation error: {}", e);
+        }
+    });
+}
+
+/// Runs the draft review workflow in the background
+fn spawn_review(app: &AppHandle) {
+    let app_clone = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let state_guard = app_clone.state::<AppState>();
+        if let Err(e) = commands::run_review(app_clone.clone(), state_guard).await {
+            eprintln!("❌ Review error: {}", e);
+        }
+    });
+}
+
+/// Runs the grammar explanation workflow in the background
+fn spawn_explain(app: &AppHandle) {
+    let app_clone = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let state_guard = app_clone.state::<AppState>();
+        if let Err(e) = commands::run_explain(app_clone.clone(), state_guard).await {
+            eprintln!("❌ Explain error: {}", e);
+        }
+    });
+}
+
+/// Retranslates the last captured text with options derived from the last
+/// result, without copying the selection again
+fn retranslate_last<F>(app: &AppHandle, options_for: F)
+where
+    F: FnOnce(&TranslationResult) -> TranslateOptions,
+{
+    let state = app.state::<AppState>();
+    let last_result = state.last_result.lock().ok().and_then(|last| last.clone());
+    let Some(last) = last_result else {
+        println!("[DEBUG] Nothing to retranslate yet");
+        return;
+    };
+
+    let options = options_for(&last);
+    let app_clone = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let state_guard = app_clone.state::<AppState>();
+        let result = commands::translate_text(
+            app_clone.clone(),
+            state_guard,
+            last.original,
+            options.source_lang,
+            options.target_lang,
+            options.formality,
+        )
+        .await;
+        if let Err(e) = result {
+            eprintln!("❌ Retranslation error: {}", e);
+        }
+    });
+}
+
+/// Re-emits the last translation so the overlay shows it again
+fn show_last_result(app: &AppHandle) {
+    let state = app.state::<AppState>();
+    let last_result = state.last_result.lock().ok().and_then(|last| last.clone());
+
+    match last_result {
+        Some(result) => {
+            if let Err(e) = app.emit("show-translation", result) {
+                eprintln!("❌ Failed to emit last result: {}", e);
+            }
+        }
+        None => println!("[DEBUG] No translation to show yet"),
+    }
+}
+
+/// Toggles overlay window visibility
+fn toggle_overlay(app: &AppHandle) {
+    let result: Result<(), String> = (|| {
+        let window = app
+            .get_webview_window("main")
+            .ok_or("Main window not found")?;
+
+        let is_visible = window
+            .is_visible()
+            .map_err(|e| format!("Failed to check visibility: {}", e))?;
+
+        if is_visible {
+            println!("[DEBUG] Hiding main window");
+            window
+                .hide()
+                .map_err(|e| format!("Failed to hide window: {}", e))?;
+        } else {
+            println!("[DEBUG] Showing main window");
+            window
+                .show()
+                .map_err(|e| format!("Failed to show window: {}", e))?;
+            window
+                .set_focus()
+                .map_err(|e| format!("Failed to focus window: {}", e))?;
+        }
+
+        Ok(())
+    })();
+
+    if let Err(e) = result {
+        eprintln!("❌ Show/Hide error: {}", e);
+    }
+}
+
+/// Shows the window and asks the frontend to switch the overlay to another view
+fn open_view(app: &AppHandle, event: &str) {
+    if let Some(window) = app.get_webview_window("main") {
+        window.show().ok();
+        window.set_focus().ok();
+    }
+
+    if let Err(e) = app.emit(event, ()) {
+        eprintln!("❌ Failed to emit {} event: {}", event, e);
+    }
+}
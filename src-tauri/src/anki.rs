@@ -0,0 +1,386 @@
+use rusqlite::{params, Connection, DatabaseName};
+use serde_json::json;
+use std::io::Write;
+use std::path::Path;
+use zip::write::SimpleFileOptions;
+
+use crate::history::now_millis;
+use crate::vocabulary::VocabularyItem;
+
+/// Deck and note type name shown in Anki
+const DECK_NAME: &str = "Japanese Slack Vocabulary";
+
+/// Fixed ids so importing a newer export updates the same deck and note type
+/// instead of creating copies
+const DECK_ID: i64 = 1_717_000_000_001;
+const MODEL_ID: i64 = 1_717_000_000_002;
+
+const FIELDS: [&str; 5] = ["Word", "Reading", "Meaning", "Sentence", "Translation"];
+
+const FRONT_TEMPLATE: &str = r#"<div class="word">{{Word}}</div>"#;
+
+const BACK_TEMPLATE: &str = r#"{{FrontSide}}
+<hr id="answer">
+<div class="reading">{{Reading}}</div>
+<div class="meaning">{{Meaning}}</div>
+<div class="sentence">{{Sentence}}</div>
+<div class="translation">{{Translation}}</div>"#;
+
+const CSS: &str = ".card { font-family: sans-serif; font-size: 20px; text-align: center; }
+.word { font-size: 48px; }
+.reading { font-size: 24px; color: #3a6ea5; }
+.sentence { margin-top: 16px; }
+.translation { color: #888; font-size: 16px; }";
+
+/// Anki schema 11 collection, as written by Anki 2.1 for `.apkg` exports
+const SCHEMA: &str = "
+    CREATE TABLE col (
+        id INTEGER PRIMARY KEY, crt INTEGER NOT NULL, mod INTEGER NOT NULL, scm INTEGER NOT NULL,
+        ver INTEGER NOT NULL, dty INTEGER NOT NULL, usn INTEGER NOT NULL, ls INTEGER NOT NULL,
+        conf TEXT NOT NULL, models TEXT NOT NULL, decks TEXT NOT NULL, dconf TEXT NOT NULL, tags TEXT NOT NULL
+    );
+    CREATE TABLE notes (
+        id INTEGER PRIMARY KEY, guid TEXT NOT NULL, mid INTEGER NOT NULL, mod INTEGER NOT NULL,
+        usn INTEGER NOT NULL, tags TEXT NOT NULL, flds TEXT NOT NULL, sfld INTEGER NOT NULL,
+        csum INTEGER NOT NULL, flags INTEGER NOT NULL, data TEXT NOT NULL
+    );
+    CREATE TABLE cards (
+        id INTEGER PRIMARY KEY, nid INTEGER NOT NULL, did INTEGER NOT NULL, ord INTEGER NOT NULL,
+        mod INTEGER NOT NULL, usn INTEGER NOT NULL, type INTEGER NOT NULL, queue INTEGER NOT NULL,
+        due INTEGER NOT NULL, ivl INTEGER NOT NULL, factor INTEGER NOT NULL, reps INTEGER NOT NULL,
+        lapses INTEGER NOT NULL, left INTEGER NOT NULL, odue INTEGER NOT NULL, odid INTEGER NOT NULL,
+        flags INTEGER NOT NULL, data TEXT NOT NULL
+    );
+    CREATE TABLE revlog (
+        id INTEGER PRIMARY KEY, cid INTEGER NOT NULL, usn INTEGER NOT NULL, ease INTEGER NOT NULL,
+        ivl INTEGER NOT NULL, lastIvl INTEGER NOT NULL, factor INTEGER NOT NULL, time INTEGER NOT NULL,
+        type INTEGER NOT NULL
+    );
+    CREATE TABLE graves (usn INTEGER NOT NULL, oid INTEGER NOT NULL, type INTEGER NOT NULL);
+    CREATE INDEX ix_notes_usn ON notes (usn);
+    CREATE INDEX ix_cards_usn ON cards (usn);
+    CREATE INDEX ix_revlog_usn ON revlog (usn);
+    CREATE INDEX ix_cards_nid ON cards (nid);
+    CREATE INDEX ix_cards_sched ON cards (did, queue, due);
+    CREATE INDEX ix_revlog_cid ON revlog (cid);
+    CREATE INDEX ix_notes_csum ON notes (csum);";
+
+/// Tab-separated deck with Anki's import headers (Anki 2.1.55+ reads them;
+/// older versions ignore the `#` lines). Fields are HTML.
+pub fn to_tsv(items: &[VocabularyItem]) -> String {
+    let mut out = format!("#separator:tab\n#html:true\n#columns:{}\n", FIELDS.join("\t"));
+    for item in items {
+        let fields = note_fields(item);
+        let row: Vec<String> = fields
+            .iter()
+            .map(|field| field.replace(['\t', '\r'], " ").replace('\n', "<br>"))
+            .collect();
+        out.push_str(&row.join("\t"));
+        out.push('\n');
+    }
+    out
+}
+
+/// Write an Anki package with one note (and card) per word
+pub fn write_apkg(items: &[VocabularyItem], path: &Path) -> Result<(), String> {
+    // The collection is built as an in-memory SQLite database, then zipped
+    let collection = build_collection(items)?;
+
+    let file = std::fs::File::create(path).map_err(|e| format!("Failed to create {}: {}", path.display(), e))?;
+    let mut zip = zip::ZipWriter::new(file);
+    let options = SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+    let write_error = |e: &dyn std::fmt::Display| format!("Failed to write Anki package: {}", e);
+
+    zip.start_file("collection.anki2", options).map_err(|e| write_error(&e))?;
+    zip.write_all(&collection).map_err(|e| write_error(&e))?;
+    // No images or audio
+    zip.start_file("media", options).map_err(|e| write_error(&e))?;
+    zip.write_all(b"{}").map_err(|e| write_error(&e))?;
+    zip.finish().map_err(|e| write_error(&e))?;
+    Ok(())
+}
+
+/// SQLite file contents of a collection holding the words
+fn build_collection(items: &[VocabularyItem]) -> Result<Vec<u8>, String> {
+    let mut conn = Connection::open_in_memory().map_err(|e| format!("Failed to create Anki collection: {}", e))?;
+    let tx = conn
+        .transaction()
+        .map_err(|e| format!("Failed to create Anki collection: {}", e))?;
+    let sql_error = |e: rusqlite::Error| format!("Failed to write Anki collection: {}", e);
+
+    tx.execute_batch(SCHEMA).map_err(sql_error)?;
+
+    let now_ms = now_millis();
+    let now = now_ms / 1000;
+    tx.execute(
+        "INSERT INTO col (id, crt, mod, scm, ver, dty, usn, ls, conf, models, decks, dconf, tags)
+         VALUES (1, ?1, ?2, ?2, 11, 0, 0, 0, ?3, ?4, ?5, ?6, '{}')",
+        params![
+            now,
+            now_ms,
+            collection_config().to_string(),
+            json!({ MODEL_ID.to_string(): note_type(now) }).to_string(),
+            json!({ "1": deck(1, "Default", now), DECK_ID.to_string(): deck(DECK_ID, DECK_NAME, now) }).to_string(),
+            json!({ "1": deck_options() }).to_string(),
+        ],
+    )
+    .map_err(sql_error)?;
+
+    {
+        let mut insert_note = tx
+            .prepare(
+                "INSERT INTO notes (id, guid, mid, mod, usn, tags, flds, sfld, csum, flags, data)
+                 VALUES (?1, ?2, ?3, ?4, -1, ?5, ?6, ?7, ?8, 0, '')",
+            )
+            .map_err(sql_error)?;
+        let mut insert_card = tx
+            .prepare(
+                "INSERT INTO cards (id, nid, did, ord, mod, usn, type, queue, due, ivl, factor, reps, lapses, left, odue, odid, flags, data)
+                 VALUES (?1, ?2, ?3, 0, ?4, -1, 0, 0, ?5, 0, 0, 0, 0, 0, 0, 0, 0, '')",
+            )
+            .map_err(sql_error)?;
+
+        // Note and card ids are millisecond timestamps in Anki; keep them unique
+        for (i, item) in items.iter().enumerate() {
+            let id = now_ms + i as i64;
+            let fields = note_fields(item);
+            let tags = format!(" {} ", item.pos);
+            insert_note
+                .execute(params![
+                    id,
+                    guid(item),
+                    MODEL_ID,
+                    now,
+                    tags,
+                    fields.join("\x1f"),
+                    fields[0],
+                    checksum(&item.word),
+                ])
+                .map_err(sql_error)?;
+            insert_card
+                .execute(params![id, id, DECK_ID, now, i as i64 + 1])
+                .map_err(sql_error)?;
+        }
+    }
+
+    tx.commit().map_err(sql_error)?;
+
+    conn.serialize(DatabaseName::Main)
+        .map(|data| data.to_vec())
+        .map_err(|e| format!("Failed to read Anki collection: {}", e))
+}
+
+/// Field values in `FIELDS` order, HTML-escaped, with the word bolded in its sentence
+fn note_fields(item: &VocabularyItem) -> [String; 5] {
+    let sentence = escape_html(&item.sentence);
+    let surface = escape_html(&item.surface);
+    let sentence = if surface.is_empty() {
+        sentence
+    } else {
+        sentence.replacen(&surface, &format!("<b>{}</b>", surface), 1)
+    };
+    [
+        escape_html(&item.word),
+        escape_html(&item.reading),
+        escape_html(item.gloss.as_deref().unwrap_or_default()),
+        sentence,
+        escape_html(&item.translation),
+    ]
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Stable note id across exports, so re-importing updates notes instead of duplicating them
+fn guid(item: &VocabularyItem) -> String {
+    let digest = sha1_smol::Sha1::from(format!("{}\x1f{}", item.word, item.reading)).digest().to_string();
+    digest[..16].to_string()
+}
+
+/// Anki's duplicate-check checksum: the first 8 hex digits of the SHA-1 of the first field
+fn checksum(first_field: &str) -> i64 {
+    let digest = sha1_smol::Sha1::from(first_field).digest().to_string();
+    i64::from_str_radix(&digest[..8], 16).unwrap_or_default()
+}
+
+fn note_type(now: i64) -> serde_json::Value {
+    let fields: Vec<_> = FIELDS
+        .iter()
+        .enumerate()
+        .map(|(ord, name)| {
+            json!({ "name": name, "ord": ord, "sticky": false, "rtl": false, "font": "Arial", "size": 20, "media": [] })
+        })
+        .collect();
+    json!({
+        "id": MODEL_ID,
+        "name": DECK_NAME,
+        "type": 0,
+        "mod": now,
+        "usn": -1,
+        "sortf": 0,
+        "did": DECK_ID,
+        "tmpls": [{
+            "name": "Recognition",
+            "ord": 0,
+            "qfmt": FRONT_TEMPLATE,
+            "afmt": BACK_TEMPLATE,
+            "did": null,
+            "bqfmt": "",
+            "bafmt": "",
+        }],
+        "flds": fields,
+        "css": CSS,
+        "latexPre": "\\documentclass[12pt]{article}\n\\special{papersize=3in,5in}\n\\usepackage{amssymb,amsmath}\n\\pagestyle{empty}\n\\setlength{\\parindent}{0in}\n\\begin{document}\n",
+        "latexPost": "\\end{document}",
+        "latexsvg": false,
+        "req": [[0, "any", [0]]],
+        "tags": [],
+        "vers": [],
+    })
+}
+
+fn deck(id: i64, name: &str, now: i64) -> serde_json::Value {
+    json!({
+        "id": id,
+        "name": name,
+        "mod": now,
+        "usn": -1,
+        "lrnToday": [0, 0],
+        "revToday": [0, 0],
+        "newToday": [0, 0],
+        "timeToday": [0, 0],
+        "collapsed": false,
+        "browserCollapsed": false,
+        "desc": "Words met in translated Japanese",
+        "dyn": 0,
+        "conf": 1,
+        "extendNew": 0,
+        "extendRev": 0,
+    })
+}
+
+fn collection_config() -> serde_json::Value {
+    json!({
+        "activeDecks": [1],
+        "curDeck": 1,
+        "newSpread": 0,
+        "collapseTime": 1200,
+        "timeLim": 0,
+        "estTimes": true,
+        "dueCounts": true,
+        "curModel": MODEL_ID,
+        "nextPos": 1,
+        "sortType": "noteFld",
+        "sortBackwards": false,
+        "addToCur": true,
+    })
+}
+
+/// Anki's default deck options
+fn deck_options() -> serde_json::Value {
+    json!({
+        "id": 1,
+        "name": "Default",
+        "mod": 0,
+        "usn": 0,
+        "maxTaken": 60,
+        "autoplay": true,
+        "timer": 0,
+        "replayq": true,
+        "dyn": false,
+        "new": { "delays": [1, 10], "ints": [1, 4, 7], "initialFactor": 2500, "order": 1, "perDay": 20, "bury": false },
+        "rev": { "perDay": 200, "ease4": 1.3, "fuzz": 0.05, "ivlFct": 1, "maxIvl": 36500, "bury": false, "hardFactor": 1.2 },
+        "lapse": { "delays": [10], "mult": 0, "minInt": 1, "leechFails": 8, "leechAction": 1 },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+
+    fn item(word: &str, reading: &str, sentence: &str) -> VocabularyItem {
+        VocabularyItem {
+            id: 1,
+            word: word.to_string(),
+            reading: reading.to_string(),
+            gloss: Some("to eat".to_string()),
+            pos: "動詞".to_string(),
+            surface: "食べ".to_string(),
+            sentence: sentence.to_string(),
+            translation: "I ate <sushi>".to_string(),
+            added_at: 0,
+        }
+    }
+
+    #[test]
+    fn tsv_has_headers_and_escaped_fields() {
+        let tsv = to_tsv(&[item("食べる", "たべる", "寿司を食べた\tよ\n本当に")]);
+        let lines: Vec<&str> = tsv.lines().collect();
+        assert_eq!(lines[..3], ["#separator:tab", "#html:true", "#columns:Word\tReading\tMeaning\tSentence\tTranslation"]);
+        assert_eq!(
+            lines[3].split('\t').collect::<Vec<_>>(),
+            ["食べる", "たべる", "to eat", "寿司を<b>食べ</b>た よ<br>本当に", "I ate &lt;sushi&gt;"]
+        );
+        assert_eq!(lines.len(), 4);
+    }
+
+    #[test]
+    fn apkg_holds_one_note_and_card_per_word() {
+        let dir = std::env::temp_dir().join(format!("anki-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let package = dir.join("vocabulary.apkg");
+        let items = [item("食べる", "たべる", "寿司を食べた"), item("飲む", "のむ", "水を飲む")];
+        write_apkg(&items, &package).unwrap();
+
+        let mut archive = zip::ZipArchive::new(std::fs::File::open(&package).unwrap()).unwrap();
+        let mut media = String::new();
+        archive.by_name("media").unwrap().read_to_string(&mut media).unwrap();
+        assert_eq!(media, "{}");
+        let mut collection = Vec::new();
+        archive.by_name("collection.anki2").unwrap().read_to_end(&mut collection).unwrap();
+        let collection_path = dir.join("collection.anki2");
+        std::fs::write(&collection_path, collection).unwrap();
+
+        let conn = Connection::open(&collection_path).unwrap();
+        let notes: Vec<(String, i64, String, String, i64)> = conn
+            .prepare("SELECT guid, mid, flds, sfld, csum FROM notes ORDER BY id")
+            .unwrap()
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?)))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(notes.len(), 2);
+        let (guid_value, mid, flds, sfld, csum) = &notes[0];
+        assert_eq!(guid_value, &guid(&items[0]));
+        assert_eq!(*mid, MODEL_ID);
+        assert_eq!(
+            flds.split('\x1f').collect::<Vec<_>>(),
+            ["食べる", "たべる", "to eat", "寿司を<b>食べ</b>た", "I ate &lt;sushi&gt;"]
+        );
+        assert_eq!(sfld, "食べる");
+        assert_eq!(*csum, checksum("食べる"));
+
+        let cards: i64 = conn
+            .query_row("SELECT count(*) FROM cards WHERE did = ?1", [DECK_ID], |row| row.get(0))
+            .unwrap();
+        assert_eq!(cards, 2);
+        let models: String = conn.query_row("SELECT models FROM col", [], |row| row.get(0)).unwrap();
+        assert!(models.contains(DECK_NAME));
+
+        drop(conn);
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn guids_are_stable_per_word_and_reading() {
+        let a = item("食べる", "たべる", "寿司を食べた");
+        let b = item("食べる", "たべる", "パンを食べる");
+        assert_eq!(guid(&a), guid(&b));
+        assert_ne!(guid(&a), guid(&item("食べる", "くべる", "")));
+    }
+}
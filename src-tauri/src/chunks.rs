@@ -0,0 +1,274 @@
+use std::ops::Range;
+
+use crate::language::is_cjk;
+
+/// Longest chunk sent in one translation request, in characters
+pub const MAX_CHUNK_CHARS: usize = 1500;
+
+/// Text from the previous chunk sent along as context, in characters
+const CONTEXT_CHARS: usize = 200;
+
+/// A piece of a long text, translated on its own
+#[derive(Clone, Debug, PartialEq)]
+pub struct Chunk {
+    /// The text to translate, without surrounding whitespace
+    pub text: String,
+    /// The end of the previous chunk, for the model to read but not translate
+    pub context: Option<String>,
+    /// Whitespace that followed the chunk in the original
+    pub separator: String,
+}
+
+/// Whether `text` is too long for a single request
+pub fn needs_chunking(text: &str) -> bool {
+    text.chars().count() > MAX_CHUNK_CHARS
+}
+
+/// Split `text` into chunks of at most `max_chars`, preferring paragraph
+/// breaks, then sentence ends (。！？ and . ! ? before a space), then spaces
+pub fn split(text: &str, max_chars: usize) -> Vec<Chunk> {
+    let mut pieces: Vec<&str> = Vec::new();
+    for paragraph in split_inclusive(text, paragraph_ends(text)) {
+        if paragraph.chars().count() <= max_chars {
+            pieces.push(paragraph);
+            continue;
+        }
+        for sentence in split_inclusive(paragraph, sentence_ends(paragraph)) {
+            if sentence.chars().count() <= max_chars {
+                pieces.push(sentence);
+            } else {
+                pieces.extend(split_hard(sentence, max_chars));
+            }
+        }
+    }
+
+    // Pack pieces into as few chunks as fit
+    let mut packed: Vec<String> = Vec::new();
+    let mut current = String::new();
+    for piece in pieces {
+        if !current.is_empty() && current.chars().count() + piece.trim_end().chars().count() > max_chars {
+            packed.push(std::mem::take(&mut current));
+        }
+        current.push_str(piece);
+    }
+    if !current.trim().is_empty() || packed.is_empty() {
+        packed.push(current);
+    } else if let Some(last) = packed.last_mut() {
+        last.push_str(&current);
+    }
+
+    let mut chunks: Vec<Chunk> = Vec::new();
+    for piece in packed {
+        let text = piece.trim_end();
+        let context = chunks.last().map(|previous| tail(&previous.text, CONTEXT_CHARS));
+        chunks.push(Chunk {
+            text: text.to_string(),
+            context,
+            separator: piece[text.len()..].to_string(),
+        });
+    }
+    chunks
+}
+
+/// Group consecutive texts (e.g. the text nodes of a document) into batches
+/// of at most `max_chars` in total. A longer text is a batch of its own.
+pub fn batches(texts: &[String], max_chars: usize) -> Vec<Range<usize>> {
+    let mut batches: Vec<Range<usize>> = Vec::new();
+    let mut chars = 0;
+    for (i, text) in texts.iter().enumerate() {
+        let length = text.chars().count();
+        match batches.last_mut() {
+            Some(batch) if chars + length <= max_chars => {
+                batch.end = i + 1;
+                chars += length;
+            }
+            _ => {
+                batches.push(i..i + 1);
+                chars = length;
+            }
+        }
+    }
+    batches
+}
+
+/// Put translated chunks back together in order. Chunks that split a
+/// paragraph are joined with a space, or nothing after CJK text.
+pub fn join(chunks: &[Chunk], translations: &[String]) -> String {
+    let mut joined = String::new();
+    for (i, (chunk, translation)) in chunks.iter().zip(translations).enumerate() {
+        joined.push_str(translation);
+        if i + 1 == chunks.len() {
+            break;
+        }
+        if chunk.separator.contains('\n') {
+            joined.push_str(&chunk.separator);
+        } else if !translation.chars().next_back().is_some_and(|c| is_cjk(c) || is_cjk_punctuation(c)) {
+            joined.push(' ');
+        }
+    }
+    joined
+}
+
+fn is_cjk_punctuation(c: char) -> bool {
+    matches!(c as u32, 0x3000..=0x303F | 0xFF01..=0xFF0F | 0xFF1A..=0xFF20)
+}
+
+/// Split `text` after each of the given byte offsets
+fn split_inclusive(text: &str, ends: Vec<usize>) -> Vec<&str> {
+    let mut pieces = Vec::new();
+    let mut start = 0;
+    for end in ends {
+        if end > start {
+            pieces.push(&text[start..end]);
+            start = end;
+        }
+    }
+    if start < text.len() {
+        pieces.push(&text[start..]);
+    }
+    pieces
+}
+
+/// Byte offsets just after each blank line
+fn paragraph_ends(text: &str) -> Vec<usize> {
+    let mut ends = Vec::new();
+    let mut newlines = 0;
+    for (i, c) in text.char_indices() {
+        match c {
+            '\n' => newlines += 1,
+            c if c.is_whitespace() => {}
+            _ => {
+                if newlines >= 2 {
+                    ends.push(i);
+                }
+                newlines = 0;
+            }
+        }
+    }
+    ends
+}
+
+/// Byte offsets just after each sentence and the whitespace that follows it
+fn sentence_ends(text: &str) -> Vec<usize> {
+    let mut ends = Vec::new();
+    let mut chars = text.char_indices().peekable();
+    let mut after_end = false;
+    while let Some((i, c)) = chars.next() {
+        let next = chars.peek().map(|&(_, next)| next);
+        match c {
+            '。' | '！' | '？' | '\n' => after_end = true,
+            // "3.5" and "e.g.x" are not sentence ends
+            '.' | '!' | '?' if next.is_none_or(char::is_whitespace) => after_end = true,
+            c if after_end && !c.is_whitespace() && !matches!(c, '」' | '』' | '）' | '"' | ')') => {
+                ends.push(i);
+                after_end = false;
+            }
+            _ => {}
+        }
+    }
+    ends
+}
+
+/// Split an overlong sentence at spaces, or anywhere if it has none
+fn split_hard(text: &str, max_chars: usize) -> Vec<&str> {
+    let mut pieces = Vec::new();
+    let mut rest = text;
+    while rest.chars().count() > max_chars {
+        let limit = rest.char_indices().nth(max_chars).map_or(rest.len(), |(i, _)| i);
+        let cut = rest[..limit]
+            .rfind(char::is_whitespace)
+            .map(|i| i + rest[i..].chars().next().map_or(1, char::len_utf8))
+            .filter(|&i| i > 0)
+            .unwrap_or(limit);
+        pieces.push(&rest[..cut]);
+        rest = &rest[cut..];
+    }
+    if !rest.is_empty() {
+        pieces.push(rest);
+    }
+    pieces
+}
+
+/// The last sentences of `text` that fit in `max_chars`
+fn tail(text: &str, max_chars: usize) -> String {
+    let mut start = text.len();
+    for end in std::iter::once(0).chain(sentence_ends(text)).rev() {
+        if text[end..].chars().count() > max_chars {
+            break;
+        }
+        start = end;
+    }
+    if start == text.len() {
+        // The last sentence alone is too long; take its end
+        let skip = text.chars().count().saturating_sub(max_chars);
+        start = text.char_indices().nth(skip).map_or(0, |(i, _)| i);
+    }
+    text[start..].trim().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts(chunks: &[Chunk]) -> Vec<&str> {
+        chunks.iter().map(|chunk| chunk.text.as_str()).collect()
+    }
+
+    #[test]
+    fn short_text_is_one_chunk() {
+        let chunks = split("Hello there.", 100);
+        assert_eq!(texts(&chunks), ["Hello there."]);
+        assert_eq!(chunks[0].context, None);
+        assert!(!needs_chunking(&"あ".repeat(MAX_CHUNK_CHARS)));
+        assert!(needs_chunking(&"あ".repeat(MAX_CHUNK_CHARS + 1)));
+    }
+
+    #[test]
+    fn japanese_sentences_split_after_their_punctuation() {
+        let chunks = split("一文目です。二文目です！三文目ですか？", 8);
+        assert_eq!(texts(&chunks), ["一文目です。", "二文目です！", "三文目ですか？"]);
+        assert_eq!(chunks[1].context.as_deref(), Some("一文目です。"));
+    }
+
+    #[test]
+    fn decimals_are_not_sentence_ends() {
+        let chunks = split("The rate is 3.5 now. It was 2.5 before.", 24);
+        assert_eq!(texts(&chunks), ["The rate is 3.5 now.", "It was 2.5 before."]);
+    }
+
+    #[test]
+    fn closing_quotes_stay_with_their_sentence() {
+        let text = "「明日行きます。」次は来週です。";
+        assert_eq!(split_inclusive(text, sentence_ends(text)), ["「明日行きます。」", "次は来週です。"]);
+        assert_eq!(texts(&split(text, 10)), ["「明日行きます。」", "次は来週です。"]);
+    }
+
+    #[test]
+    fn overlong_sentences_split_at_spaces_or_anywhere() {
+        assert_eq!(split_hard("aaaa bbbb cccc", 6), ["aaaa ", "bbbb ", "cccc"]);
+        assert_eq!(split_hard("あいうえおかきく", 3), ["あいう", "えおか", "きく"]);
+        assert!(split("x".repeat(25).as_str(), 10).iter().all(|chunk| chunk.text.chars().count() <= 10));
+    }
+
+    #[test]
+    fn chunks_pack_and_join_in_order() {
+        let text = "First paragraph here.\n\nSecond one. Third sentence.";
+        let chunks = split(text, 24);
+        assert_eq!(texts(&chunks), ["First paragraph here.", "Second one.", "Third sentence."]);
+        assert_eq!(chunks[0].separator, "\n\n");
+
+        let translations: Vec<String> = chunks.iter().map(|chunk| chunk.text.to_uppercase()).collect();
+        assert_eq!(join(&chunks, &translations), "FIRST PARAGRAPH HERE.\n\nSECOND ONE. THIRD SENTENCE.");
+
+        // Translations into Japanese are joined without spaces
+        let japanese = ["最初の段落。", "二番目。", "三番目。"].map(String::from);
+        assert_eq!(join(&chunks, &japanese), "最初の段落。\n\n二番目。三番目。");
+    }
+
+    #[test]
+    fn texts_are_batched_by_size() {
+        let texts = ["a".repeat(4), "b".repeat(4), "c".repeat(12), "d".repeat(3), "e".repeat(5)];
+        assert_eq!(batches(&texts, 10), [0..2, 2..3, 3..5]);
+        assert!(batches(&[], 10).is_empty());
+    }
+}
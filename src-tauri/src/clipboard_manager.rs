@@ -32,66 +32,18 @@ impl SmartClipboard {
     /// MUST be called with an AppHandle to dispatch to main thread on macOS
     pub fn copy_selection(&mut self, app: &AppHandle) -> Result<(), String> {
         thread::sleep(Duration::from_millis(50));
+        simulate_shortcut(app, 'c')
+    }
 
-        // Create channel to wait for main thread operation
-        let (tx, rx) = mpsc::channel();
-
-        // Dispatch keyboard operation to main thread (required on macOS)
-        app.run_on_main_thread(move || {
-            let result: Result<(), String> = (|| {
-                let mut enigo = Enigo::new(&Settings::default())
-                    .map_err(|e| format!("Failed to create Enigo: {}", e))?;
-
-                #[cfg(target_os = "macos")]
-                {
-                    println!("[DEBUG] Starting Cmd+C keyboard simulation");
-                    enigo
-                        .key(Key::Meta, Press)
-                        .map_err(|e| format!("Failed to press Cmd: {}", e))?;
-                    enigo
-                        .key(Key::Unicode('c'), Press)
-                        .map_err(|e| format!("Failed to press C: {}", e))?;
-                    thread::sleep(Duration::from_millis(10));
-                    enigo
-                        .key(Key::Unicode('c'), Release)
-                        .map_err(|e| format!("Failed to release C: {}", e))?;
-                    enigo
-                        .key(Key::Meta, Release)
-                        .map_err(|e| format!("Failed to release Cmd: {}", e))?;
-                    println!("[DEBUG] Completed Cmd+C keyboard simulation");
-                }
-
-                #[cfg(not(target_os = "macos"))]
-                {
-                    println!("[DEBUG] Starting Ctrl+C keyboard simulation");
-                    enigo
-                        .key(Key::Control, Press)
-                        .map_err(|e| format!("Failed to press Ctrl: {}", e))?;
-                    enigo
-                        .key(Key::Unicode('c'), Press)
-                        .map_err(|e| format!("Failed to press C: {}", e))?;
-                    thread::sleep(Duration::from_millis(10));
-                    enigo
-                        .key(Key::Unicode('c'), Release)
-                        .map_err(|e| format!("Failed to release C: {}", e))?;
-                    enigo
-                        .key(Key::Control, Release)
-                        .map_err(|e| format!("Failed to release Ctrl: {}", e))?;
-                    println!("[DEBUG] Completed Ctrl+C keyboard simulation");
-                }
-
-                thread::sleep(Duration::from_millis(100));
-                Ok(())
-            })();
-
-            let _ = tx.send(result);
-        })
-        .map_err(|e| format!("Failed to dispatch to main thread: {}", e))?;
-
-        // Wait for main thread operation to complete
-        rx.recv()
-            .map_err(|e| format!("Main thread operation failed: {}", e))??;
+    /// Replace the current selection with `text` by writing it to the
+    /// clipboard and simulating Cmd+V or Ctrl+V
+    pub fn paste_text(&mut self, app: &AppHandle, text: &str) -> Result<(), String> {
+        self.write_clipboard(app, text)?;
+        thread::sleep(Duration::from_millis(50));
+        simulate_shortcut(app, 'v')?;
 
+        // Give the target app time to read the clipboard before it is restored
+        thread::sleep(Duration::from_millis(150));
         Ok(())
     }
 
@@ -141,7 +93,7 @@ impl SmartClipboard {
         }
 
         // No text found - return error
-        Err("No text selected. Please select text before pressing Cmd+J.".to_string())
+        Err("No text selected. Please select text before using a translate hotkey.".to_string())
     }
 
     /// Read clipboard contents
@@ -166,3 +118,51 @@ impl SmartClipboard {
         Ok(())
     }
 }
+
+/// Simulate Cmd+<key> on macOS or Ctrl+<key> elsewhere
+/// Dispatches to the main thread (required on macOS) and waits for completion
+fn simulate_shortcut(app: &AppHandle, key: char) -> Result<(), String> {
+    // Create channel to wait for main thread operation
+    let (tx, rx) = mpsc::channel();
+
+    #[cfg(target_os = "macos")]
+    let (modifier, modifier_name) = (Key::Meta, "Cmd");
+    #[cfg(not(target_os = "macos"))]
+    let (modifier, modifier_name) = (Key::Control, "Ctrl");
+
+    // Dispatch keyboard operation to main thread (required on macOS)
+    app.run_on_main_thread(move || {
+        let result: Result<(), String> = (|| {
+            let mut enigo = Enigo::new(&Settings::default())
+                .map_err(|e| format!("Failed to create Enigo: {}", e))?;
+
+            println!("[DEBUG] Starting {}+{} keyboard simulation", modifier_name, key.to_ascii_uppercase());
+            enigo
+                .key(modifier, Press)
+                .map_err(|e| format!("Failed to press {}: {}", modifier_name, e))?;
+            enigo
+                .key(Key::Unicode(key), Press)
+                .map_err(|e| format!("Failed to press {}: {}", key.to_ascii_uppercase(), e))?;
+            thread::sleep(Duration::from_millis(10));
+            enigo
+                .key(Key::Unicode(key), Release)
+                .map_err(|e| format!("Failed to release {}: {}", key.to_ascii_uppercase(), e))?;
+            enigo
+                .key(modifier, Release)
+                .map_err(|e| format!("Failed to release {}: {}", modifier_name, e))?;
+            println!("[DEBUG] Completed {}+{} keyboard simulation", modifier_name, key.to_ascii_uppercase());
+
+            thread::sleep(Duration::from_millis(100));
+            Ok(())
+        })();
+
+        let _ = tx.send(result);
+    })
+    .map_err(|e| format!("Failed to dispatch to main thread: {}", e))?;
+
+    // Wait for main thread operation to complete
+    rx.recv()
+        .map_err(|e| format!("Main thread operation failed: {}", e))??;
+
+    Ok(())
+}
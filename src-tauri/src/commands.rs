@@ -1,13 +1,51 @@
 use crate::clipboard_manager::SmartClipboard;
-use crate::translation;
-use crate::config::TranslationConfig;
+use crate::translation::{self, Alternative, Formality, Translation};
+use crate::config::{FormalityConfig, HotkeyConfig, TranslationConfig};
+use crate::dictionary::{self, DictionaryEntry, DictionaryStatus, DictionaryStore};
+use crate::document::Document;
+use crate::export::{self, ExportFormat};
+use crate::furigana::{self, Readings};
+use crate::jlpt::{self, Difficulty};
+use crate::glossary::{Glossary, TermEntry};
+use crate::glossary_sync::{TeamGlossary, TeamGlossaryStatus};
+use crate::grammar::{self, ExplainResult};
+use crate::history::{HistoryEntry, HistoryFilter, HistoryStore};
+use crate::kanjidic::{self, KanjiInfo};
+use crate::language::{self, LanguageConfig};
+use crate::memory::MemoryMatch;
+use crate::mrkdwn;
+use crate::review::{self, ReviewIssue, ReviewResult};
+use crate::placeholders::Placeholders;
+use crate::quantities;
+use crate::segments;
+use crate::verify::{self, BackTranslationCheck};
+use crate::vocabulary::{self, Card, DeckFormat, DueSummary, VocabularyItem, VocabularyStore};
+use crate::anki;
+use crate::chunks;
 use serde::Serialize;
-use std::sync::{Arc, Mutex};
-use tauri::{AppHandle, Emitter, State};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Instant;
+use tauri::{AppHandle, Emitter, Manager, State};
 
 pub struct AppState {
     pub clipboard: Arc<Mutex<SmartClipboard>>,
     pub translation_config: TranslationConfig,
+    pub hotkeys: HotkeyConfig,
+    pub languages: LanguageConfig,
+    pub formality: FormalityConfig,
+    pub last_result: Mutex<Option<TranslationResult>>,
+    /// `None` if the history database could not be opened
+    pub history: Option<HistoryStore>,
+    /// Personal glossary; its terms take precedence over the team's
+    pub glossary: RwLock<Glossary>,
+    /// Where imported glossary terms are saved; `None` keeps them in memory only
+    pub glossary_path: Option<PathBuf>,
+    pub team_glossary: TeamGlossary,
+    /// `None` if the dictionary database could not be opened
+    pub dictionary: Option<DictionaryStore>,
+    /// Personal word list; `None` if its database could not be opened
+    pub vocabulary: Option<VocabularyStore>,
 }
 
 #[derive(Serialize, Clone)]
@@ -16,12 +54,500 @@ pub struct TranslationResult {
     pub translated: String,
     pub source_lang: String,
     pub target_lang: String,
+    /// Language detection confidence, from 0.0 to 1.0
+    pub confidence: f32,
+    /// Detection was uncertain; the overlay asks the user to double-check
+    pub low_confidence: bool,
+    /// Model that produced the translation
+    pub provider: String,
+    /// Application the text was captured from, if known
+    pub source_app: Option<String>,
+    /// Time spent waiting for the translation service
+    pub latency_ms: u64,
+    /// Register requested for Japanese output, if any
+    pub formality: Option<Formality>,
+    /// Glossary terms the translation does not use as required
+    pub glossary_warnings: Vec<String>,
+    /// Numbers, dates, times, amounts and percentages the translation drops or changes
+    pub quantity_warnings: Vec<String>,
+    /// Slack markup and glossary terms the model dropped from the translation
+    pub markup_warnings: Vec<String>,
+    /// Similar earlier translations, for consistency
+    pub memory_matches: Vec<MemoryMatch>,
+    /// Back-translation check, if it has been run
+    pub back_translation: Option<BackTranslationCheck>,
+    /// Furigana and romaji for whichever side is Japanese, if reading annotations are on
+    pub readings: Option<Readings>,
+    /// JLPT level of each word on the Japanese side and how hard it is to read overall
+    pub difficulty: Option<Difficulty>,
+    /// Dictionary entries, when a short term was looked up instead of translated
+    pub dictionary_entries: Vec<DictionaryEntry>,
+    /// Other ways to translate the text, best first, with notes on nuance
+    pub alternatives: Vec<Alternative>,
+    /// Index into `alternatives` of the one the user picked, if any
+    pub chosen_alternative: Option<usize>,
+    /// Row in the history database, once recorded
+    pub history_id: Option<i64>,
+}
+
+/// Provider recorded for translations reused from translation memory
+pub const MEMORY_PROVIDER: &str = "translation-memory";
+
+/// Provider recorded for short terms answered from the offline dictionary
+pub const DICTIONARY_PROVIDER: &str = "jmdict";
+
+/// Fuzzy translation memory matches shown with a translation
+const MAX_MEMORY_MATCHES: usize = 3;
+
+/// Dictionary entries shown for a looked-up term
+const MAX_DICTIONARY_ENTRIES: usize = 5;
+
+/// Cards handed to a practice session at a time
+const MAX_DUE_CARDS: usize = 50;
+
+/// Options for a single run of the translation workflow
+#[derive(Default, Clone)]
+pub struct TranslateOptions {
+    /// Paste the translation over the selection instead of only showing the overlay
+    pub replace: bool,
+    /// Treat the text as this language instead of detecting it
+    pub source_lang: Option<String>,
+    /// Translate into this language instead of the configured default
+    pub target_lang: Option<String>,
+    /// Formality for Japanese output instead of the configured default
+    pub formality: Option<Formality>,
+}
+
+/// Chunks of a long translation finished so far, for the overlay
+#[derive(Serialize, Clone)]
+pub struct TranslationProgress {
+    pub done: usize,
+    pub total: usize,
+}
+
+/// Home and working languages, for the overlay's language pickers
+#[derive(Serialize)]
+pub struct LanguageSettings {
+    pub home_lang: String,
+    pub working_langs: Vec<String>,
 }
 
 #[tauri::command]
 pub async fn translate_selection(
     app: AppHandle,
     state: State<'_, AppState>,
+) -> Result<TranslationResult, String> {
+    run_translation(app, state, TranslateOptions::default()).await
+}
+
+/// Translate text the app already has (e.g. the last captured selection)
+/// with an explicit direction, without touching the clipboard
+#[tauri::command]
+pub async fn translate_text(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    text: String,
+    source_lang: Option<String>,
+    target_lang: Option<String>,
+    formality: Option<Formality>,
+) -> Result<TranslationResult, String> {
+    if text.trim().is_empty() {
+        return Err("No text to translate".to_string());
+    }
+
+    let options = TranslateOptions {
+        source_lang: normalize_option(source_lang)?,
+        target_lang: normalize_option(target_lang)?,
+        formality,
+        ..Default::default()
+    };
+
+    app.emit("translation-loading", true)
+        .map_err(|e| format!("Failed to emit loading event: {}", e))?;
+
+    let result = translate_captured(&app, &state, text, &options).await?;
+    publish_result(&app, &state, result)
+}
+
+#[tauri::command]
+pub fn get_language_settings(state: State<'_, AppState>) -> LanguageSettings {
+    LanguageSettings {
+        home_lang: state.languages.home_lang.clone(),
+        working_langs: state.languages.working_langs.clone(),
+    }
+}
+
+#[tauri::command]
+pub fn list_history(
+    state: State<'_, AppState>,
+    limit: Option<usize>,
+    offset: Option<usize>,
+) -> Result<Vec<HistoryEntry>, String> {
+    history_store(&state)?.list(limit.unwrap_or(50), offset.unwrap_or(0))
+}
+
+#[tauri::command]
+pub fn search_history(
+    state: State<'_, AppState>,
+    query: String,
+    limit: Option<usize>,
+) -> Result<Vec<HistoryEntry>, String> {
+    history_store(&state)?.search(&query, limit.unwrap_or(50))
+}
+
+#[tauri::command]
+pub fn delete_history_entry(state: State<'_, AppState>, id: i64) -> Result<bool, String> {
+    history_store(&state)?.delete(id)
+}
+
+#[tauri::command]
+pub fn clear_history(state: State<'_, AppState>) -> Result<usize, String> {
+    history_store(&state)?.clear()
+}
+
+/// Where an export was written and how many entries it contains
+#[derive(Serialize)]
+pub struct ExportSummary {
+    pub path: String,
+    pub count: usize,
+}
+
+/// Export history matching the date range and language pair. Writes to `path`,
+/// or to the Downloads folder if no path is given.
+#[tauri::command]
+pub fn export_history(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    format: ExportFormat,
+    path: Option<String>,
+    filter: HistoryFilter,
+) -> Result<ExportSummary, String> {
+    let filter = HistoryFilter {
+        source_lang: normalize_option(filter.source_lang)?,
+        target_lang: normalize_option(filter.target_lang)?,
+        ..filter
+    };
+    let entries = history_store(&state)?.filtered(&filter)?;
+    let contents = export::render(&entries, format, filter.source_lang.as_deref())?;
+
+    let path = match path.filter(|path| !path.trim().is_empty()) {
+        Some(path) => std::path::PathBuf::from(path),
+        None => {
+            let date = &export::format_timestamp(crate::history::now_millis(), true)[..8];
+            app.path()
+                .download_dir()
+                .map_err(|e| format!("Failed to resolve Downloads folder: {}", e))?
+                .join(format!("translation-history-{}.{}", date, format.extension()))
+        }
+    };
+
+    std::fs::write(&path, contents).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    println!("[INFO] Exported {} history entries to {}", entries.len(), path.display());
+
+    Ok(ExportSummary {
+        path: path.display().to_string(),
+        count: entries.len(),
+    })
+}
+
+/// Import glossary terms from a CSV or TBX file, returning how many were read.
+/// Imported terms replace existing entries for the same term and language pair.
+#[tauri::command]
+pub fn import_glossary(state: State<'_, AppState>, path: String) -> Result<usize, String> {
+    let imported = Glossary::import_file(std::path::Path::new(&path))?;
+    let count = imported.terms.len();
+
+    let mut glossary = state
+        .glossary
+        .write()
+        .map_err(|e| format!("Failed to lock glossary: {}", e))?;
+    glossary.merge(imported);
+    if let Some(glossary_path) = &state.glossary_path {
+        glossary.save(glossary_path)?;
+    }
+
+    println!("[INFO] Imported {} glossary terms from {}", count, path);
+    Ok(count)
+}
+
+#[tauri::command]
+pub fn list_glossary(state: State<'_, AppState>) -> Result<Vec<TermEntry>, String> {
+    let glossary = state
+        .glossary
+        .read()
+        .map_err(|e| format!("Failed to lock glossary: {}", e))?;
+    Ok(glossary.terms.clone())
+}
+
+#[tauri::command]
+pub fn clear_glossary(state: State<'_, AppState>) -> Result<(), String> {
+    let mut glossary = state
+        .glossary
+        .write()
+        .map_err(|e| format!("Failed to lock glossary: {}", e))?;
+    *glossary = Glossary::default();
+    match &state.glossary_path {
+        Some(path) => glossary.save(path),
+        None => Ok(()),
+    }
+}
+
+#[tauri::command]
+pub fn get_team_glossary_status(state: State<'_, AppState>) -> TeamGlossaryStatus {
+    state.team_glossary.status()
+}
+
+#[tauri::command]
+pub fn get_dictionary_status(state: State<'_, AppState>) -> Option<DictionaryStatus> {
+    state.dictionary.as_ref().map(DictionaryStore::status)
+}
+
+/// Download and import the dictionary again, e.g. for a newer JMdict release
+#[tauri::command]
+pub async fn update_dictionary(state: State<'_, AppState>) -> Result<DictionaryStatus, String> {
+    let dictionary = state
+        .dictionary
+        .as_ref()
+        .ok_or_else(|| "The dictionary database is not available".to_string())?;
+    dictionary.import(false).await?;
+    Ok(dictionary.status())
+}
+
+/// KANJIDIC2 readings, meanings, stroke count, JLPT level and radicals for each
+/// kanji in the text, in order of appearance. Kanji missing from KANJIDIC2 are skipped.
+#[tauri::command]
+pub fn get_kanji_breakdown(state: State<'_, AppState>, text: String) -> Result<Vec<KanjiInfo>, String> {
+    let dictionary = state
+        .dictionary
+        .as_ref()
+        .ok_or_else(|| "The dictionary database is not available".to_string())?;
+    if dictionary.status().kanji == 0 {
+        return Err("The kanji dictionary (KANJIDIC2) has not been imported yet".to_string());
+    }
+
+    let mut breakdown = Vec::new();
+    for literal in kanjidic::kanji_in(&text) {
+        if let Some(info) = dictionary.kanji(literal)? {
+            breakdown.push(info);
+        }
+    }
+    Ok(breakdown)
+}
+
+/// Check the team glossary source for changes now
+#[tauri::command]
+pub async fn sync_team_glossary(state: State<'_, AppState>) -> Result<TeamGlossaryStatus, String> {
+    state.team_glossary.sync().await?;
+    Ok(state.team_glossary.status())
+}
+
+/// Team terms with personal terms merged over them
+fn effective_glossary(state: &AppState) -> Result<Glossary, String> {
+    let mut glossary = state.team_glossary.glossary();
+    let personal = state
+        .glossary
+        .read()
+        .map_err(|e| format!("Failed to lock glossary: {}", e))?
+        .clone();
+    glossary.merge(personal);
+    Ok(glossary)
+}
+
+/// Save notable words from the Japanese side of the last translation to the
+/// word list, returning the words that were new
+#[tauri::command]
+pub fn extract_vocabulary(state: State<'_, AppState>) -> Result<Vec<VocabularyItem>, String> {
+    let last = state
+        .last_result
+        .lock()
+        .map_err(|e| format!("Failed to lock last result: {}", e))?
+        .clone()
+        .ok_or_else(|| "No translation to take words from".to_string())?;
+    let (japanese, other) = if language::primary_subtag(&last.source_lang) == "ja" {
+        (&last.original, &last.translated)
+    } else if language::primary_subtag(&last.target_lang) == "ja" {
+        (&last.translated, &last.original)
+    } else {
+        return Err("The last translation has no Japanese text".to_string());
+    };
+
+    let words = vocabulary::extract(japanese, state.dictionary.as_ref())?;
+    let added = vocabulary_store(&state)?.add(&words, other)?;
+    println!("[INFO] Added {} of {} words to the word list", added.len(), words.len());
+    Ok(added)
+}
+
+#[tauri::command]
+pub fn list_vocabulary(state: State<'_, AppState>) -> Result<Vec<VocabularyItem>, String> {
+    vocabulary_store(&state)?.list()
+}
+
+#[tauri::command]
+pub fn delete_vocabulary_word(state: State<'_, AppState>, id: i64) -> Result<bool, String> {
+    vocabulary_store(&state)?.delete(id)
+}
+
+/// Export the word list as an Anki deck. Writes to `path`, or to the
+/// Downloads folder if no path is given.
+#[tauri::command]
+pub fn export_vocabulary(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    format: DeckFormat,
+    path: Option<String>,
+) -> Result<ExportSummary, String> {
+    let items = vocabulary_store(&state)?.list()?;
+    if items.is_empty() {
+        return Err("The word list is empty".to_string());
+    }
+
+    let path = match path.filter(|path| !path.trim().is_empty()) {
+        Some(path) => std::path::PathBuf::from(path),
+        None => {
+            let date = &export::format_timestamp(crate::history::now_millis(), true)[..8];
+            app.path()
+                .download_dir()
+                .map_err(|e| format!("Failed to resolve Downloads folder: {}", e))?
+                .join(format!("vocabulary-{}.{}", date, format.extension()))
+        }
+    };
+
+    match format {
+        DeckFormat::Apkg => anki::write_apkg(&items, &path)?,
+        DeckFormat::Tsv => std::fs::write(&path, anki::to_tsv(&items))
+            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?,
+    }
+    println!("[INFO] Exported {} words to {}", items.len(), path.display());
+
+    Ok(ExportSummary {
+        path: path.display().to_string(),
+        count: items.len(),
+    })
+}
+
+/// Words from the word list that are due for practice
+#[tauri::command]
+pub fn get_due_cards(state: State<'_, AppState>, limit: Option<usize>) -> Result<Vec<Card>, String> {
+    vocabulary_store(&state)?.due_cards(crate::history::now_millis(), limit.unwrap_or(MAX_DUE_CARDS))
+}
+
+#[tauri::command]
+pub fn get_due_summary(state: State<'_, AppState>) -> Result<DueSummary, String> {
+    vocabulary_store(&state)?.due_summary(crate::history::now_millis())
+}
+
+/// Record how well a word was remembered, from 0 (forgotten) to 5 (perfect),
+/// and schedule its next review
+#[tauri::command]
+pub fn grade_card(state: State<'_, AppState>, id: i64, grade: u8) -> Result<Card, String> {
+    let card = vocabulary_store(&state)?.grade(id, grade, crate::history::now_millis())?;
+    println!(
+        "[DEBUG] Graded '{}' {} - next review in {} day(s)",
+        card.item.word, grade, card.schedule.interval_days
+    );
+    Ok(card)
+}
+
+fn vocabulary_store(state: &AppState) -> Result<&VocabularyStore, String> {
+    state
+        .vocabulary
+        .as_ref()
+        .ok_or_else(|| "The word list is unavailable".to_string())
+}
+
+fn history_store(state: &AppState) -> Result<&HistoryStore, String> {
+    state
+        .history
+        .as_ref()
+        .ok_or_else(|| "Translation history is unavailable".to_string())
+}
+
+#[tauri::command]
+pub fn get_last_result(state: State<'_, AppState>) -> Result<Option<TranslationResult>, String> {
+    let last_result = state
+        .last_result
+        .lock()
+        .map_err(|e| format!("Failed to lock last result: {}", e))?;
+    Ok(last_result.clone())
+}
+
+/// Furigana and romaji for Japanese text, when the overlay asks for them
+#[tauri::command]
+pub fn get_readings(text: String) -> Result<Readings, String> {
+    furigana::annotate(&text)
+}
+
+/// Log the alternative translation the user picked in the overlay to history, and
+/// make it the translation memory's translation of this text
+#[tauri::command]
+pub fn choose_alternative(state: State<'_, AppState>, index: usize) -> Result<(), String> {
+    let mut last_result = state
+        .last_result
+        .lock()
+        .map_err(|e| format!("Failed to lock last result: {}", e))?;
+    let last = last_result
+        .as_mut()
+        .ok_or_else(|| "No translation to choose an alternative for".to_string())?;
+    let alternative = last
+        .alternatives
+        .get(index)
+        .ok_or_else(|| format!("No alternative translation {}", index))?;
+
+    println!("[DEBUG] Chose {} alternative: '{}'", alternative.style, alternative.text);
+    if let Some(history) = &state.history {
+        if let Some(id) = last.history_id {
+            history.choose_alternative(id, &alternative.style, &alternative.text)?;
+        }
+        history.remember(
+            &last.source_lang,
+            &last.target_lang,
+            last.formality,
+            &last.original,
+            &alternative.text,
+        )?;
+    }
+    last.chosen_alternative = Some(index);
+    Ok(())
+}
+
+/// Run the back-translation check on the last result, on request from the overlay
+#[tauri::command]
+pub async fn verify_translation(state: State<'_, AppState>) -> Result<BackTranslationCheck, String> {
+    let last = state
+        .last_result
+        .lock()
+        .map_err(|e| format!("Failed to lock last result: {}", e))?
+        .clone()
+        .ok_or_else(|| "No translation to verify".to_string())?;
+
+    let check = back_translate(
+        &state,
+        &last.original,
+        &last.translated,
+        &last.source_lang,
+        &last.target_lang,
+        last.formality,
+    )
+    .await?;
+
+    // Keep the check with the result so "show last result" includes it
+    let mut last_result = state
+        .last_result
+        .lock()
+        .map_err(|e| format!("Failed to lock last result: {}", e))?;
+    if let Some(result) = last_result.as_mut().filter(|result| result.original == last.original) {
+        result.back_translation = Some(check.clone());
+    }
+
+    Ok(check)
+}
+
+/// Copy the selection, translate it and show the result in the overlay
+/// Shared by the `translate_selection` command, hotkeys and the tray menu
+pub async fn run_translation(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    mut options: TranslateOptions,
 ) -> Result<TranslationResult, String> {
     println!("[DEBUG] ===== Translation workflow started =====");
 
@@ -29,7 +555,64 @@ pub async fn translate_selection(
     app.emit("translation-loading", true)
         .map_err(|e| format!("Failed to emit loading event: {}", e))?;
 
+    // Remember where the selection came from before the overlay takes focus
+    let window = active_window();
+    let source_app = window.as_ref().map(|window| window.app_name.clone());
+
+    // Per-recipient formality, matched against the app name and window title
+    if options.formality.is_none() {
+        let window_desc = window
+            .as_ref()
+            .map(|window| format!("{} {}", window.app_name, window.title));
+        options.formality = state.formality.for_window(window_desc.as_deref());
+    }
+
     // Step 1-4: Copy text from user selection
+    let selected_text = capture_selection(&app, &state)?;
+
+    // Step 5-6: Detect language and translate (async operation - no lock held)
+    let mut result = translate_captured(&app, &state, selected_text, &options)
+        .await
+        .inspect_err(|_| {
+            let clipboard = state.clipboard.lock().ok();
+            if let Some(cb) = clipboard {
+                cb.restore_clipboard(&app).ok();
+            }
+        })?;
+    result.source_app = source_app;
+
+    // Step 7-8: Paste over the selection if requested, then restore clipboard
+    {
+        let mut clipboard = state
+            .clipboard
+            .lock()
+            .map_err(|e| format!("Failed to lock clipboard: {}", e))?;
+
+        if options.replace {
+            println!("[DEBUG] Replace mode - pasting translation over selection");
+            let paste_result = clipboard.paste_text(&app, &result.translated);
+            if let Err(e) = paste_result {
+                clipboard.restore_clipboard(&app).ok();
+                return Err(format!("Failed to paste translation: {}", e));
+            }
+        } else {
+            println!("[DEBUG] Overlay-only mode - restoring original clipboard");
+        }
+
+        // Restore original clipboard
+        clipboard
+            .restore_clipboard(&app)
+            .map_err(|e| format!("Failed to restore clipboard: {}", e))?;
+    } // Lock is released here
+
+    let result = publish_result(&app, &state, result)?;
+    println!("[DEBUG] ===== Translation workflow completed successfully =====");
+    Ok(result)
+}
+
+/// Save the clipboard and copy the current selection. The caller restores the
+/// clipboard when done; it is restored here if nothing was selected.
+fn capture_selection(app: &AppHandle, state: &AppState) -> Result<String, String> {
     let selected_text = {
         let mut clipboard = state
             .clipboard
@@ -39,12 +622,12 @@ pub async fn translate_selection(
         // Save current clipboard
         println!("[DEBUG] Saving current clipboard");
         clipboard
-            .save_clipboard(&app)
+            .save_clipboard(app)
             .map_err(|e| format!("Failed to save clipboard: {}", e))?;
 
         // Try to copy selected text
         let copy_result = clipboard
-            .copy_with_fallback(&app)
+            .copy_with_fallback(app)
             .map_err(|e| format!("Failed to copy text: {}", e))?;
 
         println!("[DEBUG] Copy result: text='{}'", copy_result.text);
@@ -56,57 +639,602 @@ pub async fn translate_selection(
 
     if selected_text.trim().is_empty() {
         let clipboard = state.clipboard.lock().map_err(|e| format!("Failed to lock clipboard: {}", e))?;
-        clipboard.restore_clipboard(&app).ok();
+        clipboard.restore_clipboard(app).ok();
         return Err("No text selected".to_string());
     }
 
-    // Step 5: Detect language and get target
-    let source_lang = translation::detect_language(&selected_text);
-    let target_lang = translation::get_target_language(source_lang);
-    println!("[DEBUG] Detected language: {} -> {}", source_lang, target_lang);
+    Ok(selected_text)
+}
+
+/// Copy the current selection and restore the clipboard straight away, for
+/// actions that only read the selection
+fn read_selection(app: &AppHandle, state: &AppState) -> Result<String, String> {
+    let selected_text = capture_selection(app, state)?;
+    let clipboard = state
+        .clipboard
+        .lock()
+        .map_err(|e| format!("Failed to lock clipboard: {}", e))?;
+    clipboard
+        .restore_clipboard(app)
+        .map_err(|e| format!("Failed to restore clipboard: {}", e))?;
+    Ok(selected_text)
+}
 
-    // Step 6: Translate (async operation - no lock held)
-    println!("[DEBUG] Calling translation API");
-    let translation_text = translation::translate(&selected_text, target_lang, &state.translation_config)
-        .await
-        .map_err(|e| {
-            let clipboard = state.clipboard.lock().ok();
-            if let Some(cb) = clipboard {
-                cb.restore_clipboard(&app).ok();
+/// Review the selected Japanese draft and show the assessment in the overlay
+pub async fn run_review(app: AppHandle, state: State<'_, AppState>) -> Result<ReviewResult, String> {
+    app.emit("translation-loading", true)
+        .map_err(|e| format!("Failed to emit loading event: {}", e))?;
+
+    let selected_text = read_selection(&app, &state)?;
+
+    review_text(app, state, selected_text).await
+}
+
+/// Assess the politeness and phrasing of a Japanese draft, with a back-translation
+#[tauri::command]
+pub async fn review_text(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    text: String,
+) -> Result<ReviewResult, String> {
+    if text.trim().is_empty() {
+        return Err("No text to review".to_string());
+    }
+
+    let detection = language::detect_language(&text, &state.languages.preferred());
+    if language::primary_subtag(&detection.lang) != "ja" {
+        return Err(format!(
+            "Review works on Japanese drafts; this looks like {}",
+            detection.lang
+        ));
+    }
+
+    let home_lang = state.languages.home_lang.clone();
+    // A Japanese-speaking user checks the draft against their working language instead
+    let back_lang = state.languages.target_for("ja");
+    println!("[DEBUG] Reviewing Japanese draft ({} chars)", text.chars().count());
+
+    // The service review and the back-translation are independent requests
+    let (service_review, back_translation) = futures::join!(
+        translation::review(&text, &home_lang, &state.translation_config),
+        translation::translate(&text, Some("ja"), &back_lang, None, &state.translation_config),
+    );
+    let back_translation = back_translation.map_err(|e| format!("Back-translation failed: {}", e))?;
+
+    // Fall back to the built-in register check if the Worker cannot review
+    let (politeness, register_issues) = review::assess(&text);
+    let mut flagged: Vec<(Option<std::ops::Range<usize>>, ReviewIssue)> = Vec::new();
+    let (rewrite, provider) = match service_review {
+        Ok(service_review) => {
+            let mut taken = Vec::new();
+            for note in service_review.notes {
+                let range = review::locate(&text, &note.text, &taken);
+                if let Some(range) = &range {
+                    taken.push(range.clone());
+                }
+                flagged.push((
+                    range,
+                    ReviewIssue {
+                        problem: note.problem,
+                        suggestion: note.suggestion,
+                    },
+                ));
             }
-            format!("Translation failed: {}", e)
-        })?;
+            (service_review.rewrite, service_review.model)
+        }
+        Err(e) => {
+            eprintln!("⚠ Draft review unavailable, using built-in checks only: {}", e);
+            (None, "local".to_string())
+        }
+    };
+    flagged.extend(register_issues.into_iter().map(|(range, issue)| (Some(range), issue)));
 
-    println!("[DEBUG] Translation result: '{}'", translation_text);
+    let (spans, issues) = review::annotate(&text, flagged);
+    let result = ReviewResult {
+        original: text,
+        politeness,
+        spans,
+        issues,
+        rewrite,
+        back_translation: back_translation.text,
+        back_translation_lang: back_lang,
+        provider,
+    };
 
-    // Step 7-8: Restore clipboard (overlay-only mode, no paste)
+    println!("[DEBUG] Emitting show-review event to frontend");
+    app.emit("show-review", result.clone())
+        .map_err(|e| format!("Failed to emit event: {}", e))?;
+
+    Ok(result)
+}
+
+/// Explain the grammar of the selected Japanese sentence in the overlay
+pub async fn run_explain(app: AppHandle, state: State<'_, AppState>) -> Result<ExplainResult, String> {
+    app.emit("translation-loading", true)
+        .map_err(|e| format!("Failed to emit loading event: {}", e))?;
+
+    let selected_text = read_selection(&app, &state)?;
+
+    explain_text(app, state, selected_text).await
+}
+
+/// Break a Japanese sentence into phrases, particles and conjugations, with a
+/// literal and a natural translation into the home language
+#[tauri::command]
+pub async fn explain_text(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    text: String,
+) -> Result<ExplainResult, String> {
+    let text = text.trim().to_string();
+    if text.is_empty() {
+        return Err("No text to explain".to_string());
+    }
+
+    let detection = language::detect_language(&text, &state.languages.preferred());
+    if language::primary_subtag(&detection.lang) != "ja" {
+        return Err(format!(
+            "Grammar explanation works on Japanese text; this looks like {}",
+            detection.lang
+        ));
+    }
+
+    let explain_lang = state.languages.home_lang.clone();
+    println!("[DEBUG] Explaining Japanese sentence ({} chars)", text.chars().count());
+
+    let explanation = translation::explain(&text, &explain_lang, &state.translation_config).await?;
+    let misaligned = grammar::misaligned_phrases(&text, &explanation);
+    if !misaligned.is_empty() {
+        eprintln!("⚠ Explanation phrases not found in the sentence: {}", misaligned.join(", "));
+    }
+
+    let result = ExplainResult {
+        original: text,
+        explain_lang,
+        explanation,
+    };
+
+    println!("[DEBUG] Emitting show-explanation event to frontend");
+    app.emit("show-explanation", result.clone())
+        .map_err(|e| format!("Failed to emit event: {}", e))?;
+
+    Ok(result)
+}
+
+/// Detect the direction (unless overridden) and translate already-captured text
+pub async fn translate_captured(
+    app: &AppHandle,
+    state: &AppState,
+    text: String,
+    options: &TranslateOptions,
+) -> Result<TranslationResult, String> {
+    let preferred = state.languages.preferred();
+    let detection = match &options.source_lang {
+        Some(lang) => language::Detection {
+            lang: lang.clone(),
+            confidence: 1.0,
+        },
+        None => language::detect_language(&text, &preferred),
+    };
+    let source_lang = detection.lang.clone();
+
+    // Mixed-language text is translated per segment into the home language,
+    // unless the user has said what language the text is in
+    let segments = match options.source_lang {
+        Some(_) => Vec::new(),
+        None => segments::split_segments(&text, &preferred),
+    };
+    let multilingual = segments::is_multilingual(&segments);
+    let target_lang = options.target_lang.clone().unwrap_or_else(|| {
+        if multilingual {
+            state.languages.home_lang.clone()
+        } else {
+            state.languages.target_for(&source_lang)
+        }
+    });
+    println!(
+        "[DEBUG] Detected language: {} ({:.2}) -> {}",
+        source_lang, detection.confidence, target_lang
+    );
+
+    let formality = options
+        .formality
+        .or(state.formality.default)
+        .filter(|_| Formality::applies_to(&target_lang));
+
+    // A single Japanese word needs a dictionary, not a translation round-trip
+    if !multilingual && language::primary_subtag(&source_lang) == "ja" && language::primary_subtag(&target_lang) == "en" {
+        if let Some(entries) = look_up_term(state, &text) {
+            println!("[DEBUG] Found {} dictionary entries - skipping translation API", entries.len());
+            return Ok(TranslationResult {
+                translated: entries[0].summary(),
+                original: text,
+                source_lang,
+                target_lang,
+                confidence: detection.confidence,
+                low_confidence: detection.is_low_confidence(),
+                provider: DICTIONARY_PROVIDER.to_string(),
+                source_app: None,
+                latency_ms: 0,
+                formality: None,
+                glossary_warnings: Vec::new(),
+                quantity_warnings: Vec::new(),
+                markup_warnings: Vec::new(),
+                memory_matches: Vec::new(),
+                back_translation: None,
+                readings: None,
+                difficulty: None,
+                dictionary_entries: entries,
+                alternatives: Vec::new(),
+                chosen_alternative: None,
+                history_id: None,
+            });
+        }
+    }
+
+    // Reuse an earlier translation of exactly this text; near matches are shown alongside
+    let mut memory_matches = match &state.history {
+        Some(history) => history
+            .recall(&text, &source_lang, &target_lang, formality, MAX_MEMORY_MATCHES)
+            .unwrap_or_else(|e| {
+                eprintln!("⚠ {}", e);
+                Vec::new()
+            }),
+        None => Vec::new(),
+    };
+    let exact_match = memory_matches
+        .first()
+        .filter(|m| m.score >= 1.0)
+        .map(|m| m.target.clone());
+
+    let glossary = effective_glossary(state)?;
+    let started = Instant::now();
+    let (translation, dropped) = match exact_match {
+        Some(target) => {
+            println!("[DEBUG] Exact translation memory match - skipping translation API");
+            memory_matches.clear();
+            let translation = Translation {
+                text: target,
+                model: MEMORY_PROVIDER.to_string(),
+                alternatives: Vec::new(),
+            };
+            (translation, Vec::new())
+        }
+        None => {
+            let request = GlossaryRequest {
+                text: &text,
+                segments,
+                source_lang: &source_lang,
+                target_lang: &target_lang,
+                formality,
+            };
+            translate_with_glossary(app, state, request, &glossary).await?
+        }
+    };
+    let latency_ms = started.elapsed().as_millis() as u64;
+
+    let glossary_warnings = glossary.check(&text, &translation.text, &source_lang, &target_lang);
+    let quantity_warnings = quantities::check(&text, &translation.text);
+    let markup_warnings: Vec<String> = dropped
+        .iter()
+        .map(|span| format!("{} is missing from the translation", span))
+        .collect();
+    for warning in glossary_warnings.iter().chain(&quantity_warnings).chain(&markup_warnings) {
+        eprintln!("⚠ {}", warning);
+    }
+
+    println!("[DEBUG] Translation result: '{}' ({} ms)", translation.text, latency_ms);
+
+    // Mixed-language text has no single source language to translate back into
+    let back_translation = if state.translation_config.back_translation_check
+        && translation.model != MEMORY_PROVIDER
+        && !multilingual
     {
-        let clipboard = state
-            .clipboard
-            .lock()
-            .map_err(|e| format!("Failed to lock clipboard: {}", e))?;
+        back_translate(state, &text, &translation.text, &source_lang, &target_lang, formality)
+            .await
+            .inspect_err(|e| eprintln!("⚠ {}", e))
+            .ok()
+    } else {
+        None
+    };
 
-        println!("[DEBUG] Overlay-only mode - restoring original clipboard");
+    let readings = if state.translation_config.reading_annotations {
+        japanese_side(&text, &translation.text, &source_lang, &target_lang)
+            .map(|japanese| furigana::annotate(japanese).inspect_err(|e| eprintln!("⚠ {}", e)))
+            .and_then(Result::ok)
+    } else {
+        None
+    };
 
-        // Restore original clipboard
-        clipboard
-            .restore_clipboard(&app)
-            .map_err(|e| format!("Failed to restore clipboard: {}", e))?;
-    } // Lock is released here
+    let difficulty = japanese_side(&text, &translation.text, &source_lang, &target_lang)
+        .map(|japanese| jlpt::assess(japanese, state.dictionary.as_ref()).inspect_err(|e| eprintln!("⚠ {}", e)))
+        .and_then(Result::ok);
+
+    Ok(TranslationResult {
+        original: text,
+        translated: translation.text,
+        source_lang,
+        target_lang,
+        confidence: detection.confidence,
+        low_confidence: detection.is_low_confidence(),
+        provider: translation.model,
+        source_app: None,
+        latency_ms,
+        formality,
+        glossary_warnings,
+        quantity_warnings,
+        markup_warnings,
+        memory_matches,
+        back_translation,
+        readings,
+        difficulty,
+        dictionary_entries: Vec::new(),
+        alternatives: translation.alternatives,
+        chosen_alternative: None,
+        history_id: None,
+    })
+}
 
-    // Step 10: Create result
-    let result = TranslationResult {
-        original: selected_text,
-        translated: translation_text.clone(),
-        source_lang: source_lang.to_string(),
-        target_lang: target_lang.to_string(),
+/// Dictionary entries for a short term, trying its dictionary form if it is
+/// inflected (行きました → 行く). `None` for sentences or unknown words.
+fn look_up_term(state: &AppState, text: &str) -> Option<Vec<DictionaryEntry>> {
+    let dictionary = state.dictionary.as_ref()?;
+    if !dictionary::is_short_term(text) {
+        return None;
+    }
+
+    let lookup = |term: &str| {
+        dictionary
+            .lookup(term, MAX_DICTIONARY_ENTRIES)
+            .inspect_err(|e| eprintln!("⚠ {}", e))
+            .ok()
+            .filter(|entries| !entries.is_empty())
     };
+    lookup(text.trim()).or_else(|| {
+        let base = furigana::dictionary_form(text).inspect_err(|e| eprintln!("⚠ {}", e)).ok()??;
+        lookup(&base)
+    })
+}
+
+/// The Japanese text of a translation pair: the original if it is Japanese, else the translation
+fn japanese_side<'a>(original: &'a str, translated: &'a str, source_lang: &str, target_lang: &str) -> Option<&'a str> {
+    if language::primary_subtag(source_lang) == "ja" {
+        Some(original)
+    } else if language::primary_subtag(target_lang) == "ja" {
+        Some(translated)
+    } else {
+        None
+    }
+}
+
+/// Translate a translation back into the source language and compare it with the original
+async fn back_translate(
+    state: &AppState,
+    original: &str,
+    translated: &str,
+    source_lang: &str,
+    target_lang: &str,
+    formality: Option<Formality>,
+) -> Result<BackTranslationCheck, String> {
+    println!("[DEBUG] Calling translation API for back-translation into {}", source_lang);
+    let formality = formality.filter(|_| Formality::applies_to(source_lang));
+    let back = translation::translate(
+        translated,
+        Some(target_lang),
+        source_lang,
+        formality,
+        &state.translation_config,
+    )
+    .await
+    .map_err(|e| format!("Back-translation failed: {}", e))?;
+
+    let check = verify::compare(original, &back.text, source_lang);
+    println!(
+        "[DEBUG] Back-translation similarity: {:.2}, {} issue(s)",
+        check.similarity,
+        check.issues.len()
+    );
+    Ok(check)
+}
+
+/// Text for `translate_with_glossary`, and the languages and register to translate it with
+struct GlossaryRequest<'a> {
+    text: &'a str,
+    /// Language segments of mixed-language text; translated one by one if there are several
+    segments: Vec<segments::Segment>,
+    source_lang: &'a str,
+    target_lang: &'a str,
+    formality: Option<Formality>,
+}
+
+/// Call the translation service with glossary terms and Slack markup swapped
+/// for placeholders, so the model cannot rephrase them, and restore them
+/// afterwards. Markdown and HTML keep their structure: only text nodes are translated.
+/// Long text is chunked on every path; plain text shows its progress in the
+/// overlay. Also returns the protected spans the model dropped.
+async fn translate_with_glossary(
+    app: &AppHandle,
+    state: &AppState,
+    request: GlossaryRequest<'_>,
+    glossary: &Glossary,
+) -> Result<(Translation, Vec<String>), String> {
+    let GlossaryRequest {
+        text,
+        segments,
+        source_lang,
+        target_lang,
+        formality,
+    } = request;
+    let mut placeholders = Placeholders::new();
+
+    // Markdown and HTML: translate the text nodes and keep the structure
+    if let Some(document) = Document::parse(text) {
+        let texts: Vec<String> = document
+            .texts(&mut placeholders)
+            .iter()
+            .map(|node| {
+                let shielded = mrkdwn::shield(node, &mut placeholders);
+                glossary.protect(&shielded, source_lang, target_lang, &mut placeholders)
+            })
+            .collect();
+        println!("[DEBUG] Calling translation API for {} text nodes", texts.len());
+        let (translations, model) = translation::translate_batch(
+            &texts,
+            Some(source_lang),
+            target_lang,
+            formality,
+            &state.translation_config,
+        )
+        .await
+        .map_err(|e| format!("Translation failed: {}", e))?;
+
+        let assembled = document.assemble(&translations)?;
+        let (restored, missing) = placeholders.restore(&assembled);
+        let translation = Translation {
+            text: restored,
+            model,
+            alternatives: Vec::new(),
+        };
+        return Ok((translation, missing));
+    }
+
+    let translation_result = if segments::needs_segmenting(&segments) {
+        let segments: Vec<_> = segments
+            .into_iter()
+            .map(|segment| protect_segment(segment, glossary, target_lang, &mut placeholders))
+            .collect();
+        println!("[DEBUG] Calling translation API for {} segments", segments.len());
+        translation::translate_segments(&segments, target_lang, formality, &state.translation_config).await
+    } else {
+        let shielded = mrkdwn::shield(text, &mut placeholders);
+        let protected = glossary.protect(&shielded, source_lang, target_lang, &mut placeholders);
+        if chunks::needs_chunking(&protected) {
+            let chunks = chunks::split(&protected, chunks::MAX_CHUNK_CHARS);
+            println!("[DEBUG] Calling translation API for {} chunks", chunks.len());
+            translation::translate_chunks(
+                &chunks,
+                Some(source_lang),
+                target_lang,
+                formality,
+                &state.translation_config,
+                |done, total| {
+                    if let Err(e) = app.emit("translation-progress", TranslationProgress { done, total }) {
+                        eprintln!("⚠ Failed to emit progress event: {}", e);
+                    }
+                },
+            )
+            .await
+        } else {
+            println!("[DEBUG] Calling translation API");
+            translation::translate_with_alternatives(
+                &protected,
+                Some(source_lang),
+                target_lang,
+                formality,
+                state.translation_config.alternatives,
+                &state.translation_config,
+            )
+            .await
+        }
+    };
+    let mut translation = translation_result.map_err(|e| format!("Translation failed: {}", e))?;
+
+    let (restored, missing) = placeholders.restore(&translation.text);
+    translation.text = restored;
+
+    // An alternative that lost a glossary term or markup is not worth offering
+    translation.alternatives.retain_mut(|alternative| {
+        let (restored, missing) = placeholders.restore(&alternative.text);
+        if !missing.is_empty() {
+            eprintln!("⚠ Dropping alternative that lost protected span(s): {:?}", missing);
+            return false;
+        }
+        alternative.text = restored;
+        true
+    });
+    Ok((translation, missing))
+}
+
+/// Protect Slack markup and glossary terms within one segment, using the segment's own language
+fn protect_segment(
+    segment: segments::Segment,
+    glossary: &Glossary,
+    target_lang: &str,
+    placeholders: &mut Placeholders,
+) -> segments::Segment {
+    match segment {
+        segments::Segment::Text { text, lang } => segments::Segment::Text {
+            text: glossary.protect(&mrkdwn::shield(&text, placeholders), &lang, target_lang, placeholders),
+            lang,
+        },
+        verbatim => verbatim,
+    }
+}
+
+/// Remember the result as the last translation and show it in the overlay
+fn publish_result(
+    app: &AppHandle,
+    state: &AppState,
+    mut result: TranslationResult,
+) -> Result<TranslationResult, String> {
+    if let Some(history) = &state.history {
+        match history.record(&result) {
+            Ok(id) => result.history_id = Some(id),
+            Err(e) => eprintln!("⚠ {}", e),
+        }
+        if result.provider != MEMORY_PROVIDER && result.provider != DICTIONARY_PROVIDER {
+            let remembered = history.remember(
+                &result.source_lang,
+                &result.target_lang,
+                result.formality,
+                &result.original,
+                &result.translated,
+            );
+            if let Err(e) = remembered {
+                eprintln!("⚠ {}", e);
+            }
+        }
+    }
+
+    if let Ok(mut last_result) = state.last_result.lock() {
+        *last_result = Some(result.clone());
+    }
 
-    // Step 11: Emit event to show overlay
     println!("[DEBUG] Emitting show-translation event to frontend");
     app.emit("show-translation", result.clone())
         .map_err(|e| format!("Failed to emit event: {}", e))?;
 
-    println!("[DEBUG] ===== Translation workflow completed successfully =====");
     Ok(result)
 }
+
+/// The frontmost window, i.e. where the selection came from
+struct SourceWindow {
+    app_name: String,
+    title: String,
+}
+
+#[cfg(not(any(target_os = "android", target_os = "ios")))]
+fn active_window() -> Option<SourceWindow> {
+    active_win_pos_rs::get_active_window()
+        .ok()
+        .filter(|window| !window.app_name.is_empty())
+        .map(|window| SourceWindow {
+            app_name: window.app_name,
+            title: window.title,
+        })
+}
+
+#[cfg(any(target_os = "android", target_os = "ios"))]
+fn active_window() -> Option<SourceWindow> {
+    None
+}
+
+/// Normalize an optional language tag coming from the frontend
+fn normalize_option(tag: Option<String>) -> Result<Option<String>, String> {
+    match tag.filter(|tag| !tag.trim().is_empty()) {
+        Some(tag) => language::normalize_tag(&tag)
+            .map(Some)
+            .ok_or_else(|| format!("Invalid language tag: {}", tag)),
+        None => Ok(None),
+    }
+}
@@ -1,6 +1,26 @@
+use crate::actions::Action;
+use crate::dictionary::DEFAULT_JMDICT_URL;
+use crate::kanjidic::DEFAULT_KANJIDIC_URL;
+use crate::language::normalize_tag;
+use crate::translation::Formality;
+use std::time::Duration;
+
+/// Alternative translations asked for unless `TRANSLATION_ALTERNATIVES` says otherwise.
+/// They make every response longer and slower, so they are opt-in.
+const DEFAULT_ALTERNATIVES: usize = 0;
+
+/// More alternatives than this only repeat each other
+const MAX_ALTERNATIVES: usize = 5;
+
 /// Configuration for the translation service
 pub struct TranslationConfig {
     pub worker_url: String,
+    /// Translate every result back into the source language to catch mistranslations
+    pub back_translation_check: bool,
+    /// Show furigana and romaji for Japanese text with every translation
+    pub reading_annotations: bool,
+    /// Alternative translations (e.g. literal, natural, concise) to ask for; 0 turns them off
+    pub alternatives: usize,
 }
 
 impl TranslationConfig {
@@ -25,6 +45,315 @@ impl TranslationConfig {
             }
         };
 
-        Self { worker_url }
+        // Costs a second translation call per request, so it is off by default
+        let back_translation_check = env_flag("BACK_TRANSLATION_CHECK");
+        let reading_annotations = env_flag("READING_ANNOTATIONS");
+        let alternatives = std::env::var("TRANSLATION_ALTERNATIVES")
+            .ok()
+            .and_then(|count| count.trim().parse::<usize>().ok())
+            .unwrap_or(DEFAULT_ALTERNATIVES)
+            .min(MAX_ALTERNATIVES);
+
+        Self {
+            worker_url,
+            back_translation_check,
+            reading_annotations,
+            alternatives,
+        }
+    }
+}
+
+/// Whether an on/off environment variable is set to on (1, true, yes or on)
+fn env_flag(name: &str) -> bool {
+    std::env::var(name)
+        .map(|value| matches!(value.trim().to_lowercase().as_str(), "1" | "true" | "yes" | "on"))
+        .unwrap_or(false)
+}
+
+/// Global hotkey bindings, one shortcut per action
+pub struct HotkeyConfig {
+    pub bindings: Vec<(Action, String)>,
+}
+
+impl HotkeyConfig {
+    /// Load hotkey bindings from environment variables, falling back to defaults.
+    /// Shortcuts use the global-shortcut syntax, e.g. `CmdOrCtrl+Shift+J`.
+    /// Set a variable to an empty string or `none` to leave that action unbound.
+    pub fn from_env() -> Self {
+        Self::from_lookup(|name| std::env::var(name).ok())
+    }
+
+    /// Bindings from a variable lookup. Malformed shortcuts are reported and left unbound.
+    fn from_lookup(lookup: impl Fn(&str) -> Option<String>) -> Self {
+        let force_target = lookup("FORCE_TARGET_LANG")
+            .and_then(|tag| normalize_tag(&tag))
+            .unwrap_or_else(|| "en".to_string());
+        let retranslate_as = lookup("RETRANSLATE_AS_LANG")
+            .and_then(|tag| normalize_tag(&tag))
+            .unwrap_or_else(|| "zh".to_string());
+
+        let defaults = [
+            ("HOTKEY_TRANSLATE", "CmdOrCtrl+J", Action::Translate),
+            ("HOTKEY_TRANSLATE_REPLACE", "CmdOrCtrl+Shift+J", Action::TranslateAndReplace),
+            ("HOTKEY_EXPLAIN", "CmdOrCtrl+Alt+J", Action::ExplainGrammar),
+            ("HOTKEY_REVIEW", "CmdOrCtrl+Alt+R", Action::ReviewDraft),
+            ("HOTKEY_FORCE_TARGET", "", Action::TranslateTo(force_target)),
+            ("HOTKEY_SWAP_DIRECTION", "CmdOrCtrl+Alt+S", Action::SwapDirection),
+            ("HOTKEY_RETRANSLATE_AS", "", Action::RetranslateAs(retranslate_as)),
+            ("HOTKEY_SHOW_LAST", "CmdOrCtrl+Alt+L", Action::ShowLastResult),
+            ("HOTKEY_TOGGLE_OVERLAY", "", Action::ToggleOverlay),
+            ("HOTKEY_OPEN_HISTORY", "CmdOrCtrl+Alt+H", Action::OpenHistory),
+            ("HOTKEY_OPEN_GLOSSARY", "", Action::OpenGlossary),
+            ("HOTKEY_OPEN_VOCABULARY", "", Action::OpenVocabulary),
+            ("HOTKEY_PRACTICE", "", Action::PracticeWords),
+        ];
+
+        let bindings = defaults
+            .into_iter()
+            .filter_map(|(var, default, action)| {
+                let shortcut = lookup(var).unwrap_or_else(|| default.to_string());
+                match parse_shortcut(&shortcut) {
+                    Ok(shortcut) => shortcut.map(|shortcut| (action, shortcut)),
+                    Err(e) => {
+                        eprintln!("⚠ {} in {}, leaving {:?} unbound", e, var, action);
+                        None
+                    }
+                }
+            })
+            .collect();
+
+        Self { bindings }
+    }
+
+    /// Shortcut bound to an action, if any
+    pub fn shortcut_for(&self, action: &Action) -> Option<&str> {
+        self.bindings
+            .iter()
+            .find(|(bound, _)| bound == action)
+            .map(|(_, shortcut)| shortcut.as_str())
+    }
+}
+
+/// A shortcut setting: `Ok(None)` leaves the action unbound, and shortcuts the
+/// global-shortcut plugin cannot parse are an error
+fn parse_shortcut(value: &str) -> Result<Option<String>, String> {
+    let value = value.trim();
+    if value.is_empty() || value.eq_ignore_ascii_case("none") {
+        return Ok(None);
+    }
+
+    #[cfg(not(any(target_os = "android", target_os = "ios")))]
+    value
+        .parse::<tauri_plugin_global_shortcut::Shortcut>()
+        .map_err(|e| format!("Invalid hotkey '{}': {}", value, e))?;
+
+    Ok(Some(value.to_string()))
+}
+
+/// Parse comma-separated `pattern=formality` pairs. Patterns are lowercased
+/// for matching; invalid pairs are reported and skipped.
+fn parse_formality_rules(value: &str) -> Vec<(String, Formality)> {
+    value
+        .split(',')
+        .filter(|rule| !rule.trim().is_empty())
+        .filter_map(|rule| {
+            let parsed = rule.rsplit_once('=').and_then(|(pattern, value)| {
+                let pattern = pattern.trim();
+                let formality = Formality::parse(value)?;
+                (!pattern.is_empty()).then(|| (pattern.to_lowercase(), formality))
+            });
+            if parsed.is_none() {
+                eprintln!("⚠ Ignoring invalid formality rule '{}'", rule.trim());
+            }
+            parsed
+        })
+        .collect()
+}
+
+/// Shared team glossary, loaded from a path or HTTP(S) URL
+pub struct TeamGlossaryConfig {
+    /// File path or URL; `None` if the team has no shared glossary
+    pub source: Option<String>,
+    pub refresh_interval: Duration,
+}
+
+impl TeamGlossaryConfig {
+    /// Load from `TEAM_GLOSSARY` and `TEAM_GLOSSARY_REFRESH_MINUTES` (default 15)
+    pub fn from_env() -> Self {
+        let source = std::env::var("TEAM_GLOSSARY")
+            .ok()
+            .map(|source| source.trim().to_string())
+            .filter(|source| !source.is_empty());
+        let minutes = std::env::var("TEAM_GLOSSARY_REFRESH_MINUTES")
+            .ok()
+            .and_then(|minutes| minutes.trim().parse::<u64>().ok())
+            .filter(|&minutes| minutes > 0)
+            .unwrap_or(15);
+
+        Self {
+            source,
+            refresh_interval: Duration::from_secs(minutes * 60),
+        }
+    }
+}
+
+/// Offline dictionaries: JMdict for looking up short Japanese terms,
+/// KANJIDIC2 for kanji breakdowns and an optional extra JLPT word list
+pub struct DictionaryConfig {
+    /// File path or URL of JMdict XML (optionally gzipped); `None` disables lookups
+    pub source: Option<String>,
+    /// File path or URL of KANJIDIC2 XML (optionally gzipped); `None` disables breakdowns
+    pub kanjidic_source: Option<String>,
+    /// Extra JLPT word list (`JLPT_WORD_LIST`), in the format of the built-in one
+    pub jlpt_word_list: Option<String>,
+    /// Import on first run. Only when `JMDICT_SOURCE` or `KANJIDIC_SOURCE` is set: the
+    /// default downloads are megabytes, so otherwise the user starts them from the glossary panel.
+    pub auto_import: bool,
+}
+
+impl DictionaryConfig {
+    /// Load from `JMDICT_SOURCE` and `KANJIDIC_SOURCE`, defaulting to the EDRDG
+    /// downloads. Set either to an empty string or `none` to turn it off.
+    pub fn from_env() -> Self {
+        let source = dictionary_source("JMDICT_SOURCE", DEFAULT_JMDICT_URL);
+        let kanjidic_source = dictionary_source("KANJIDIC_SOURCE", DEFAULT_KANJIDIC_URL);
+        let explicit = |name: &str, source: &Option<String>| source.is_some() && std::env::var_os(name).is_some();
+        Self {
+            auto_import: explicit("JMDICT_SOURCE", &source) || explicit("KANJIDIC_SOURCE", &kanjidic_source),
+            source,
+            kanjidic_source,
+            jlpt_word_list: std::env::var("JLPT_WORD_LIST")
+                .ok()
+                .map(|path| path.trim().to_string())
+                .filter(|path| !path.is_empty()),
+        }
+    }
+}
+
+fn dictionary_source(name: &str, default: &str) -> Option<String> {
+    match std::env::var(name) {
+        Ok(source) => Some(source.trim().to_string())
+            .filter(|source| !source.is_empty() && !source.eq_ignore_ascii_case("none")),
+        Err(_) => Some(default.to_string()),
+    }
+}
+
+/// Formality for Japanese translations, by default and per recipient
+pub struct FormalityConfig {
+    /// `None` lets the translation service choose
+    pub default: Option<Formality>,
+    /// Case-insensitive substrings of the source window (app name and title, e.g.
+    /// a Slack DM or channel name), with the formality to use for matching windows
+    pub rules: Vec<(String, Formality)>,
+}
+
+impl FormalityConfig {
+    /// Load from `FORMALITY_DEFAULT` and `FORMALITY_RULES`, a comma-separated
+    /// list of `pattern=formality` pairs, e.g. `Tanaka=honorific,#random=casual`
+    pub fn from_env() -> Self {
+        let default = std::env::var("FORMALITY_DEFAULT")
+            .ok()
+            .and_then(|value| Formality::parse(&value));
+
+        let rules = parse_formality_rules(&std::env::var("FORMALITY_RULES").unwrap_or_default());
+
+        Self { default, rules }
+    }
+
+    /// Formality for text captured from this window: the first matching rule, or the default
+    pub fn for_window(&self, window: Option<&str>) -> Option<Formality> {
+        let window = window.map(str::to_lowercase);
+        window
+            .and_then(|window| {
+                self.rules
+                    .iter()
+                    .find(|(pattern, _)| window.contains(pattern.as_str()))
+                    .map(|(_, formality)| *formality)
+            })
+            .or(self.default)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn hotkeys(vars: &[(&str, &str)]) -> HotkeyConfig {
+        let vars: HashMap<String, String> = vars.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+        HotkeyConfig::from_lookup(|name| vars.get(name).cloned())
+    }
+
+    #[test]
+    fn parses_formality_rules() {
+        let rules = parse_formality_rules(" Tanaka=Honorific, #random = casual ,a=b=keigo,,");
+        assert_eq!(
+            rules,
+            vec![
+                ("tanaka".to_string(), Formality::Honorific),
+                ("#random".to_string(), Formality::Casual),
+                ("a=b".to_string(), Formality::Honorific),
+            ]
+        );
+        assert!(parse_formality_rules("").is_empty());
+        assert!(parse_formality_rules("Tanaka,=polite,Sato=rude").is_empty());
+    }
+
+    #[test]
+    fn matches_formality_by_window() {
+        let config = FormalityConfig {
+            default: Some(Formality::Polite),
+            rules: parse_formality_rules("tanaka=honorific,#random=casual,slack=polite"),
+        };
+        assert_eq!(config.for_window(Some("Slack - DM with TANAKA Hiroshi")), Some(Formality::Honorific));
+        assert_eq!(config.for_window(Some("Slack | #random")), Some(Formality::Casual));
+        assert_eq!(config.for_window(Some("Mail - Inbox")), Some(Formality::Polite));
+        assert_eq!(config.for_window(None), Some(Formality::Polite));
+
+        let no_default = FormalityConfig {
+            default: None,
+            rules: config.rules,
+        };
+        assert_eq!(no_default.for_window(Some("Mail - Inbox")), None);
+        assert_eq!(no_default.for_window(None), None);
+    }
+
+    #[test]
+    fn default_hotkeys() {
+        let config = hotkeys(&[]);
+        assert_eq!(config.shortcut_for(&Action::Translate), Some("CmdOrCtrl+J"));
+        assert_eq!(config.shortcut_for(&Action::TranslateAndReplace), Some("CmdOrCtrl+Shift+J"));
+        assert_eq!(config.shortcut_for(&Action::OpenHistory), Some("CmdOrCtrl+Alt+H"));
+        assert_eq!(config.shortcut_for(&Action::ToggleOverlay), None);
+        assert_eq!(config.shortcut_for(&Action::TranslateTo("en".to_string())), None);
+        assert_eq!(config.bindings.len(), 7);
+    }
+
+    #[test]
+    fn overridden_hotkeys() {
+        let config = hotkeys(&[
+            ("HOTKEY_TRANSLATE", " Alt+Shift+T "),
+            ("HOTKEY_REVIEW", "none"),
+            ("HOTKEY_EXPLAIN", ""),
+            ("HOTKEY_FORCE_TARGET", "CmdOrCtrl+Alt+E"),
+            ("FORCE_TARGET_LANG", "ja"),
+        ]);
+        assert_eq!(config.shortcut_for(&Action::Translate), Some("Alt+Shift+T"));
+        assert_eq!(config.shortcut_for(&Action::ReviewDraft), None);
+        assert_eq!(config.shortcut_for(&Action::ExplainGrammar), None);
+        assert_eq!(config.shortcut_for(&Action::TranslateTo("ja".to_string())), Some("CmdOrCtrl+Alt+E"));
+    }
+
+    #[test]
+    fn malformed_hotkeys_are_rejected() {
+        for shortcut in ["CmdOrCtrl+", "CmdOrCtrl+Banana", "J+Shift"] {
+            let error = parse_shortcut(shortcut).unwrap_err();
+            assert!(error.starts_with(&format!("Invalid hotkey '{}'", shortcut)), "{}", error);
+        }
+        assert_eq!(parse_shortcut(" none "), Ok(None));
+
+        let config = hotkeys(&[("HOTKEY_TRANSLATE", "CmdOrCtrl+Banana")]);
+        assert_eq!(config.shortcut_for(&Action::Translate), None);
+        assert_eq!(config.shortcut_for(&Action::TranslateAndReplace), Some("CmdOrCtrl+Shift+J"));
     }
 }
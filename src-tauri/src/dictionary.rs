@@ -0,0 +1,647 @@
+use flate2::read::GzDecoder;
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use regex::Regex;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+use crate::commands::AppState;
+use crate::glossary_sync::TeamSource;
+use crate::history::now_millis;
+use crate::kanjidic::{self, KanjiInfo};
+
+/// English-only JMdict from the Electronic Dictionary Research and Development Group
+pub const DEFAULT_JMDICT_URL: &str = "https://www.edrdg.org/pub/Nihongo/JMdict_e.gz";
+
+/// Longest selection, in characters, that is looked up instead of translated
+const MAX_TERM_CHARS: usize = 15;
+
+/// Schema migrations, applied in order and tracked with `PRAGMA user_version`
+const MIGRATIONS: &[&str] = &[
+    // 1: JMdict entries, looked up by any kanji or kana spelling
+    "CREATE TABLE jmdict_entries (
+        id INTEGER PRIMARY KEY,
+        common INTEGER NOT NULL,
+        entry TEXT NOT NULL
+    );
+    CREATE TABLE jmdict_keys (
+        key TEXT NOT NULL,
+        entry_id INTEGER NOT NULL
+    );
+    CREATE INDEX jmdict_keys_key ON jmdict_keys(key);
+    CREATE TABLE dictionary_info (
+        name TEXT PRIMARY KEY,
+        source TEXT NOT NULL,
+        entries INTEGER NOT NULL,
+        imported_at INTEGER NOT NULL
+    );",
+    // 2: KANJIDIC2 characters for kanji breakdowns
+    "CREATE TABLE kanji (
+        literal TEXT PRIMARY KEY,
+        info TEXT NOT NULL
+    );",
+];
+
+/// One JMdict entry
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct DictionaryEntry {
+    /// JMdict sequence number
+    pub id: i64,
+    /// Kanji spellings, most common first
+    pub kanji: Vec<String>,
+    /// Kana readings, most common first
+    pub readings: Vec<String>,
+    pub senses: Vec<Sense>,
+    /// Marked as a common word (news1, ichi1, spec1/2 or gai1)
+    pub common: bool,
+}
+
+/// One meaning of an entry
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct Sense {
+    /// Parts of speech, e.g. "noun (common) (futsuumeishi)"
+    pub pos: Vec<String>,
+    pub glosses: Vec<String>,
+    /// Usage notes, e.g. "word usually written using kana alone"
+    pub misc: Vec<String>,
+}
+
+impl DictionaryEntry {
+    /// Short summary for the translation field: the glosses of the first sense
+    pub fn summary(&self) -> String {
+        self.senses
+            .first()
+            .map(|sense| sense.glosses.join("; "))
+            .unwrap_or_default()
+    }
+}
+
+/// Import state, shown in the overlay
+#[derive(Serialize, Clone, Debug)]
+pub struct DictionaryStatus {
+    /// JMdict entries
+    pub entries: i64,
+    /// KANJIDIC2 characters
+    pub kanji: i64,
+    pub source: Option<String>,
+    /// Unix milliseconds of the last JMdict import
+    pub imported_at: Option<i64>,
+    pub importing: bool,
+    /// Error from the last import attempt
+    pub error: Option<String>,
+}
+
+/// Local SQLite store of dictionary data
+pub struct DictionaryStore {
+    path: PathBuf,
+    /// Where to import JMdict from; `None` disables downloads
+    source: Option<TeamSource>,
+    /// Where to import KANJIDIC2 from; `None` disables downloads
+    kanjidic_source: Option<TeamSource>,
+    conn: Mutex<Connection>,
+    importing: AtomicBool,
+    last_error: Mutex<Option<String>>,
+}
+
+impl DictionaryStore {
+    /// Open (or create) the dictionary database at `path` and migrate it
+    pub fn open(path: &Path, source: Option<TeamSource>, kanjidic_source: Option<TeamSource>) -> Result<Self, String> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create dictionary directory: {}", e))?;
+        }
+
+        let conn = Connection::open(path).map_err(|e| format!("Failed to open dictionary database: {}", e))?;
+        // Lookups keep working while an import writes in the background
+        conn.pragma_update(None, "journal_mode", "WAL")
+            .map_err(|e| format!("Failed to configure dictionary database: {}", e))?;
+
+        let version: usize = conn
+            .query_row("PRAGMA user_version", [], |row| row.get(0))
+            .map_err(|e| format!("Failed to read dictionary schema version: {}", e))?;
+        for (i, migration) in MIGRATIONS.iter().enumerate().skip(version) {
+            conn.execute_batch(&format!(
+                "BEGIN; {} PRAGMA user_version = {}; COMMIT;",
+                migration,
+                i + 1
+            ))
+            .map_err(|e| format!("Failed to migrate dictionary database to v{}: {}", i + 1, e))?;
+        }
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            source,
+            kanjidic_source,
+            conn: Mutex::new(conn),
+            importing: AtomicBool::new(false),
+            last_error: Mutex::new(None),
+        })
+    }
+
+    fn conn(&self) -> Result<std::sync::MutexGuard<'_, Connection>, String> {
+        self.conn
+            .lock()
+            .map_err(|e| format!("Failed to lock dictionary database: {}", e))
+    }
+
+    /// Entries spelled exactly `term` (in kanji or kana), common words first
+    pub fn lookup(&self, term: &str, limit: usize) -> Result<Vec<DictionaryEntry>, String> {
+        let conn = self.conn()?;
+        let mut stmt = conn
+            .prepare_cached(
+                "SELECT e.entry FROM jmdict_keys k JOIN jmdict_entries e ON e.id = k.entry_id
+                 WHERE k.key = ?1 GROUP BY e.id ORDER BY e.common DESC, e.id LIMIT ?2",
+            )
+            .map_err(|e| format!("Failed to look up dictionary: {}", e))?;
+        let rows = stmt
+            .query_map(params![term.trim(), limit as i64], |row| row.get::<_, String>(0))
+            .map_err(|e| format!("Failed to look up dictionary: {}", e))?;
+
+        rows.map(|row| {
+            let json = row.map_err(|e| format!("Failed to read dictionary entry: {}", e))?;
+            serde_json::from_str(&json).map_err(|e| format!("Invalid dictionary entry: {}", e))
+        })
+        .collect()
+    }
+
+    /// KANJIDIC2 data for one kanji
+    pub fn kanji(&self, literal: char) -> Result<Option<KanjiInfo>, String> {
+        let conn = self.conn()?;
+        let json: Option<String> = conn
+            .query_row("SELECT info FROM kanji WHERE literal = ?1", [literal.to_string()], |row| row.get(0))
+            .optional()
+            .map_err(|e| format!("Failed to look up kanji: {}", e))?;
+        json.map(|json| serde_json::from_str(&json).map_err(|e| format!("Invalid kanji entry: {}", e)))
+            .transpose()
+    }
+
+    /// Whether a dictionary has nothing to look up yet, but a source to import from
+    pub fn needs_import(&self) -> bool {
+        let status = self.status();
+        (self.source.is_some() && status.entries == 0) || (self.kanjidic_source.is_some() && status.kanji == 0)
+    }
+
+    pub fn status(&self) -> DictionaryStatus {
+        let jmdict = self.info("jmdict");
+        DictionaryStatus {
+            entries: jmdict.as_ref().map_or(0, |(_, entries, _)| *entries),
+            kanji: self.info("kanjidic").map_or(0, |(_, entries, _)| entries),
+            source: jmdict.as_ref().map(|(source, _, _)| source.clone()),
+            imported_at: jmdict.map(|(_, _, imported_at)| imported_at),
+            importing: self.importing.load(Ordering::SeqCst),
+            error: self.last_error.lock().ok().and_then(|error| error.clone()),
+        }
+    }
+
+    /// Source, entry count and import time of an imported dictionary
+    fn info(&self, name: &str) -> Option<(String, i64, i64)> {
+        self.conn().ok().and_then(|conn| {
+            conn.query_row(
+                "SELECT source, entries, imported_at FROM dictionary_info WHERE name = ?1",
+                [name],
+                |row| Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?, row.get::<_, i64>(2)?)),
+            )
+            .optional()
+            .ok()
+            .flatten()
+        })
+    }
+
+    /// Download or read JMdict and KANJIDIC2 from their configured sources and
+    /// replace the stored data. With `missing_only`, dictionaries that already
+    /// have data are left alone.
+    pub async fn import(&self, missing_only: bool) -> Result<(), String> {
+        if self.source.is_none() && self.kanjidic_source.is_none() {
+            return Err("No dictionary source is configured (JMDICT_SOURCE, KANJIDIC_SOURCE)".to_string());
+        }
+        if self.importing.swap(true, Ordering::SeqCst) {
+            return Err("A dictionary import is already running".to_string());
+        }
+
+        let status = self.status();
+        let imports: [(&Option<TeamSource>, bool, Importer); 2] = [
+            (&self.source, status.entries == 0, import_jmdict),
+            (&self.kanjidic_source, status.kanji == 0, import_kanjidic),
+        ];
+        let mut errors = Vec::new();
+        for (source, missing, importer) in imports {
+            if let Some(source) = source.as_ref().filter(|_| missing || !missing_only) {
+                if let Err(e) = self.import_from(source, importer).await {
+                    errors.push(e);
+                }
+            }
+        }
+
+        self.importing.store(false, Ordering::SeqCst);
+        let result = if errors.is_empty() { Ok(()) } else { Err(errors.join("; ")) };
+        if let Ok(mut last_error) = self.last_error.lock() {
+            *last_error = result.as_ref().err().cloned();
+        }
+        result
+    }
+
+    async fn import_from(&self, source: &TeamSource, importer: Importer) -> Result<usize, String> {
+        let (bytes, description) = match source {
+            TeamSource::File(path) => (
+                std::fs::read(path).map_err(|e| format!("Cannot read dictionary {}: {}", path.display(), e))?,
+                path.display().to_string(),
+            ),
+            TeamSource::Url(url) => {
+                println!("[INFO] Downloading dictionary from {}", url);
+                (download(url).await?, url.clone())
+            }
+        };
+
+        // Parsing hundreds of thousands of elements takes a while; keep it off the async runtime
+        let path = self.path.clone();
+        let stored_source = description.clone();
+        let count = tauri::async_runtime::spawn_blocking(move || {
+            let conn = Connection::open(&path).map_err(|e| format!("Failed to open dictionary database: {}", e))?;
+            importer(conn, &bytes, &stored_source)
+        })
+        .await
+        .map_err(|e| format!("Dictionary import failed: {}", e))??;
+
+        println!("[INFO] Dictionary imported from {}: {} entries", description, count);
+        Ok(count)
+    }
+}
+
+/// Replaces one dictionary's stored data with a file's contents, returning the entry count
+type Importer = fn(Connection, &[u8], &str) -> Result<usize, String>;
+
+async fn download(url: &str) -> Result<Vec<u8>, String> {
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(300))
+        .build()
+        .map_err(|e| format!("HTTP client error: {}", e))?;
+
+    let response = client.get(url).send().await.map_err(|e| {
+        if e.is_connect() || e.is_timeout() {
+            format!("Cannot reach dictionary download at {}", url)
+        } else {
+            format!("Dictionary download failed: {}", e)
+        }
+    })?;
+    if !response.status().is_success() {
+        return Err(format!("Dictionary download failed: HTTP {}", response.status()));
+    }
+
+    response
+        .bytes()
+        .await
+        .map(|bytes| bytes.to_vec())
+        .map_err(|e| format!("Failed to read dictionary download: {}", e))
+}
+
+/// Reader over XML that may be gzipped
+fn xml_reader(bytes: &[u8]) -> Box<dyn BufRead + '_> {
+    if bytes.starts_with(&[0x1f, 0x8b]) {
+        Box::new(BufReader::new(GzDecoder::new(bytes)))
+    } else {
+        Box::new(BufReader::new(bytes))
+    }
+}
+
+/// Record a finished import in `dictionary_info`
+fn record_import(tx: &rusqlite::Transaction, name: &str, source: &str, count: usize) -> Result<(), String> {
+    tx.execute(
+        "INSERT OR REPLACE INTO dictionary_info (name, source, entries, imported_at) VALUES (?1, ?2, ?3, ?4)",
+        params![name, source, count as i64, now_millis()],
+    )
+    .map(|_| ())
+    .map_err(|e| format!("Failed to import dictionary: {}", e))
+}
+
+/// Parse JMdict XML (optionally gzipped) and replace the stored entries in one transaction
+fn import_jmdict(mut conn: Connection, bytes: &[u8], source: &str) -> Result<usize, String> {
+    let reader = xml_reader(bytes);
+    let tx = conn
+        .transaction()
+        .map_err(|e| format!("Failed to start dictionary import: {}", e))?;
+    tx.execute_batch("DELETE FROM jmdict_keys; DELETE FROM jmdict_entries;")
+        .map_err(|e| format!("Failed to clear dictionary: {}", e))?;
+
+    let mut count = 0;
+    {
+        let mut insert_entry = tx
+            .prepare("INSERT OR REPLACE INTO jmdict_entries (id, common, entry) VALUES (?1, ?2, ?3)")
+            .map_err(|e| format!("Failed to import dictionary: {}", e))?;
+        let mut insert_key = tx
+            .prepare("INSERT INTO jmdict_keys (key, entry_id) VALUES (?1, ?2)")
+            .map_err(|e| format!("Failed to import dictionary: {}", e))?;
+
+        parse_jmdict(reader, |entry| {
+            let json = serde_json::to_string(&entry).map_err(|e| format!("Failed to store dictionary entry: {}", e))?;
+            insert_entry
+                .execute(params![entry.id, entry.common, json])
+                .map_err(|e| format!("Failed to store dictionary entry: {}", e))?;
+            for key in entry.kanji.iter().chain(&entry.readings) {
+                insert_key
+                    .execute(params![key, entry.id])
+                    .map_err(|e| format!("Failed to store dictionary entry: {}", e))?;
+            }
+            count += 1;
+            Ok(())
+        })?;
+    }
+
+    if count == 0 {
+        return Err("The dictionary file has no JMdict entries".to_string());
+    }
+    record_import(&tx, "jmdict", source, count)?;
+    tx.commit().map_err(|e| format!("Failed to save dictionary: {}", e))?;
+
+    Ok(count)
+}
+
+/// Parse KANJIDIC2 XML (optionally gzipped) and replace the stored kanji in one transaction
+fn import_kanjidic(mut conn: Connection, bytes: &[u8], source: &str) -> Result<usize, String> {
+    let reader = xml_reader(bytes);
+    let tx = conn
+        .transaction()
+        .map_err(|e| format!("Failed to start kanji import: {}", e))?;
+    tx.execute("DELETE FROM kanji", [])
+        .map_err(|e| format!("Failed to clear kanji: {}", e))?;
+
+    let mut count = 0;
+    {
+        let mut insert = tx
+            .prepare("INSERT OR REPLACE INTO kanji (literal, info) VALUES (?1, ?2)")
+            .map_err(|e| format!("Failed to import kanji: {}", e))?;
+        kanjidic::parse_kanjidic(reader, |kanji| {
+            let json = serde_json::to_string(&kanji).map_err(|e| format!("Failed to store kanji: {}", e))?;
+            insert
+                .execute(params![kanji.literal, json])
+                .map_err(|e| format!("Failed to store kanji: {}", e))?;
+            count += 1;
+            Ok(())
+        })?;
+    }
+
+    if count == 0 {
+        return Err("The kanji dictionary file has no KANJIDIC2 characters".to_string());
+    }
+    record_import(&tx, "kanjidic", source, count)?;
+    tx.commit().map_err(|e| format!("Failed to save kanji: {}", e))?;
+
+    Ok(count)
+}
+
+/// Stream entries out of JMdict XML. Part-of-speech and usage codes are DTD
+/// entities (`&n;`, `&uk;`), expanded to their descriptions from the DOCTYPE.
+pub fn parse_jmdict<R: BufRead>(
+    input: R,
+    mut on_entry: impl FnMut(DictionaryEntry) -> Result<(), String>,
+) -> Result<(), String> {
+    let mut reader = Reader::from_reader(input);
+    reader.config_mut().trim_text(true);
+
+    let mut buf = Vec::new();
+    let mut entities: HashMap<String, String> = HashMap::new();
+    let mut entry: Option<DictionaryEntry> = None;
+    let mut sense = Sense::default();
+    // A sense without its own <pos> uses the previous sense's
+    let mut last_pos: Vec<String> = Vec::new();
+    let mut element: Vec<u8> = Vec::new();
+    let mut foreign_gloss = false;
+
+    loop {
+        let event = reader.read_event_into(&mut buf).map_err(|e| {
+            format!("Invalid JMdict at position {}: {}", reader.error_position(), e)
+        })?;
+        match event {
+            Event::DocType(doctype) => {
+                let doctype = String::from_utf8_lossy(&doctype).to_string();
+                entities = parse_entities(&doctype);
+            }
+            Event::Start(e) => {
+                element = e.local_name().as_ref().to_vec();
+                match element.as_slice() {
+                    b"entry" => {
+                        entry = Some(DictionaryEntry {
+                            id: 0,
+                            kanji: Vec::new(),
+                            readings: Vec::new(),
+                            senses: Vec::new(),
+                            common: false,
+                        });
+                        last_pos.clear();
+                    }
+                    b"sense" => sense = Sense::default(),
+                    b"gloss" => {
+                        // Full JMdict has glosses in several languages; keep English
+                        foreign_gloss = e
+                            .try_get_attribute("xml:lang")
+                            .ok()
+                            .flatten()
+                            .is_some_and(|lang| lang.value.as_ref() != b"eng");
+                    }
+                    _ => {}
+                }
+            }
+            Event::Text(text) => {
+                let Some(entry) = entry.as_mut() else { continue };
+                let text = text
+                    .unescape_with(|name| entities.get(name).map(String::as_str))
+                    .map_err(|e| format!("Invalid JMdict text: {}", e))?
+                    .to_string();
+                match element.as_slice() {
+                    b"ent_seq" => entry.id = text.parse().unwrap_or_default(),
+                    b"keb" => entry.kanji.push(text),
+                    b"reb" => entry.readings.push(text),
+                    b"ke_pri" | b"re_pri" => {
+                        entry.common |= matches!(text.as_str(), "news1" | "ichi1" | "spec1" | "spec2" | "gai1");
+                    }
+                    b"pos" => sense.pos.push(text),
+                    b"misc" => sense.misc.push(text),
+                    b"gloss" if !foreign_gloss => sense.glosses.push(text),
+                    _ => {}
+                }
+            }
+            Event::End(e) => {
+                match e.local_name().as_ref() {
+                    b"sense" => {
+                        let mut finished = std::mem::take(&mut sense);
+                        if finished.pos.is_empty() {
+                            finished.pos = last_pos.clone();
+                        } else {
+                            last_pos = finished.pos.clone();
+                        }
+                        if let Some(entry) = entry.as_mut().filter(|_| !finished.glosses.is_empty()) {
+                            entry.senses.push(finished);
+                        }
+                    }
+                    b"entry" => {
+                        if let Some(finished) = entry.take().filter(|entry| !entry.senses.is_empty()) {
+                            on_entry(finished)?;
+                        }
+                    }
+                    _ => {}
+                }
+                element.clear();
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+        buf.clear();
+    }
+
+    Ok(())
+}
+
+/// `<!ENTITY name "description">` declarations from a DOCTYPE
+fn parse_entities(doctype: &str) -> HashMap<String, String> {
+    let pattern = Regex::new(r#"<!ENTITY\s+(\S+)\s+"([^"]*)">"#).expect("valid entity pattern");
+    pattern
+        .captures_iter(doctype)
+        .map(|captures| (captures[1].to_string(), captures[2].to_string()))
+        .collect()
+}
+
+/// Whether a selection looks like a single Japanese word or short phrase
+/// rather than a sentence
+pub fn is_short_term(text: &str) -> bool {
+    let text = text.trim();
+    let chars = text.chars().count();
+    chars > 0
+        && chars <= MAX_TERM_CHARS
+        && !text.contains(char::is_whitespace)
+        && !text.contains(['。', '、', '！', '？', '!', '?', '.', ','])
+        && text.chars().any(|c| matches!(c as u32, 0x3040..=0x30FF | 0x3400..=0x4DBF | 0x4E00..=0x9FFF))
+}
+
+/// Import the dictionary in the background, e.g. on first run
+pub fn spawn_import(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let state = app.state::<AppState>();
+        if let Some(dictionary) = &state.dictionary {
+            if let Err(e) = dictionary.import(true).await {
+                eprintln!("⚠ {}", e);
+                eprintln!("  Short Japanese terms will be translated instead of looked up.");
+            }
+        }
+    });
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const JMDICT: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE JMdict [
+<!ENTITY n "noun (common) (futsuumeishi)">
+<!ENTITY vs "noun or participle which takes the aux. verb suru">
+<!ENTITY uk "word usually written using kana alone">
+]>
+<JMdict>
+<entry>
+<ent_seq>1000001</ent_seq>
+<k_ele><keb>勉強</keb><ke_pri>ichi1</ke_pri></k_ele>
+<r_ele><reb>べんきょう</reb><re_pri>ichi1</re_pri></r_ele>
+<sense>
+<pos>&n;</pos>
+<pos>&vs;</pos>
+<gloss>study</gloss>
+<gloss xml:lang="ger">Lernen</gloss>
+</sense>
+<sense>
+<gloss>diligence</gloss>
+<gloss>working hard</gloss>
+</sense>
+<sense>
+<gloss xml:lang="fre">rabais</gloss>
+</sense>
+</entry>
+<entry>
+<ent_seq>1000002</ent_seq>
+<r_ele><reb>ちょっと</reb></r_ele>
+<sense>
+<misc>&uk;</misc>
+<gloss xml:lang="eng">a little</gloss>
+</sense>
+</entry>
+<entry>
+<ent_seq>1000003</ent_seq>
+<r_ele><reb>シュトラーセ</reb></r_ele>
+<sense>
+<gloss xml:lang="ger">Straße</gloss>
+</sense>
+</entry>
+</JMdict>"#;
+
+    fn parse(xml: &str) -> Vec<DictionaryEntry> {
+        let mut entries = Vec::new();
+        parse_jmdict(xml.as_bytes(), |entry| {
+            entries.push(entry);
+            Ok(())
+        })
+        .unwrap();
+        entries
+    }
+
+    #[test]
+    fn entities_expand_and_pos_carries_over() {
+        let entries = parse(JMDICT);
+        let study = &entries[0];
+        assert_eq!(study.id, 1000001);
+        assert_eq!(study.kanji, ["勉強"]);
+        assert_eq!(study.readings, ["べんきょう"]);
+        assert!(study.common);
+        assert_eq!(
+            study.senses[0].pos,
+            ["noun (common) (futsuumeishi)", "noun or participle which takes the aux. verb suru"]
+        );
+        // The second sense has no <pos> of its own
+        assert_eq!(study.senses[1].pos, study.senses[0].pos);
+        assert_eq!(study.summary(), "study");
+
+        assert_eq!(entries[1].senses[0].misc, ["word usually written using kana alone"]);
+        assert!(!entries[1].common);
+    }
+
+    #[test]
+    fn non_english_glosses_are_dropped() {
+        let entries = parse(JMDICT);
+        // The French-only sense and the German-only entry disappear entirely
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].senses.len(), 2);
+        assert_eq!(entries[0].senses[1].glosses, ["diligence", "working hard"]);
+        assert_eq!(entries[1].senses[0].glosses, ["a little"]);
+    }
+
+    #[test]
+    fn import_stores_every_spelling() {
+        let path = std::env::temp_dir().join(format!("jmdict-test-{}.sqlite3", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let store = DictionaryStore::open(&path, None, None).unwrap();
+
+        let count = import_jmdict(Connection::open(&path).unwrap(), JMDICT.as_bytes(), "fixture").unwrap();
+        assert_eq!(count, 2);
+        assert_eq!(store.lookup("べんきょう", 5).unwrap()[0].id, 1000001);
+        assert_eq!(store.lookup("勉強", 5).unwrap()[0].id, 1000001);
+        assert!(store.lookup("シュトラーセ", 5).unwrap().is_empty());
+        assert_eq!(store.status().entries, 2);
+        assert_eq!(store.status().source.as_deref(), Some("fixture"));
+
+        drop(store);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn short_terms() {
+        assert!(is_short_term("勉強"));
+        assert!(is_short_term(" ちょっと "));
+        assert!(!is_short_term("今日は勉強します。"));
+        assert!(!is_short_term("hello"));
+    }
+}
@@ -0,0 +1,354 @@
+use pulldown_cmark::{Event, Options, Parser, Tag, TagEnd};
+use regex::Regex;
+use std::ops::Range;
+use std::sync::OnceLock;
+
+use crate::placeholders::Placeholders;
+
+/// HTML elements whose content is kept exactly as written
+const RAW_ELEMENTS: &[&str] = &["script", "style", "pre", "code", "textarea", "kbd", "samp"];
+
+/// HTML elements that sit inside a run of text instead of starting a new one
+const INLINE_ELEMENTS: &[&str] = &[
+    "a", "abbr", "b", "bdi", "bdo", "br", "cite", "code", "data", "del", "dfn", "em", "font", "i", "img", "ins",
+    "kbd", "mark", "q", "s", "samp", "small", "span", "strike", "strong", "sub", "sup", "time", "u", "var", "wbr",
+];
+
+/// Lines that make text Markdown rather than plain prose: headings, list
+/// items, quotes, table rows and code fences
+fn markdown_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| {
+        Regex::new(r"(?m)^ {0,3}(?:#{1,6}\s|[-*+]\s+\S|\d{1,9}[.)]\s+\S|>\s?\S|\|.*\|\s*$|```)")
+            .expect("valid Markdown pattern")
+    })
+}
+
+fn html_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| {
+        Regex::new(
+            r"(?i)</(?:p|div|span|a|b|i|em|strong|ul|ol|li|h[1-6]|table|thead|tbody|tr|td|th|code|pre|blockquote|section|article)>|<br\s*/?>",
+        )
+        .expect("valid HTML pattern")
+    })
+}
+
+/// Comments, doctypes, tags (with quoted attributes) and character references
+fn html_token_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| {
+        Regex::new(concat!(
+            r"<!--[\s\S]*?-->|<![^>]*>",
+            r#"|</?(?P<name>[A-Za-z][A-Za-z0-9-]*)(?:[^>"']|"[^"]*"|'[^']*')*>"#,
+            r"|&(?:#[0-9]+|#[xX][0-9a-fA-F]+|[A-Za-z][A-Za-z0-9]*);",
+        ))
+        .expect("valid HTML token pattern")
+    })
+}
+
+/// A run of prose to translate as one piece, such as a paragraph, heading,
+/// list item or table cell, and the text inside it. Everything else in the
+/// run (emphasis markers, inline code, links, tags) is markup.
+#[derive(Debug, Default)]
+struct Run {
+    range: Range<usize>,
+    text: Vec<Range<usize>>,
+}
+
+impl Run {
+    fn extend(&mut self, range: Range<usize>) {
+        if self.range.is_empty() {
+            self.range = range;
+        } else {
+            self.range.start = self.range.start.min(range.start);
+            self.range.end = self.range.end.max(range.end);
+        }
+    }
+
+    fn push_text(&mut self, range: Range<usize>) {
+        self.extend(range.clone());
+        self.text.push(range);
+    }
+}
+
+/// Markdown or HTML parsed into the runs of prose it contains. Translating
+/// the runs and putting them back keeps the structure, URLs, code and
+/// attributes exactly as written.
+#[derive(Debug)]
+pub struct Document<'a> {
+    source: &'a str,
+    runs: Vec<Run>,
+}
+
+impl<'a> Document<'a> {
+    /// Parse `text` if it has Markdown or HTML structure; `None` for plain text
+    /// or documents with nothing to translate
+    pub fn parse(text: &'a str) -> Option<Self> {
+        let mut document = Document {
+            source: text,
+            runs: Vec::new(),
+        };
+        if html_pattern().is_match(text) {
+            document.parse_html(0..text.len());
+        } else if markdown_pattern().is_match(text) {
+            document.parse_markdown();
+        } else {
+            return None;
+        }
+        (!document.runs.is_empty()).then_some(document)
+    }
+
+    /// The text of each run, with markup inside it swapped for placeholders
+    pub fn texts(&self, placeholders: &mut Placeholders) -> Vec<String> {
+        self.runs
+            .iter()
+            .map(|run| {
+                let mut text = String::new();
+                let mut position = run.range.start;
+                for range in &run.text {
+                    if position < range.start {
+                        text.push_str(&placeholders.insert(&self.source[position..range.start]));
+                    }
+                    text.push_str(&self.source[range.clone()]);
+                    position = range.end;
+                }
+                if position < run.range.end {
+                    text.push_str(&placeholders.insert(&self.source[position..run.range.end]));
+                }
+                text
+            })
+            .collect()
+    }
+
+    /// The document with each run replaced by its translation, in order
+    pub fn assemble(&self, translations: &[String]) -> Result<String, String> {
+        if translations.len() != self.runs.len() {
+            return Err(format!(
+                "Expected {} translated text nodes, got {}",
+                self.runs.len(),
+                translations.len()
+            ));
+        }
+
+        let mut assembled = String::with_capacity(self.source.len());
+        let mut position = 0;
+        for (run, translation) in self.runs.iter().zip(translations) {
+            assembled.push_str(&self.source[position..run.range.start]);
+            assembled.push_str(translation);
+            position = run.range.end;
+        }
+        assembled.push_str(&self.source[position..]);
+        Ok(assembled)
+    }
+
+    /// Keep a finished run if it has words in it, without the whitespace at its edges
+    fn finish(&mut self, run: &mut Run) {
+        let mut run = std::mem::take(run);
+        let text = &self.source[run.range.clone()];
+        let start = run.range.start + (text.len() - text.trim_start().len());
+        let end = run.range.start + text.trim_end().len();
+        if start >= end {
+            return;
+        }
+        run.range = start..end;
+        run.text = run
+            .text
+            .into_iter()
+            .map(|range| range.start.max(start)..range.end.min(end))
+            .filter(|range| !range.is_empty())
+            .collect();
+
+        if run.text.iter().any(|range| self.source[range.clone()].chars().any(char::is_alphabetic)) {
+            self.runs.push(run);
+        }
+    }
+
+    fn parse_markdown(&mut self) {
+        let options = Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH | Options::ENABLE_TASKLISTS;
+        let source = self.source;
+        let mut run = Run::default();
+        let mut in_code_block = false;
+
+        for (event, range) in Parser::new_ext(source, options).into_offset_iter() {
+            match event {
+                Event::Start(Tag::CodeBlock(_)) => {
+                    self.finish(&mut run);
+                    in_code_block = true;
+                }
+                Event::End(TagEnd::CodeBlock) => in_code_block = false,
+                _ if in_code_block => {}
+                Event::Start(
+                    Tag::Emphasis
+                    | Tag::Strong
+                    | Tag::Strikethrough
+                    | Tag::Superscript
+                    | Tag::Subscript
+                    | Tag::Link { .. }
+                    | Tag::Image { .. },
+                ) => run.extend(range),
+                Event::End(
+                    TagEnd::Emphasis
+                    | TagEnd::Strong
+                    | TagEnd::Strikethrough
+                    | TagEnd::Superscript
+                    | TagEnd::Subscript
+                    | TagEnd::Link
+                    | TagEnd::Image,
+                ) => {}
+                // Any other block starts or ends a run
+                Event::Start(_) | Event::End(_) | Event::Rule | Event::DisplayMath(_) => self.finish(&mut run),
+                Event::Text(_) => run.push_text(range),
+                Event::Html(_) => {
+                    self.finish(&mut run);
+                    self.parse_html(range);
+                }
+                Event::Code(_)
+                | Event::InlineHtml(_)
+                | Event::InlineMath(_)
+                | Event::FootnoteReference(_)
+                | Event::SoftBreak
+                | Event::HardBreak
+                | Event::TaskListMarker(_) => run.extend(range),
+            }
+        }
+        self.finish(&mut run);
+    }
+
+    fn parse_html(&mut self, range: Range<usize>) {
+        let source = self.source;
+        let mut run = Run::default();
+        let mut position = range.start;
+
+        while let Some(caps) = html_token_pattern().captures_at(&source[..range.end], position) {
+            let token = caps.get(0).expect("match");
+            if position < token.start() {
+                run.push_text(position..token.start());
+            }
+            position = token.end();
+
+            let Some(name) = caps.name("name").map(|name| name.as_str().to_ascii_lowercase()) else {
+                // Character references belong to the text; comments and doctypes end it
+                if token.as_str().starts_with('&') {
+                    run.extend(token.range());
+                } else {
+                    self.finish(&mut run);
+                }
+                continue;
+            };
+            let is_inline = INLINE_ELEMENTS.contains(&name.as_str());
+
+            // Code and scripts are kept whole, up to their closing tag
+            let mut markup = token.range();
+            if RAW_ELEMENTS.contains(&name.as_str()) && !token.as_str().starts_with("</") {
+                let rest = source[position..range.end].to_ascii_lowercase();
+                markup.end = match rest.find(&format!("</{}", name)) {
+                    Some(close) => rest[close..]
+                        .find('>')
+                        .map_or(range.end, |end| position + close + end + 1),
+                    None => range.end,
+                };
+                position = markup.end;
+            }
+
+            if is_inline {
+                run.extend(markup);
+            } else {
+                self.finish(&mut run);
+            }
+        }
+        if position < range.end {
+            run.push_text(position..range.end);
+        }
+        self.finish(&mut run);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MARKDOWN: &str = "# Release notes
+
+Read the [install guide](https://example.com/docs/Install) before `cargo build`.
+
+- Fixed *login* bug
+- Added **export** | not a table
+
+```rust
+let greeting = \"hello\";
+```
+";
+
+    const HTML: &str = "<p class=\"note\">Hello <a href=\"https://example.com/Path\" title=\"Docs\">world</a> &amp; friends</p>
+<pre>keep   this
+  As Is</pre>
+<p>Use <code>rm -rf</code> carefully<br>Thanks</p>";
+
+    /// Translate each run with `translate`, as the app does: placeholders in,
+    /// assemble, then restore
+    fn round_trip(source: &str, translate: impl Fn(&str) -> String) -> String {
+        let document = Document::parse(source).expect("structured document");
+        let mut placeholders = Placeholders::new();
+        let translations: Vec<String> = document.texts(&mut placeholders).iter().map(|text| translate(text)).collect();
+        let (restored, missing) = placeholders.restore(&document.assemble(&translations).unwrap());
+        assert!(missing.is_empty(), "{:?}", missing);
+        restored
+    }
+
+    #[test]
+    fn identity_translation_reproduces_the_source() {
+        assert_eq!(round_trip(MARKDOWN, str::to_string), MARKDOWN);
+        assert_eq!(round_trip(HTML, str::to_string), HTML);
+    }
+
+    #[test]
+    fn markdown_keeps_urls_and_code() {
+        let translated = round_trip(MARKDOWN, str::to_uppercase);
+        assert_eq!(
+            translated,
+            "# RELEASE NOTES
+
+READ THE [INSTALL GUIDE](https://example.com/docs/Install) BEFORE `cargo build`.
+
+- FIXED *LOGIN* BUG
+- ADDED **EXPORT** | NOT A TABLE
+
+```rust
+let greeting = \"hello\";
+```
+"
+        );
+    }
+
+    #[test]
+    fn html_keeps_attributes_and_raw_elements() {
+        let translated = round_trip(HTML, str::to_uppercase);
+        assert_eq!(
+            translated,
+            "<p class=\"note\">HELLO <a href=\"https://example.com/Path\" title=\"Docs\">WORLD</a> &amp; FRIENDS</p>
+<pre>keep   this
+  As Is</pre>
+<p>USE <code>rm -rf</code> CAREFULLY<br>THANKS</p>"
+        );
+    }
+
+    #[test]
+    fn runs_are_paragraphs_with_inline_markup_as_placeholders() {
+        let document = Document::parse(HTML).unwrap();
+        let texts = document.texts(&mut Placeholders::new());
+        assert_eq!(texts, ["Hello ⟦0⟧world⟦1⟧ ⟦2⟧ friends", "Use ⟦3⟧ carefully⟦4⟧Thanks"]);
+    }
+
+    #[test]
+    fn plain_text_is_not_a_document() {
+        assert!(Document::parse("Just a sentence. With 2 parts.").is_none());
+        assert!(Document::parse("```\nonly code\n```").is_none());
+    }
+
+    #[test]
+    fn translation_count_must_match() {
+        let document = Document::parse(HTML).unwrap();
+        assert!(document.assemble(&["one".to_string()]).is_err());
+    }
+}
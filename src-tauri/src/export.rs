@@ -0,0 +1,260 @@
+use serde::Deserialize;
+use std::fmt::Write as _;
+
+use crate::history::HistoryEntry;
+
+/// File formats history can be exported to
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Csv,
+    Jsonl,
+    /// TMX 1.4 translation memory exchange
+    Tmx,
+}
+
+impl ExportFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Jsonl => "jsonl",
+            ExportFormat::Tmx => "tmx",
+        }
+    }
+}
+
+/// Render history entries in the given format
+pub fn render(entries: &[HistoryEntry], format: ExportFormat, srclang: Option<&str>) -> Result<String, String> {
+    match format {
+        ExportFormat::Csv => Ok(to_csv(entries)),
+        ExportFormat::Jsonl => to_jsonl(entries),
+        ExportFormat::Tmx => Ok(to_tmx(entries, srclang)),
+    }
+}
+
+/// RFC 4180 CSV with a header row
+fn to_csv(entries: &[HistoryEntry]) -> String {
+    let mut out = String::from(
+        "id,created_at,source_lang,target_lang,original,translated,chosen_style,chosen_text,provider,source_app,latency_ms\r\n",
+    );
+
+    for entry in entries {
+        let fields = [
+            entry.id.to_string(),
+            format_timestamp(entry.created_at, false),
+            entry.source_lang.clone(),
+            entry.target_lang.clone(),
+            entry.original.clone(),
+            entry.translated.clone(),
+            entry.chosen_style.clone().unwrap_or_default(),
+            entry.chosen_text.clone().unwrap_or_default(),
+            entry.provider.clone(),
+            entry.source_app.clone().unwrap_or_default(),
+            entry.latency_ms.to_string(),
+        ];
+        let row: Vec<String> = fields.iter().map(|field| csv_field(field)).collect();
+        out.push_str(&row.join(","));
+        out.push_str("\r\n");
+    }
+
+    out
+}
+
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\r', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// One JSON object per line
+fn to_jsonl(entries: &[HistoryEntry]) -> Result<String, String> {
+    let mut out = String::new();
+    for entry in entries {
+        let line = serde_json::to_string(entry).map_err(|e| format!("Failed to serialize history entry: {}", e))?;
+        out.push_str(&line);
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+/// TMX 1.4 document with one translation unit per entry.
+/// `srclang` is the common source language, or `*all*` when entries mix sources.
+fn to_tmx(entries: &[HistoryEntry], srclang: Option<&str>) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, r#"<?xml version="1.0" encoding="UTF-8"?>"#);
+    let _ = writeln!(out, r#"<tmx version="1.4">"#);
+    let _ = writeln!(
+        out,
+        r#"  <header creationtool="Japanese Slack Translator" creationtoolversion="{}" segtype="paragraph" o-tmf="sqlite" adminlang="en" srclang="{}" datatype="plaintext" creationdate="{}"/>"#,
+        env!("CARGO_PKG_VERSION"),
+        xml_escape(srclang.unwrap_or("*all*")),
+        format_timestamp(crate::history::now_millis(), true),
+    );
+    let _ = writeln!(out, "  <body>");
+
+    for entry in entries {
+        let _ = writeln!(
+            out,
+            r#"    <tu tuid="{}" srclang="{}" creationdate="{}">"#,
+            entry.id,
+            xml_escape(&entry.source_lang),
+            format_timestamp(entry.created_at, true)
+        );
+        let _ = writeln!(out, r#"      <prop type="x-provider">{}</prop>"#, xml_escape(&entry.provider));
+        if let Some(app) = &entry.source_app {
+            let _ = writeln!(out, r#"      <prop type="x-source-app">{}</prop>"#, xml_escape(app));
+        }
+        if let Some(style) = &entry.chosen_style {
+            let _ = writeln!(out, r#"      <prop type="x-chosen-style">{}</prop>"#, xml_escape(style));
+        }
+        // The alternative the user picked is the translation they actually used
+        let target = entry.chosen_text.as_ref().unwrap_or(&entry.translated);
+        for (lang, text) in [(&entry.source_lang, &entry.original), (&entry.target_lang, target)] {
+            let _ = writeln!(
+                out,
+                r#"      <tuv xml:lang="{}"><seg>{}</seg></tuv>"#,
+                xml_escape(lang),
+                xml_escape(text)
+            );
+        }
+        let _ = writeln!(out, "    </tu>");
+    }
+
+    let _ = writeln!(out, "  </body>");
+    let _ = writeln!(out, "</tmx>");
+    out
+}
+
+/// Escape XML special characters and drop characters XML 1.0 cannot contain
+fn xml_escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            '\t' | '\n' | '\r' => out.push(c),
+            c if (c as u32) < 0x20 => {}
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Format Unix milliseconds as UTC, either ISO 8601 (`2024-03-15T09:30:00Z`)
+/// or the compact TMX form (`20240315T093000Z`)
+pub fn format_timestamp(millis: i64, compact: bool) -> String {
+    let secs = millis.div_euclid(1000);
+    let days = secs.div_euclid(86_400);
+    let secs_of_day = secs.rem_euclid(86_400);
+    let (hour, minute, second) = (secs_of_day / 3600, secs_of_day % 3600 / 60, secs_of_day % 60);
+
+    // Civil date from days since 1970-01-01 (Howard Hinnant's algorithm)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    if compact {
+        format!("{:04}{:02}{:02}T{:02}{:02}{:02}Z", year, month, day, hour, minute, second)
+    } else {
+        format!("{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z", year, month, day, hour, minute, second)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(original: &str, translated: &str) -> HistoryEntry {
+        HistoryEntry {
+            id: 7,
+            created_at: 1_709_210_096_000,
+            original: original.to_string(),
+            translated: translated.to_string(),
+            source_lang: "ja".to_string(),
+            target_lang: "en".to_string(),
+            provider: "worker".to_string(),
+            source_app: Some("Slack".to_string()),
+            latency_ms: 420,
+            chosen_style: None,
+            chosen_text: None,
+        }
+    }
+
+    #[test]
+    fn formats_timestamps() {
+        assert_eq!(format_timestamp(0, false), "1970-01-01T00:00:00Z");
+        assert_eq!(format_timestamp(0, true), "19700101T000000Z");
+        assert_eq!(format_timestamp(1_709_210_096_000, false), "2024-02-29T12:34:56Z");
+        assert_eq!(format_timestamp(1_709_210_096_999, true), "20240229T123456Z");
+        assert_eq!(format_timestamp(-14_182_940_000, false), "1969-07-20T20:17:40Z");
+        assert_eq!(format_timestamp(-1, true), "19691231T235959Z");
+    }
+
+    #[test]
+    fn csv_quotes_special_fields() {
+        assert_eq!(csv_field("plain"), "plain");
+        assert_eq!(csv_field("a, b"), "\"a, b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv_field("line\nbreak"), "\"line\nbreak\"");
+
+        let csv = to_csv(&[entry("はい、そうです", "Yes, \"that's right\"\nThanks")]);
+        let mut lines = csv.split("\r\n");
+        assert_eq!(
+            lines.next(),
+            Some("id,created_at,source_lang,target_lang,original,translated,chosen_style,chosen_text,provider,source_app,latency_ms")
+        );
+        assert_eq!(
+            lines.next(),
+            Some("7,2024-02-29T12:34:56Z,ja,en,はい、そうです,\"Yes, \"\"that's right\"\"\nThanks\",,,worker,Slack,420")
+        );
+    }
+
+    #[test]
+    fn tmx_escapes_text_and_drops_control_characters() {
+        assert_eq!(xml_escape("a & b < c > d \"e\" 'f'"), "a &amp; b &lt; c &gt; d &quot;e&quot; &apos;f&apos;");
+        assert_eq!(xml_escape("bell\u{7}\tok\u{1b}"), "bell\tok");
+    }
+
+    #[test]
+    fn tmx_has_one_unit_per_entry() {
+        let tmx = to_tmx(&[entry("<b>会議</b>", "Q&A")], Some("ja"));
+        assert!(tmx.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<tmx version=\"1.4\">\n"));
+        assert!(tmx.contains(r#"srclang="ja" datatype="plaintext""#));
+        let unit = r#"    <tu tuid="7" srclang="ja" creationdate="20240229T123456Z">
+      <prop type="x-provider">worker</prop>
+      <prop type="x-source-app">Slack</prop>
+      <tuv xml:lang="ja"><seg>&lt;b&gt;会議&lt;/b&gt;</seg></tuv>
+      <tuv xml:lang="en"><seg>Q&amp;A</seg></tuv>
+    </tu>
+"#;
+        assert!(tmx.contains(unit), "{}", tmx);
+        assert!(tmx.ends_with("  </body>\n</tmx>\n"));
+        assert!(to_tmx(&[], None).contains(r#"srclang="*all*""#));
+    }
+
+    #[test]
+    fn exports_the_chosen_alternative() {
+        let mut chosen = entry("了解です", "Understood");
+        chosen.chosen_style = Some("casual".to_string());
+        chosen.chosen_text = Some("Got it, thanks".to_string());
+
+        let csv = to_csv(std::slice::from_ref(&chosen));
+        assert!(csv.contains(",了解です,Understood,casual,\"Got it, thanks\",worker,"), "{}", csv);
+
+        let tmx = to_tmx(&[chosen], Some("ja"));
+        assert!(tmx.contains(r#"<prop type="x-chosen-style">casual</prop>"#));
+        assert!(tmx.contains(r#"<tuv xml:lang="en"><seg>Got it, thanks</seg></tuv>"#));
+        assert!(!tmx.contains("Understood"));
+    }
+}
@@ -0,0 +1,555 @@
+use lindera::dictionary::load_dictionary;
+use lindera::mode::Mode;
+use lindera::segmenter::Segmenter;
+use serde::Serialize;
+use std::borrow::Cow;
+use std::sync::OnceLock;
+
+/// Part of a token: kanji with their reading, or kana and other text without one
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct RubyPart {
+    pub text: String,
+    /// Hiragana reading, shown as furigana over `text`
+    pub reading: Option<String>,
+}
+
+/// One word of Japanese text as found by the morphological analyzer
+#[derive(Serialize, Clone, Debug)]
+pub struct RubyToken {
+    pub surface: String,
+    /// Hiragana reading of the whole token, if the dictionary knows it
+    pub reading: Option<String>,
+    pub romaji: String,
+    /// The surface split so furigana sits over kanji only (行っ → 行[い] っ)
+    pub ruby: Vec<RubyPart>,
+    /// Written against the previous word in romaji (auxiliaries like ます/た, て)
+    #[serde(skip)]
+    attach: bool,
+}
+
+/// Readings for a Japanese text, shown with the translation
+#[derive(Serialize, Clone, Debug)]
+pub struct Readings {
+    pub tokens: Vec<RubyToken>,
+    /// Hepburn romaji of the whole text
+    pub romaji: String,
+}
+
+/// IPADIC feature columns (after the surface)
+const POS: usize = 0;
+const POS_DETAIL: usize = 1;
+const BASE_FORM: usize = 6;
+const READING: usize = 7;
+const PRONUNCIATION: usize = 8;
+
+/// The IPADIC segmenter, loaded from the binary on first use
+fn segmenter() -> Result<&'static Segmenter, String> {
+    static SEGMENTER: OnceLock<Result<Segmenter, String>> = OnceLock::new();
+    SEGMENTER
+        .get_or_init(|| {
+            let dictionary = load_dictionary("embedded://ipadic")
+                .map_err(|e| format!("Failed to load Japanese dictionary: {}", e))?;
+            Ok(Segmenter::new(Mode::Normal, dictionary, None))
+        })
+        .as_ref()
+        .map_err(|e| e.clone())
+}
+
+/// Tokenize Japanese text and look up readings for each token
+pub fn annotate(text: &str) -> Result<Readings, String> {
+    let mut tokens = segmenter()?
+        .segment(Cow::Borrowed(text))
+        .map_err(|e| format!("Failed to analyze Japanese text: {}", e))?;
+
+    let tokens: Vec<RubyToken> = tokens
+        .iter_mut()
+        .map(|token| {
+            let surface = token.surface.to_string();
+            let details: Vec<String> = token.details().into_iter().map(str::to_string).collect();
+            ruby_token(surface, &details)
+        })
+        .collect();
+
+    let romaji = join_romaji(&tokens);
+    Ok(Readings { tokens, romaji })
+}
+
+/// Dictionary form of a single inflected word, e.g. 行きました → 行く. `None` if the
+/// text is more than one word or is already in dictionary form.
+pub fn dictionary_form(text: &str) -> Result<Option<String>, String> {
+    let mut tokens = segmenter()?
+        .segment(Cow::Borrowed(text.trim()))
+        .map_err(|e| format!("Failed to analyze Japanese text: {}", e))?;
+    let Some((first, rest)) = tokens.split_first_mut() else {
+        return Ok(None);
+    };
+
+    // Everything after the word must be inflection: auxiliaries, て/で and
+    // non-independent verbs (〜ている)
+    let inflection_only = rest.iter_mut().all(|token| {
+        let details = token.details();
+        match details.get(POS).copied() {
+            Some("助動詞") => true,
+            Some("助詞") => details.get(POS_DETAIL).copied() == Some("接続助詞"),
+            Some("動詞") => details.get(POS_DETAIL).copied() == Some("非自立"),
+            _ => false,
+        }
+    });
+    if !inflection_only {
+        return Ok(None);
+    }
+
+    let surface = first.surface.to_string();
+    Ok(first
+        .get_detail(BASE_FORM)
+        .filter(|base| *base != "*" && *base != surface)
+        .map(str::to_string))
+}
+
+/// A content word (noun, verb, adjective or adverb) found in a text
+#[derive(Clone, Debug)]
+pub struct ContentWord {
+    /// As written in the text, e.g. 食べ
+    pub surface: String,
+    /// Dictionary form, e.g. 食べる
+    pub base_form: String,
+    /// Hiragana reading of the dictionary form
+    pub reading: Option<String>,
+    /// IPADIC part of speech, e.g. 動詞
+    pub pos: String,
+    /// Byte offset of the surface in the text
+    pub offset: usize,
+}
+
+/// Nouns, verbs, adjectives and adverbs in the text, in order, leaving out
+/// numbers, pronouns, names, suffixes and non-independent words
+pub fn content_words(text: &str) -> Result<Vec<ContentWord>, String> {
+    let mut tokens = segmenter()?
+        .segment(Cow::Borrowed(text))
+        .map_err(|e| format!("Failed to analyze Japanese text: {}", e))?;
+
+    let mut words = Vec::new();
+    for token in tokens.iter_mut() {
+        let offset = token.byte_start;
+        let surface = token.surface.to_string();
+        let details = token.details();
+        let field = |index: usize| details.get(index).copied().filter(|value| *value != "*");
+        let pos = field(POS).unwrap_or_default();
+        let detail = field(POS_DETAIL).unwrap_or_default();
+        let is_content = match pos {
+            "名詞" => !matches!(detail, "数" | "代名詞" | "非自立" | "接尾" | "特殊" | "固有名詞"),
+            "動詞" | "形容詞" => detail == "自立",
+            "副詞" => true,
+            _ => false,
+        };
+        if !is_content {
+            continue;
+        }
+
+        let base_form = field(BASE_FORM).unwrap_or(&surface).to_string();
+        // Inflected words are read from their dictionary form (食べ → たべる)
+        let reading = if base_form == surface {
+            field(READING).map(to_hiragana)
+        } else {
+            reading_of(&base_form)?
+        };
+        words.push(ContentWord {
+            surface,
+            base_form,
+            reading,
+            pos: pos.to_string(),
+            offset,
+        });
+    }
+    Ok(words)
+}
+
+/// Hiragana reading of a word, if the dictionary knows all of it
+fn reading_of(word: &str) -> Result<Option<String>, String> {
+    let mut tokens = segmenter()?
+        .segment(Cow::Borrowed(word))
+        .map_err(|e| format!("Failed to analyze Japanese text: {}", e))?;
+    Ok(tokens
+        .iter_mut()
+        .map(|token| token.get_detail(READING).filter(|reading| *reading != "*").map(to_hiragana))
+        .collect())
+}
+
+/// Build a token from its surface and IPADIC features
+fn ruby_token(surface: String, details: &[String]) -> RubyToken {
+    let field = |index: usize| details.get(index).map(String::as_str).filter(|value| *value != "*");
+    let reading = field(READING);
+    let pos = field(POS).unwrap_or_default();
+    let attach = match pos {
+        // The copula is written as its own word (kaigi desu)
+        "助動詞" => !matches!(surface.as_str(), "です" | "でし" | "でしょ" | "だ" | "だっ" | "だろ"),
+        "助詞" => field(POS_DETAIL) == Some("接続助詞") && matches!(surface.as_str(), "て" | "で" | "ば"),
+        _ => false,
+    };
+
+    let romaji = match (surface.as_str(), pos) {
+        // Particles are written は/へ/を but pronounced wa/e/o
+        ("は", "助詞") => "wa".to_string(),
+        ("へ", "助詞") => "e".to_string(),
+        ("を", _) => "o".to_string(),
+        _ => match reading {
+            Some(reading) => hepburn(reading, field(PRONUNCIATION)),
+            None => hepburn(&surface, None),
+        },
+    };
+
+    let reading = reading.map(to_hiragana);
+    let ruby = match &reading {
+        Some(reading) if surface.chars().any(is_kanji) => split_ruby(&surface, reading),
+        _ => vec![RubyPart {
+            text: surface.clone(),
+            reading: None,
+        }],
+    };
+
+    RubyToken {
+        reading: reading.filter(|_| surface.chars().any(is_kanji)),
+        surface,
+        romaji,
+        ruby,
+        attach,
+    }
+}
+
+/// Romaji for a sentence: words separated by spaces, with auxiliaries and
+/// punctuation attached to the word before them
+fn join_romaji(tokens: &[RubyToken]) -> String {
+    let mut romaji = String::new();
+    let mut geminate = false;
+    for token in tokens {
+        let attach = token.attach || token.romaji.starts_with(|c: char| !c.is_alphanumeric());
+        if !romaji.is_empty() && !attach && !romaji.ends_with(char::is_whitespace) {
+            romaji.push(' ');
+        }
+        // A word ending in っ (行っ) doubles the first consonant of the next (itte)
+        if geminate {
+            if token.romaji.starts_with("ch") {
+                romaji.push('t');
+            } else if let Some(consonant) = token.romaji.chars().next().filter(|c| c.is_ascii_alphabetic() && !"aiueon".contains(*c)) {
+                romaji.push(consonant);
+            }
+        }
+        romaji.push_str(&token.romaji);
+        geminate = token.surface.ends_with(['っ', 'ッ']);
+    }
+    romaji
+}
+
+/// Kanji, including the repetition mark 々
+pub fn is_kanji(c: char) -> bool {
+    matches!(c as u32, 0x3400..=0x4DBF | 0x4E00..=0x9FFF | 0xF900..=0xFAFF) || c == '々'
+}
+
+fn is_kana(c: char) -> bool {
+    matches!(c as u32, 0x3041..=0x3096 | 0x30A1..=0x30FA | 0x30FC)
+}
+
+/// Katakana to hiragana; other characters are left as they are
+pub fn to_hiragana(text: &str) -> String {
+    text.chars().map(hiragana).collect()
+}
+
+fn hiragana(c: char) -> char {
+    match c as u32 {
+        0x30A1..=0x30F6 => char::from_u32(c as u32 - 0x60).unwrap_or(c),
+        _ => c,
+    }
+}
+
+/// Put the reading over the kanji only, leaving kana before and after the
+/// kanji (okurigana) without furigana: 取り扱い/とりあつかい → 取[と] り 扱[あつか] い
+fn split_ruby(surface: &str, reading: &str) -> Vec<RubyPart> {
+    let surface_chars: Vec<char> = surface.chars().collect();
+    let reading_chars: Vec<char> = reading.chars().collect();
+    split_ruby_chars(&surface_chars, &reading_chars).unwrap_or_else(|| {
+        vec![RubyPart {
+            text: surface.to_string(),
+            reading: Some(reading.to_string()),
+        }]
+    })
+}
+
+fn split_ruby_chars(surface: &[char], reading: &[char]) -> Option<Vec<RubyPart>> {
+    let Some(first_kanji) = surface.iter().position(|&c| !is_kana(c)) else {
+        return (to_hiragana(&surface.iter().collect::<String>()) == reading.iter().collect::<String>()).then(|| {
+            vec![RubyPart {
+                text: surface.iter().collect(),
+                reading: None,
+            }]
+        });
+    };
+
+    // Leading kana must match the reading exactly
+    let prefix: String = surface[..first_kanji].iter().collect();
+    let reading_prefix: String = reading.get(..first_kanji)?.iter().collect();
+    if to_hiragana(&prefix) != reading_prefix {
+        return None;
+    }
+    let surface = &surface[first_kanji..];
+    let reading = &reading[first_kanji..];
+
+    let kanji_len = surface.iter().position(|&c| is_kana(c)).unwrap_or(surface.len());
+    let kanji: String = surface[..kanji_len].iter().collect();
+    let rest = &surface[kanji_len..];
+
+    let mut parts = Vec::new();
+    if !prefix.is_empty() {
+        parts.push(RubyPart { text: prefix, reading: None });
+    }
+
+    if rest.is_empty() {
+        if reading.is_empty() {
+            return None;
+        }
+        parts.push(RubyPart {
+            text: kanji,
+            reading: Some(reading.iter().collect()),
+        });
+        return Some(parts);
+    }
+
+    // The kana after this run of kanji: find where they start in the reading.
+    // Each kanji reads as at least one kana, so search from there.
+    let next_kana = hiragana(rest[0]);
+    for split in kanji_len..reading.len() {
+        if reading[split] != next_kana {
+            continue;
+        }
+        if let Some(tail) = split_ruby_chars(rest, &reading[split..]) {
+            parts.push(RubyPart {
+                text: kanji,
+                reading: Some(reading[..split].iter().collect()),
+            });
+            parts.extend(tail);
+            return Some(parts);
+        }
+    }
+    None
+}
+
+/// Hepburn romaji for a kana reading. The IPADIC pronunciation (which writes
+/// long vowels as ー, e.g. トーキョー for トウキョウ) is used to spell long vowels
+/// with macrons, so 東京 is tōkyō but 思う stays omou and 映画 stays eiga.
+fn hepburn(reading: &str, pronunciation: Option<&str>) -> String {
+    let kana: Vec<char> = reading.chars().map(hiragana).collect();
+    let spoken: Option<Vec<char>> = pronunciation
+        .map(|p| p.chars().collect::<Vec<char>>())
+        .filter(|p| p.len() == kana.len());
+
+    let mut romaji = String::new();
+    let mut i = 0;
+    while i < kana.len() {
+        let c = kana[i];
+        let next = kana.get(i + 1).copied();
+
+        // Long vowel: ー in the reading, or ー in the pronunciation after o/u
+        let long = c == 'ー' || spoken.as_ref().is_some_and(|s| s[i] == 'ー' && matches!(c, 'う' | 'お' | 'ー'));
+        if long {
+            if let Some(last) = romaji.pop() {
+                romaji.push(match last {
+                    'a' => 'ā',
+                    'i' => 'ī',
+                    'u' => 'ū',
+                    'e' => 'ē',
+                    'o' => 'ō',
+                    other => other,
+                });
+            }
+            i += 1;
+            continue;
+        }
+
+        // Small tsu doubles the next consonant (っち → tchi)
+        if c == 'っ' {
+            if let Some(syllable) = next.and_then(|n| syllable(n, kana.get(i + 2).copied())) {
+                let syllable = syllable.0;
+                if syllable.starts_with("ch") {
+                    romaji.push('t');
+                } else if let Some(consonant) = syllable.chars().next().filter(|c| !"aiueo".contains(*c)) {
+                    romaji.push(consonant);
+                }
+            }
+            i += 1;
+            continue;
+        }
+
+        // ん before a vowel or y is written n' (kin'en, not kinen)
+        if c == 'ん' {
+            romaji.push('n');
+            let before_vowel = next
+                .and_then(|n| syllable(n, None))
+                .is_some_and(|(s, _)| s.starts_with(['a', 'i', 'u', 'e', 'o', 'y']));
+            if before_vowel {
+                romaji.push('\'');
+            }
+            i += 1;
+            continue;
+        }
+
+        match syllable(c, next) {
+            Some((text, used)) => {
+                romaji.push_str(text);
+                i += used;
+            }
+            None => {
+                romaji.push(punctuation(c));
+                i += 1;
+            }
+        }
+    }
+    romaji
+}
+
+fn punctuation(c: char) -> char {
+    match c {
+        '。' => '.',
+        '、' => ',',
+        '！' => '!',
+        '？' => '?',
+        '「' | '」' | '『' | '』' => '"',
+        '（' => '(',
+        '）' => ')',
+        '・' => ' ',
+        '　' => ' ',
+        c => c,
+    }
+}
+
+/// Romaji for the hiragana at `c` (with a following small ゃ/ゅ/ょ etc.), and how
+/// many characters it used
+fn syllable(c: char, next: Option<char>) -> Option<(&'static str, usize)> {
+    if let Some(small) = next.filter(|n| matches!(n, 'ゃ' | 'ゅ' | 'ょ' | 'ぁ' | 'ぃ' | 'ぇ' | 'ぉ')) {
+        if let Some(digraph) = digraph(c, small) {
+            return Some((digraph, 2));
+        }
+    }
+    let single = match c {
+        'あ' => "a", 'い' => "i", 'う' => "u", 'え' => "e", 'お' => "o",
+        'か' => "ka", 'き' => "ki", 'く' => "ku", 'け' => "ke", 'こ' => "ko",
+        'が' => "ga", 'ぎ' => "gi", 'ぐ' => "gu", 'げ' => "ge", 'ご' => "go",
+        'さ' => "sa", 'し' => "shi", 'す' => "su", 'せ' => "se", 'そ' => "so",
+        'ざ' => "za", 'じ' => "ji", 'ず' => "zu", 'ぜ' => "ze", 'ぞ' => "zo",
+        'た' => "ta", 'ち' => "chi", 'つ' => "tsu", 'て' => "te", 'と' => "to",
+        'だ' => "da", 'ぢ' => "ji", 'づ' => "zu", 'で' => "de", 'ど' => "do",
+        'な' => "na", 'に' => "ni", 'ぬ' => "nu", 'ね' => "ne", 'の' => "no",
+        'は' => "ha", 'ひ' => "hi", 'ふ' => "fu", 'へ' => "he", 'ほ' => "ho",
+        'ば' => "ba", 'び' => "bi", 'ぶ' => "bu", 'べ' => "be", 'ぼ' => "bo",
+        'ぱ' => "pa", 'ぴ' => "pi", 'ぷ' => "pu", 'ぺ' => "pe", 'ぽ' => "po",
+        'ま' => "ma", 'み' => "mi", 'む' => "mu", 'め' => "me", 'も' => "mo",
+        'や' => "ya", 'ゆ' => "yu", 'よ' => "yo",
+        'ら' => "ra", 'り' => "ri", 'る' => "ru", 'れ' => "re", 'ろ' => "ro",
+        'わ' => "wa", 'ゐ' => "i", 'ゑ' => "e", 'を' => "o",
+        'ゔ' => "vu",
+        'ぁ' => "a", 'ぃ' => "i", 'ぅ' => "u", 'ぇ' => "e", 'ぉ' => "o",
+        'ゃ' => "ya", 'ゅ' => "yu", 'ょ' => "yo", 'ゎ' => "wa",
+        _ => return None,
+    };
+    Some((single, 1))
+}
+
+fn digraph(c: char, small: char) -> Option<&'static str> {
+    let stem = match c {
+        'き' => "ky", 'ぎ' => "gy", 'に' => "ny", 'ひ' => "hy", 'び' => "by",
+        'ぴ' => "py", 'み' => "my", 'り' => "ry",
+        'し' => "sh", 'じ' => "j", 'ち' => "ch", 'ぢ' => "j",
+        // Loanword sounds: ティ, ファ, ウェ, ヴァ, ジェ, チェ, シェ
+        'て' if small == 'ぃ' => return Some("ti"),
+        'で' if small == 'ぃ' => return Some("di"),
+        'ふ' => "f", 'う' => "w", 'ゔ' => "v",
+        _ => return None,
+    };
+    let vowel = match small {
+        'ゃ' => "a",
+        'ゅ' => "u",
+        'ょ' => "o",
+        'ぁ' => "a",
+        'ぃ' => "i",
+        'ぇ' => "e",
+        'ぉ' => "o",
+        _ => return None,
+    };
+    // ky + a etc.; shy/jy/chy are written without the y
+    Some(match (stem, vowel) {
+        ("ky", "a") => "kya", ("ky", "u") => "kyu", ("ky", "o") => "kyo",
+        ("gy", "a") => "gya", ("gy", "u") => "gyu", ("gy", "o") => "gyo",
+        ("ny", "a") => "nya", ("ny", "u") => "nyu", ("ny", "o") => "nyo",
+        ("hy", "a") => "hya", ("hy", "u") => "hyu", ("hy", "o") => "hyo",
+        ("by", "a") => "bya", ("by", "u") => "byu", ("by", "o") => "byo",
+        ("py", "a") => "pya", ("py", "u") => "pyu", ("py", "o") => "pyo",
+        ("my", "a") => "mya", ("my", "u") => "myu", ("my", "o") => "myo",
+        ("ry", "a") => "rya", ("ry", "u") => "ryu", ("ry", "o") => "ryo",
+        ("sh", "a") => "sha", ("sh", "u") => "shu", ("sh", "o") => "sho", ("sh", "e") => "she",
+        ("j", "a") => "ja", ("j", "u") => "ju", ("j", "o") => "jo", ("j", "e") => "je",
+        ("ch", "a") => "cha", ("ch", "u") => "chu", ("ch", "o") => "cho", ("ch", "e") => "che",
+        ("f", "a") => "fa", ("f", "i") => "fi", ("f", "e") => "fe", ("f", "o") => "fo",
+        ("w", "i") => "wi", ("w", "e") => "we", ("w", "o") => "wo",
+        ("v", "a") => "va", ("v", "i") => "vi", ("v", "e") => "ve", ("v", "o") => "vo",
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ruby(parts: &[(&str, Option<&str>)]) -> Vec<RubyPart> {
+        parts
+            .iter()
+            .map(|(text, reading)| RubyPart {
+                text: text.to_string(),
+                reading: reading.map(str::to_string),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn romaji_doubles_consonant_after_small_tsu() {
+        assert_eq!(hepburn("がっこう", Some("ガッコー")), "gakkō");
+        assert_eq!(hepburn("まっちゃ", None), "matcha");
+        assert_eq!(hepburn("きって", None), "kitte");
+    }
+
+    #[test]
+    fn romaji_separates_n_before_vowels_and_y() {
+        assert_eq!(hepburn("しんぶん", None), "shinbun");
+        assert_eq!(hepburn("きんえん", None), "kin'en");
+        assert_eq!(hepburn("こんや", None), "kon'ya");
+    }
+
+    #[test]
+    fn romaji_long_vowels_follow_pronunciation() {
+        assert_eq!(hepburn("とうきょう", Some("トーキョー")), "tōkyō");
+        assert_eq!(hepburn("おもう", Some("オモウ")), "omou");
+        assert_eq!(hepburn("えいが", Some("エーガ")), "eiga");
+        assert_eq!(hepburn("コーヒー", None), "kōhī");
+    }
+
+    #[test]
+    fn romaji_digraphs_and_loanwords() {
+        assert_eq!(hepburn("しゃしん", None), "shashin");
+        assert_eq!(hepburn("ジェット", None), "jetto");
+        assert_eq!(hepburn("パーティー", None), "pātī");
+        assert_eq!(hepburn("ファイル", None), "fairu");
+    }
+
+    #[test]
+    fn furigana_skips_okurigana() {
+        assert_eq!(
+            split_ruby("取り扱い", "とりあつかい"),
+            ruby(&[("取", Some("と")), ("り", None), ("扱", Some("あつか")), ("い", None)])
+        );
+        assert_eq!(split_ruby("行っ", "いっ"), ruby(&[("行", Some("い")), ("っ", None)]));
+        assert_eq!(split_ruby("お茶", "おちゃ"), ruby(&[("お", None), ("茶", Some("ちゃ"))]));
+        assert_eq!(split_ruby("東京", "とうきょう"), ruby(&[("東京", Some("とうきょう"))]));
+    }
+
+    #[test]
+    fn furigana_falls_back_to_whole_word_when_kana_do_not_match() {
+        assert_eq!(split_ruby("食べる", "のむ"), ruby(&[("食べる", Some("のむ"))]));
+    }
+}
@@ -0,0 +1,444 @@
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::OnceLock;
+
+use crate::language::{normalize_tag, same_language};
+use crate::placeholders::Placeholders;
+
+/// A required translation for a term, or a term that must stay as written
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TermEntry {
+    /// Language of `source`, or `*` for any language
+    pub source_lang: String,
+    /// Language `target` is written in, or `*` for any language
+    pub target_lang: String,
+    pub source: String,
+    /// Required translation; `None` means do not translate
+    pub target: Option<String>,
+    /// Patterns for `source` and `expected()`, compiled once and shared by clones
+    #[serde(skip)]
+    patterns: OnceLock<(Option<TermPattern>, Option<TermPattern>)>,
+}
+
+impl TermEntry {
+    pub fn new(source_lang: String, target_lang: String, source: String, target: Option<String>) -> Self {
+        Self {
+            source_lang,
+            target_lang,
+            source,
+            target,
+            patterns: OnceLock::new(),
+        }
+    }
+
+    fn patterns(&self) -> &(Option<TermPattern>, Option<TermPattern>) {
+        self.patterns
+            .get_or_init(|| (TermPattern::new(&self.source), TermPattern::new(self.expected())))
+    }
+
+    /// Occurrences of the term in source text
+    fn find_source(&self, text: &str) -> Vec<(usize, usize)> {
+        self.patterns().0.as_ref().map_or_else(Vec::new, |pattern| pattern.find(text))
+    }
+
+    /// Occurrences of the required form in translated text
+    fn find_expected(&self, text: &str) -> Vec<(usize, usize)> {
+        self.patterns().1.as_ref().map_or_else(Vec::new, |pattern| pattern.find(text))
+    }
+
+    fn applies_to(&self, source_lang: &str, target_lang: &str) -> bool {
+        lang_matches(&self.source_lang, source_lang) && lang_matches(&self.target_lang, target_lang)
+    }
+
+    /// Text the term must appear as in the translation
+    pub fn expected(&self) -> &str {
+        self.target.as_deref().unwrap_or(&self.source)
+    }
+
+    fn same_term(&self, other: &TermEntry) -> bool {
+        self.source_lang.eq_ignore_ascii_case(&other.source_lang)
+            && self.target_lang.eq_ignore_ascii_case(&other.target_lang)
+            && self.source.to_lowercase() == other.source.to_lowercase()
+    }
+}
+
+fn lang_matches(pattern: &str, lang: &str) -> bool {
+    pattern == "*" || same_language(pattern, lang)
+}
+
+/// Product names and jargon with fixed translations
+#[derive(Serialize, Deserialize, Default, Clone, Debug)]
+pub struct Glossary {
+    pub terms: Vec<TermEntry>,
+}
+
+impl Glossary {
+    /// Load a glossary saved with [`Glossary::save`]; a missing file is an empty glossary
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let glossary: Self = match std::fs::read_to_string(path) {
+            Ok(json) => serde_json::from_str(&json).map_err(|e| format!("Invalid glossary {}: {}", path.display(), e))?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Self::default(),
+            Err(e) => return Err(format!("Failed to read glossary {}: {}", path.display(), e)),
+        };
+        glossary.compile();
+        Ok(glossary)
+    }
+
+    /// Compile every term's patterns now, so translations (which work on
+    /// clones of the glossary) do not compile them again
+    pub fn compile(&self) {
+        for term in &self.terms {
+            term.patterns();
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create glossary directory: {}", e))?;
+        }
+        let json = serde_json::to_string_pretty(self).map_err(|e| format!("Failed to serialize glossary: {}", e))?;
+        std::fs::write(path, json).map_err(|e| format!("Failed to write glossary {}: {}", path.display(), e))
+    }
+
+    /// Parse a CSV, TBX or JSON file, chosen by extension
+    pub fn import_file(path: &Path) -> Result<Self, String> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        let extension = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .unwrap_or_default();
+        Self::parse(&contents, extension)
+    }
+
+    /// Parse glossary contents in the given format (`csv`, `tbx`/`xml` or `json`)
+    pub fn parse(contents: &str, format: &str) -> Result<Self, String> {
+        let glossary: Self = match format.to_ascii_lowercase().as_str() {
+            "csv" => Self::from_csv(contents)?,
+            "tbx" | "xml" => Self::from_tbx(contents)?,
+            "json" => serde_json::from_str(contents).map_err(|e| format!("Invalid glossary JSON: {}", e))?,
+            other => return Err(format!("Unsupported glossary format '{}': use .csv, .tbx or .json", other)),
+        };
+        glossary.compile();
+        Ok(glossary)
+    }
+
+    /// CSV with the header `source_lang,target_lang,source,target`.
+    /// An empty target (or one equal to the source) marks a do-not-translate term;
+    /// `*` in a language column matches any language.
+    pub fn from_csv(contents: &str) -> Result<Self, String> {
+        let mut rows = parse_csv(contents).into_iter();
+        let header: Vec<String> = rows
+            .next()
+            .ok_or("Glossary CSV is empty")?
+            .iter()
+            .map(|column| column.trim().to_ascii_lowercase())
+            .collect();
+
+        let column = |name: &str| {
+            header
+                .iter()
+                .position(|column| column == name)
+                .ok_or_else(|| format!("Glossary CSV is missing the '{}' column", name))
+        };
+        let (source_lang_col, target_lang_col) = (column("source_lang")?, column("target_lang")?);
+        let (source_col, target_col) = (column("source")?, column("target")?);
+
+        let mut terms = Vec::new();
+        for (line, row) in rows.enumerate() {
+            let field = |i: usize| row.get(i).map(|value| value.trim()).unwrap_or_default();
+            let source = field(source_col);
+            if source.is_empty() {
+                continue;
+            }
+
+            let target = field(target_col);
+            terms.push(TermEntry::new(
+                parse_lang(field(source_lang_col)).ok_or_else(|| format!("Invalid source_lang on row {}", line + 2))?,
+                parse_lang(field(target_lang_col)).ok_or_else(|| format!("Invalid target_lang on row {}", line + 2))?,
+                source.to_string(),
+                (!target.is_empty() && target != source).then(|| target.to_string()),
+            ));
+        }
+
+        Ok(Self { terms })
+    }
+
+    /// TBX (TBX-Basic `termEntry` or TBX v3 `conceptEntry`). Every pair of
+    /// languages within a concept becomes a term entry in both directions.
+    pub fn from_tbx(contents: &str) -> Result<Self, String> {
+        let mut reader = Reader::from_str(contents);
+        reader.config_mut().trim_text(true);
+
+        let mut terms = Vec::new();
+        // Terms of the current concept, grouped by language
+        let mut concept: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        let mut current_lang: Option<String> = None;
+        let mut in_term = false;
+        let mut term_text = String::new();
+
+        loop {
+            match reader.read_event() {
+                Ok(Event::Start(e)) => match e.local_name().as_ref() {
+                    b"termEntry" | b"conceptEntry" => concept.clear(),
+                    b"langSet" | b"langSec" => {
+                        current_lang = e
+                            .try_get_attribute("xml:lang")
+                            .ok()
+                            .flatten()
+                            .and_then(|attr| attr.unescape_value().ok())
+                            .and_then(|lang| normalize_tag(&lang));
+                    }
+                    b"term" => {
+                        in_term = true;
+                        term_text.clear();
+                    }
+                    _ => {}
+                },
+                Ok(Event::Text(text)) if in_term => {
+                    let text = text.unescape().map_err(|e| format!("Invalid TBX text: {}", e))?;
+                    term_text.push_str(&text);
+                }
+                Ok(Event::End(e)) => match e.local_name().as_ref() {
+                    b"term" => {
+                        in_term = false;
+                        if let (Some(lang), false) = (&current_lang, term_text.trim().is_empty()) {
+                            concept
+                                .entry(lang.clone())
+                                .or_default()
+                                .push(term_text.trim().to_string());
+                        }
+                    }
+                    b"langSet" | b"langSec" => current_lang = None,
+                    b"termEntry" | b"conceptEntry" => {
+                        for (source_lang, sources) in &concept {
+                            for (target_lang, targets) in &concept {
+                                if source_lang == target_lang {
+                                    continue;
+                                }
+                                // The first term of a language is the preferred one
+                                for source in sources {
+                                    terms.push(TermEntry::new(
+                                        source_lang.clone(),
+                                        target_lang.clone(),
+                                        source.clone(),
+                                        (targets[0] != *source).then(|| targets[0].clone()),
+                                    ));
+                                }
+                            }
+                        }
+                        concept.clear();
+                    }
+                    _ => {}
+                },
+                Ok(Event::Eof) => break,
+                Err(e) => {
+                    return Err(format!(
+                        "Invalid TBX at position {}: {}",
+                        reader.error_position(),
+                        e
+                    ))
+                }
+                _ => {}
+            }
+        }
+
+        Ok(Self { terms })
+    }
+
+    /// Add terms from `other`, replacing entries for the same term and language pair
+    pub fn merge(&mut self, other: Glossary) {
+        for term in other.terms {
+            match self.terms.iter_mut().find(|existing| existing.same_term(&term)) {
+                Some(existing) => *existing = term,
+                None => self.terms.push(term),
+            }
+        }
+    }
+
+    /// Terms for this language pair that occur in `text`, longest first
+    fn matching_terms(&self, text: &str, source_lang: &str, target_lang: &str) -> Vec<&TermEntry> {
+        let mut terms: Vec<&TermEntry> = self
+            .terms
+            .iter()
+            .filter(|term| term.applies_to(source_lang, target_lang))
+            .filter(|term| !term.find_source(text).is_empty())
+            .collect();
+        terms.sort_by_key(|term| std::cmp::Reverse(term.source.chars().count()));
+        terms
+    }
+
+    /// Replace glossary terms in `text` with placeholders that restore to the
+    /// required translation (or the term itself for do-not-translate entries)
+    pub fn protect(
+        &self,
+        text: &str,
+        source_lang: &str,
+        target_lang: &str,
+        placeholders: &mut Placeholders,
+    ) -> String {
+        let mut protected = text.to_string();
+
+        for term in self.matching_terms(text, source_lang, target_lang) {
+            let ranges = term.find_source(&protected);
+            if ranges.is_empty() {
+                continue;
+            }
+            let token = placeholders.insert(term.expected());
+            for (start, end) in ranges.into_iter().rev() {
+                protected.replace_range(start..end, &token);
+            }
+        }
+
+        protected
+    }
+
+    /// Warnings for glossary terms in `original` whose required form is missing from `translated`
+    pub fn check(&self, original: &str, translated: &str, source_lang: &str, target_lang: &str) -> Vec<String> {
+        self.matching_terms(original, source_lang, target_lang)
+            .into_iter()
+            .filter(|term| term.find_expected(translated).is_empty())
+            .map(|term| match &term.target {
+                Some(target) => format!("Glossary: \"{}\" should be translated as \"{}\"", term.source, target),
+                None => format!("Glossary: \"{}\" should be kept untranslated", term.source),
+            })
+            .collect()
+    }
+}
+
+/// Case-insensitive, whole-word matcher for a term. Word boundaries only
+/// apply to alphanumeric edges, so CJK terms match anywhere.
+#[derive(Clone, Debug)]
+struct TermPattern {
+    regex: Regex,
+    starts_word: bool,
+    ends_word: bool,
+}
+
+fn is_word(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+impl TermPattern {
+    fn new(term: &str) -> Option<Self> {
+        if term.is_empty() {
+            return None;
+        }
+        let regex = Regex::new(&format!("(?i){}", regex::escape(term))).ok()?;
+        Some(Self {
+            regex,
+            starts_word: term.chars().next().is_some_and(is_word),
+            ends_word: term.chars().next_back().is_some_and(is_word),
+        })
+    }
+
+    /// Byte ranges of the term in `text`
+    fn find(&self, text: &str) -> Vec<(usize, usize)> {
+        self.regex
+            .find_iter(text)
+            .filter(|m| {
+                let joined_before = self.starts_word && text[..m.start()].chars().next_back().is_some_and(is_word);
+                let joined_after = self.ends_word && text[m.end()..].chars().next().is_some_and(is_word);
+                !joined_before && !joined_after
+            })
+            .map(|m| (m.start(), m.end()))
+            .collect()
+    }
+}
+
+fn parse_lang(value: &str) -> Option<String> {
+    match value {
+        "" | "*" => Some("*".to_string()),
+        tag => normalize_tag(tag),
+    }
+}
+
+/// Minimal RFC 4180 parser: quoted fields, doubled quotes, CRLF or LF rows
+fn parse_csv(contents: &str) -> Vec<Vec<String>> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = contents.trim_start_matches('\u{feff}').chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match (c, in_quotes) {
+            ('"', true) if chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            ('"', true) => in_quotes = false,
+            ('"', false) if field.is_empty() => in_quotes = true,
+            (',', false) => row.push(std::mem::take(&mut field)),
+            ('\r', false) => {}
+            ('\n', false) => {
+                row.push(std::mem::take(&mut field));
+                if row.iter().any(|value| !value.is_empty()) {
+                    rows.push(std::mem::take(&mut row));
+                }
+                row.clear();
+            }
+            (c, _) => field.push(c),
+        }
+    }
+
+    row.push(field);
+    if row.iter().any(|value| !value.is_empty()) {
+        rows.push(row);
+    }
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn glossary() -> Glossary {
+        Glossary::parse(
+            "source_lang,target_lang,source,target\nen,ja,Workspace,ワークスペース\n*,*,Acme,\nja,en,稟議,ringi approval\n",
+            "csv",
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn protects_whole_words_case_insensitively() {
+        let mut placeholders = Placeholders::new();
+        let protected = glossary().protect("Open the workspace, not workspaces.", "en", "ja", &mut placeholders);
+        assert_eq!(protected, "Open the ⟦0⟧, not workspaces.");
+        assert_eq!(placeholders.restore(&protected).0, "Open the ワークスペース, not workspaces.");
+    }
+
+    #[test]
+    fn cjk_terms_match_inside_words() {
+        let mut placeholders = Placeholders::new();
+        let protected = glossary().protect("稟議書を出してください", "ja", "en", &mut placeholders);
+        assert_eq!(protected, "⟦0⟧書を出してください");
+    }
+
+    #[test]
+    fn warns_about_terms_missing_from_translation() {
+        let glossary = glossary();
+        assert!(glossary
+            .check("Ask Acme about the workspace", "Acmeにワークスペースについて聞く", "en", "ja")
+            .is_empty());
+        assert_eq!(
+            glossary.check("Ask Acme about the workspace", "アクミーに作業場について聞く", "en", "ja"),
+            vec![
+                "Glossary: \"Workspace\" should be translated as \"ワークスペース\"".to_string(),
+                "Glossary: \"Acme\" should be kept untranslated".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn clones_keep_compiled_patterns() {
+        let glossary = glossary();
+        let clone = glossary.clone();
+        assert!(clone.terms.iter().all(|term| term.patterns.get().is_some()));
+    }
+}
@@ -0,0 +1,468 @@
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, RwLock};
+use std::time::{Duration, UNIX_EPOCH};
+use tauri::{AppHandle, Manager};
+
+use crate::commands::AppState;
+use crate::glossary::Glossary;
+use crate::history::now_millis;
+
+/// Where the team glossary is published
+#[derive(Clone, Debug, PartialEq)]
+pub enum TeamSource {
+    File(PathBuf),
+    Url(String),
+}
+
+impl TeamSource {
+    pub fn parse(value: &str) -> Self {
+        let lower = value.to_ascii_lowercase();
+        if lower.starts_with("http://") || lower.starts_with("https://") {
+            TeamSource::Url(value.to_string())
+        } else {
+            TeamSource::File(PathBuf::from(value))
+        }
+    }
+
+    fn describe(&self) -> String {
+        match self {
+            TeamSource::File(path) => path.display().to_string(),
+            TeamSource::Url(url) => url.clone(),
+        }
+    }
+
+    /// Format from the file or URL path extension
+    fn format_hint(&self) -> String {
+        let path = match self {
+            TeamSource::File(path) => path.to_string_lossy().to_string(),
+            TeamSource::Url(url) => url.split(['?', '#']).next().unwrap_or_default().to_string(),
+        };
+        Path::new(&path)
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .unwrap_or_default()
+            .to_ascii_lowercase()
+    }
+}
+
+/// Last successfully loaded team glossary, kept on disk for offline use
+#[derive(Serialize, Deserialize, Default, Clone)]
+struct TeamCache {
+    source: String,
+    /// HTTP `ETag` of the cached copy
+    etag: Option<String>,
+    /// HTTP `Last-Modified`, or the file's modification time in seconds
+    last_modified: Option<String>,
+    /// Unix milliseconds of the last successful check
+    synced_at: Option<i64>,
+    glossary: Glossary,
+}
+
+impl TeamCache {
+    fn load(path: &Path) -> Option<Self> {
+        let json = std::fs::read_to_string(path).ok()?;
+        let cache: Self = serde_json::from_str(&json).ok()?;
+        cache.glossary.compile();
+        Some(cache)
+    }
+
+    fn save(&self, path: &Path) -> Result<(), String> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create cache directory: {}", e))?;
+        }
+        let json = serde_json::to_string(self).map_err(|e| format!("Failed to serialize team glossary: {}", e))?;
+        std::fs::write(path, json).map_err(|e| format!("Failed to write team glossary cache: {}", e))
+    }
+}
+
+/// Team glossary sync state, for the glossary panel
+#[derive(Serialize, Clone)]
+pub struct TeamGlossaryStatus {
+    pub source: Option<String>,
+    pub terms: usize,
+    /// Unix milliseconds of the last successful check
+    pub synced_at: Option<i64>,
+    /// Error from the last sync attempt; the cached copy is still in use
+    pub error: Option<String>,
+}
+
+/// The shared glossary, refreshed from its source and cached for offline use
+pub struct TeamGlossary {
+    source: Option<TeamSource>,
+    cache_path: Option<PathBuf>,
+    cache: RwLock<TeamCache>,
+    last_error: Mutex<Option<String>>,
+}
+
+enum Fetched {
+    Unchanged,
+    Updated(TeamCache),
+}
+
+impl TeamGlossary {
+    /// Start from the cached copy, if it was fetched from the same source
+    pub fn new(source: Option<TeamSource>, cache_path: Option<PathBuf>) -> Self {
+        let cache = match (&source, &cache_path) {
+            (Some(source), Some(path)) => TeamCache::load(path)
+                .filter(|cache| cache.source == source.describe())
+                .unwrap_or_default(),
+            _ => TeamCache::default(),
+        };
+
+        Self {
+            source,
+            cache_path,
+            cache: RwLock::new(cache),
+            last_error: Mutex::new(None),
+        }
+    }
+
+    pub fn is_configured(&self) -> bool {
+        self.source.is_some()
+    }
+
+    pub fn glossary(&self) -> Glossary {
+        self.cache
+            .read()
+            .map(|cache| cache.glossary.clone())
+            .unwrap_or_default()
+    }
+
+    pub fn status(&self) -> TeamGlossaryStatus {
+        let (terms, synced_at) = self
+            .cache
+            .read()
+            .map(|cache| (cache.glossary.terms.len(), cache.synced_at))
+            .unwrap_or((0, None));
+
+        TeamGlossaryStatus {
+            source: self.source.as_ref().map(TeamSource::describe),
+            terms,
+            synced_at,
+            error: self.last_error.lock().ok().and_then(|error| error.clone()),
+        }
+    }
+
+    /// Check the source for changes. Returns whether the glossary changed;
+    /// on failure the last cached copy stays in use.
+    pub async fn sync(&self) -> Result<bool, String> {
+        let Some(source) = &self.source else {
+            return Ok(false);
+        };
+
+        let cached = self
+            .cache
+            .read()
+            .map_err(|e| format!("Failed to lock team glossary: {}", e))?
+            .clone();
+
+        let fetched = fetch(source, &cached).await;
+        if let Ok(mut last_error) = self.last_error.lock() {
+            *last_error = fetched.as_ref().err().cloned();
+        }
+
+        let updated = match fetched? {
+            Fetched::Unchanged => {
+                let mut cache = self
+                    .cache
+                    .write()
+                    .map_err(|e| format!("Failed to lock team glossary: {}", e))?;
+                cache.synced_at = Some(now_millis());
+                if let Some(path) = &self.cache_path {
+                    if let Err(e) = cache.save(path) {
+                        eprintln!("⚠ {}", e);
+                    }
+                }
+                false
+            }
+            Fetched::Updated(fresh) => {
+                if let Some(path) = &self.cache_path {
+                    if let Err(e) = fresh.save(path) {
+                        eprintln!("⚠ {}", e);
+                    }
+                }
+                println!(
+                    "[INFO] Team glossary updated: {} terms from {}",
+                    fresh.glossary.terms.len(),
+                    fresh.source
+                );
+                *self
+                    .cache
+                    .write()
+                    .map_err(|e| format!("Failed to lock team glossary: {}", e))? = fresh;
+                true
+            }
+        };
+
+        Ok(updated)
+    }
+}
+
+/// Load the source unless it still matches the cached validators
+async fn fetch(source: &TeamSource, cached: &TeamCache) -> Result<Fetched, String> {
+    let same_source = cached.source == source.describe();
+
+    match source {
+        TeamSource::File(path) => {
+            let modified = std::fs::metadata(path)
+                .and_then(|metadata| metadata.modified())
+                .map_err(|e| format!("Cannot read team glossary {}: {}", path.display(), e))?
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs().to_string())
+                .ok();
+
+            if same_source && modified.is_some() && modified == cached.last_modified {
+                return Ok(Fetched::Unchanged);
+            }
+
+            Ok(Fetched::Updated(TeamCache {
+                source: source.describe(),
+                etag: None,
+                last_modified: modified,
+                synced_at: Some(now_millis()),
+                glossary: Glossary::import_file(path)?,
+            }))
+        }
+        TeamSource::Url(url) => {
+            let client = reqwest::Client::builder()
+                .timeout(Duration::from_secs(30))
+                .build()
+                .map_err(|e| format!("HTTP client error: {}", e))?;
+
+            let mut request = client.get(url);
+            if same_source {
+                if let Some(etag) = &cached.etag {
+                    request = request.header(reqwest::header::IF_NONE_MATCH, etag);
+                }
+                if let Some(last_modified) = &cached.last_modified {
+                    request = request.header(reqwest::header::IF_MODIFIED_SINCE, last_modified);
+                }
+            }
+
+            let response = request.send().await.map_err(|e| {
+                if e.is_connect() || e.is_timeout() {
+                    format!("Cannot reach team glossary at {}; using the cached copy", url)
+                } else {
+                    format!("Team glossary request failed: {}", e)
+                }
+            })?;
+
+            let status = response.status();
+            if status == reqwest::StatusCode::NOT_MODIFIED {
+                return Ok(Fetched::Unchanged);
+            }
+            if !status.is_success() {
+                return Err(format!("Team glossary request failed: HTTP {}", status));
+            }
+
+            let header = |name: reqwest::header::HeaderName| {
+                response
+                    .headers()
+                    .get(name)
+                    .and_then(|value| value.to_str().ok())
+                    .map(str::to_string)
+            };
+            let etag = header(reqwest::header::ETAG);
+            let last_modified = header(reqwest::header::LAST_MODIFIED);
+            let content_type = header(reqwest::header::CONTENT_TYPE).unwrap_or_default();
+
+            let body = response
+                .text()
+                .await
+                .map_err(|e| format!("Failed to read team glossary: {}", e))?;
+
+            // The URL extension wins; fall back to the content type
+            let mut format = source.format_hint();
+            if format.is_empty() {
+                format = if content_type.contains("csv") {
+                    "csv".to_string()
+                } else if content_type.contains("json") {
+                    "json".to_string()
+                } else {
+                    "tbx".to_string()
+                };
+            }
+
+            Ok(Fetched::Updated(TeamCache {
+                source: source.describe(),
+                etag,
+                last_modified,
+                synced_at: Some(now_millis()),
+                glossary: Glossary::parse(&body, &format)?,
+            }))
+        }
+    }
+}
+
+/// Sync the team glossary now and then every `interval`
+pub fn spawn_refresh(app: &AppHandle, interval: Duration) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let state = app.state::<AppState>();
+            if let Err(e) = state.team_glossary.sync().await {
+                eprintln!("⚠ {}", e);
+            }
+            tokio::time::sleep(interval).await;
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    const CSV_V1: &str = "source_lang,target_lang,source,target\nen,ja,Workspace,ワークスペース\n";
+    const CSV_V2: &str = "source_lang,target_lang,source,target\nen,ja,Workspace,ワークスペース\nen,ja,Channel,チャンネル\n";
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("team-glossary-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// Serve HTTP on localhost, answering each request (head and headers as
+    /// text) with `respond`; `None` closes the connection without a response
+    fn serve(respond: impl Fn(&str) -> Option<String> + Send + 'static) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/glossary.csv", listener.local_addr().unwrap());
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(mut stream) = stream else { continue };
+                let mut request = Vec::new();
+                let mut buffer = [0u8; 1024];
+                while !request.ends_with(b"\r\n\r\n") {
+                    match stream.read(&mut buffer) {
+                        Ok(0) | Err(_) => break,
+                        Ok(n) => request.extend_from_slice(&buffer[..n]),
+                    }
+                }
+                if let Some(response) = respond(&String::from_utf8_lossy(&request).to_ascii_lowercase()) {
+                    let _ = stream.write_all(response.as_bytes());
+                }
+            }
+        });
+        url
+    }
+
+    fn ok(headers: &str, body: &str) -> Option<String> {
+        Some(format!(
+            "HTTP/1.1 200 OK\r\n{}Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+            headers,
+            body.len(),
+            body
+        ))
+    }
+
+    fn not_modified() -> Option<String> {
+        Some("HTTP/1.1 304 Not Modified\r\nConnection: close\r\n\r\n".to_string())
+    }
+
+    fn sync(team: &TeamGlossary) -> Result<bool, String> {
+        tauri::async_runtime::block_on(team.sync())
+    }
+
+    #[test]
+    fn etag_match_is_not_modified_and_sync_time_is_saved() {
+        let dir = temp_dir("etag");
+        let cache_path = dir.join("cache.json");
+        let conditional = Arc::new(AtomicUsize::new(0));
+        let seen = conditional.clone();
+        let url = serve(move |request| {
+            if request.contains("if-none-match: \"v1\"") {
+                seen.fetch_add(1, Ordering::SeqCst);
+                not_modified()
+            } else {
+                ok("ETag: \"v1\"\r\n", CSV_V1)
+            }
+        });
+
+        let team = TeamGlossary::new(Some(TeamSource::parse(&url)), Some(cache_path.clone()));
+        assert_eq!(sync(&team), Ok(true));
+        assert_eq!(team.status().terms, 1);
+
+        std::thread::sleep(Duration::from_millis(5));
+        assert_eq!(sync(&team), Ok(false));
+        assert_eq!(conditional.load(Ordering::SeqCst), 1);
+        assert_eq!(team.status().terms, 1);
+
+        // The check time survives a restart
+        let synced_at = team.status().synced_at;
+        let restarted = TeamGlossary::new(Some(TeamSource::parse(&url)), Some(cache_path));
+        assert_eq!(restarted.status().synced_at, synced_at);
+        assert_eq!(restarted.status().terms, 1);
+    }
+
+    #[test]
+    fn changed_last_modified_reloads() {
+        let dir = temp_dir("last-modified");
+        let version = Arc::new(AtomicUsize::new(1));
+        let current = version.clone();
+        let url = serve(move |request| {
+            let (last_modified, body) = match current.load(Ordering::SeqCst) {
+                1 => ("Wed, 01 Jan 2025 00:00:00 GMT", CSV_V1),
+                _ => ("Thu, 02 Jan 2025 00:00:00 GMT", CSV_V2),
+            };
+            if request.contains(&format!("if-modified-since: {}", last_modified.to_ascii_lowercase())) {
+                not_modified()
+            } else {
+                ok(&format!("Last-Modified: {}\r\n", last_modified), body)
+            }
+        });
+
+        let team = TeamGlossary::new(Some(TeamSource::parse(&url)), Some(dir.join("cache.json")));
+        assert_eq!(sync(&team), Ok(true));
+        assert_eq!(sync(&team), Ok(false));
+        assert_eq!(team.status().terms, 1);
+
+        version.store(2, Ordering::SeqCst);
+        assert_eq!(sync(&team), Ok(true));
+        assert_eq!(team.status().terms, 2);
+    }
+
+    #[test]
+    fn file_is_reloaded_when_modified() {
+        let dir = temp_dir("file");
+        let path = dir.join("glossary.csv");
+        std::fs::write(&path, CSV_V1).unwrap();
+
+        let team = TeamGlossary::new(Some(TeamSource::File(path.clone())), Some(dir.join("cache.json")));
+        assert_eq!(sync(&team), Ok(true));
+        assert_eq!(sync(&team), Ok(false));
+
+        std::fs::write(&path, CSV_V2).unwrap();
+        let later = std::time::SystemTime::now() + Duration::from_secs(10);
+        std::fs::File::options().write(true).open(&path).unwrap().set_modified(later).unwrap();
+        assert_eq!(sync(&team), Ok(true));
+        assert_eq!(team.status().terms, 2);
+    }
+
+    #[test]
+    fn offline_keeps_cached_copy() {
+        let dir = temp_dir("offline");
+        let cache_path = dir.join("cache.json");
+        let online = Arc::new(AtomicBool::new(true));
+        let reachable = online.clone();
+        let url = serve(move |_| reachable.load(Ordering::SeqCst).then(|| ok("", CSV_V2)).flatten());
+
+        let team = TeamGlossary::new(Some(TeamSource::parse(&url)), Some(cache_path.clone()));
+        assert_eq!(sync(&team), Ok(true));
+
+        online.store(false, Ordering::SeqCst);
+        assert!(sync(&team).is_err());
+        let status = team.status();
+        assert_eq!(status.terms, 2);
+        assert!(status.error.is_some());
+
+        // After a restart the cache is used until the source is reachable again
+        let restarted = TeamGlossary::new(Some(TeamSource::parse(&url)), Some(cache_path));
+        assert!(sync(&restarted).is_err());
+        assert_eq!(restarted.glossary().terms.len(), 2);
+    }
+}
@@ -0,0 +1,214 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::sync::OnceLock;
+
+/// JSON Schema for the Worker's `/explain` response. It is sent with the
+/// request so the model can produce structured output, and every response is
+/// validated against it before use.
+const EXPLANATION_SCHEMA: &str = r#"{
+  "type": "object",
+  "required": ["phrases", "grammar", "literal_translation", "natural_translation", "model"],
+  "properties": {
+    "phrases": {
+      "type": "array",
+      "minItems": 1,
+      "items": {
+        "type": "object",
+        "required": ["text", "role", "gloss"],
+        "properties": {
+          "text": { "type": "string", "minLength": 1 },
+          "reading": { "type": ["string", "null"] },
+          "role": { "type": "string" },
+          "gloss": { "type": "string" }
+        }
+      }
+    },
+    "grammar": {
+      "type": "array",
+      "items": {
+        "type": "object",
+        "required": ["text", "kind", "function"],
+        "properties": {
+          "text": { "type": "string", "minLength": 1 },
+          "kind": { "enum": ["particle", "conjugation", "auxiliary", "expression"] },
+          "base_form": { "type": ["string", "null"] },
+          "function": { "type": "string", "minLength": 1 }
+        }
+      }
+    },
+    "literal_translation": { "type": "string", "minLength": 1 },
+    "natural_translation": { "type": "string", "minLength": 1 },
+    "notes": { "type": "array", "items": { "type": "string" } },
+    "model": { "type": "string" }
+  }
+}"#;
+
+/// A phrase (文節) of the sentence and what it does in it
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Phrase {
+    /// Exact excerpt of the sentence
+    pub text: String,
+    #[serde(default)]
+    pub reading: Option<String>,
+    /// Grammatical role, e.g. "topic", "object", "predicate"
+    pub role: String,
+    /// Word-for-word meaning in the explanation language
+    pub gloss: String,
+}
+
+/// Kind of grammatical element
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum GrammarKind {
+    /// は, が, を, に, ...
+    Particle,
+    /// A conjugated verb or adjective form, e.g. 食べられなかった
+    Conjugation,
+    /// Auxiliaries such as です, たい, らしい
+    Auxiliary,
+    /// Set expressions such as 〜なければならない
+    Expression,
+}
+
+/// A particle, conjugation or construction and its grammatical function
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct GrammarPoint {
+    /// Exact excerpt of the sentence
+    pub text: String,
+    pub kind: GrammarKind,
+    /// Dictionary form for conjugations, e.g. 食べる
+    #[serde(default)]
+    pub base_form: Option<String>,
+    /// What it does, e.g. "potential + negative + past: could not eat"
+    pub function: String,
+}
+
+/// Structured breakdown of a Japanese sentence from the Worker's `/explain` endpoint
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct GrammarExplanation {
+    /// The sentence, in order, split into phrases
+    pub phrases: Vec<Phrase>,
+    pub grammar: Vec<GrammarPoint>,
+    /// Word-for-word translation that keeps the Japanese structure
+    pub literal_translation: String,
+    pub natural_translation: String,
+    #[serde(default)]
+    pub notes: Vec<String>,
+    pub model: String,
+}
+
+/// Explanation shown in the overlay
+#[derive(Serialize, Clone, Debug)]
+pub struct ExplainResult {
+    pub original: String,
+    /// Language the explanation is written in
+    pub explain_lang: String,
+    pub explanation: GrammarExplanation,
+}
+
+/// The response schema, for sending with the request
+pub fn schema() -> &'static Value {
+    static SCHEMA: OnceLock<Value> = OnceLock::new();
+    SCHEMA.get_or_init(|| serde_json::from_str(EXPLANATION_SCHEMA).expect("valid explanation schema"))
+}
+
+/// Validate a Worker response against the schema and parse it
+pub fn parse_explanation(response: Value) -> Result<GrammarExplanation, String> {
+    static VALIDATOR: OnceLock<jsonschema::Validator> = OnceLock::new();
+    let validator = VALIDATOR.get_or_init(|| jsonschema::validator_for(schema()).expect("valid explanation schema"));
+
+    let errors: Vec<String> = validator
+        .iter_errors(&response)
+        .map(|error| format!("{} at '{}'", error, error.instance_path))
+        .collect();
+    if !errors.is_empty() {
+        return Err(format!("Grammar explanation has an unexpected format: {}", errors.join("; ")));
+    }
+
+    serde_json::from_value(response).map_err(|e| format!("Failed to parse grammar explanation: {}", e))
+}
+
+/// Phrases that are not excerpts of the sentence, in order; the overlay shows
+/// them anyway, but they may not line up with the original
+pub fn misaligned_phrases<'a>(text: &str, explanation: &'a GrammarExplanation) -> Vec<&'a str> {
+    let mut rest = text;
+    let mut misaligned = Vec::new();
+    for phrase in &explanation.phrases {
+        match rest.find(phrase.text.trim()) {
+            Some(i) => rest = &rest[i + phrase.text.trim().len()..],
+            None => misaligned.push(phrase.text.as_str()),
+        }
+    }
+    misaligned
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn response() -> Value {
+        json!({
+            "phrases": [
+                { "text": "私は", "reading": "わたしは", "role": "topic", "gloss": "as for me" },
+                { "text": "寿司を", "reading": "すしを", "role": "object", "gloss": "sushi" },
+                { "text": "食べられなかった", "reading": "たべられなかった", "role": "predicate", "gloss": "could not eat" }
+            ],
+            "grammar": [
+                { "text": "は", "kind": "particle", "function": "topic marker" },
+                { "text": "食べられなかった", "kind": "conjugation", "base_form": "食べる", "function": "potential + negative + past" }
+            ],
+            "literal_translation": "As for me, sushi could not eat",
+            "natural_translation": "I couldn't eat the sushi",
+            "model": "test"
+        })
+    }
+
+    #[test]
+    fn valid_responses_parse() {
+        let explanation = parse_explanation(response()).unwrap();
+        assert_eq!(explanation.phrases.len(), 3);
+        assert_eq!(explanation.grammar[1].kind, GrammarKind::Conjugation);
+        assert_eq!(explanation.grammar[1].base_form.as_deref(), Some("食べる"));
+        assert_eq!(explanation.grammar[0].base_form, None);
+        assert!(explanation.notes.is_empty());
+    }
+
+    #[test]
+    fn missing_fields_are_rejected() {
+        let mut response = response();
+        response.as_object_mut().unwrap().remove("natural_translation");
+        let error = parse_explanation(response).unwrap_err();
+        assert!(error.contains("natural_translation"), "{}", error);
+    }
+
+    #[test]
+    fn unknown_kinds_are_rejected() {
+        let mut response = response();
+        response["grammar"][0]["kind"] = json!("noun");
+        let error = parse_explanation(response).unwrap_err();
+        assert!(error.contains("/grammar/0/kind"), "{}", error);
+    }
+
+    #[test]
+    fn empty_phrase_lists_are_rejected() {
+        let mut response = response();
+        response["phrases"] = json!([]);
+        assert!(parse_explanation(response).is_err());
+    }
+
+    #[test]
+    fn phrases_are_aligned_in_order() {
+        let explanation = parse_explanation(response()).unwrap();
+        assert!(misaligned_phrases("私は寿司を食べられなかった。", &explanation).is_empty());
+
+        // A phrase found only before the previous one is out of order
+        let mut swapped = explanation.clone();
+        swapped.phrases.swap(0, 1);
+        assert_eq!(misaligned_phrases("私は寿司を食べられなかった。", &swapped), ["私は"]);
+
+        let mut invented = explanation;
+        invented.phrases[1].text = "刺身を".to_string();
+        assert_eq!(misaligned_phrases("私は寿司を食べられなかった。", &invented), ["刺身を"]);
+    }
+}
@@ -0,0 +1,621 @@
+use rusqlite::functions::FunctionFlags;
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::commands::TranslationResult;
+use crate::memory::{self, MemoryMatch};
+use crate::translation::Formality;
+
+/// Schema migrations, applied in order and tracked with `PRAGMA user_version`
+const MIGRATIONS: &[&str] = &[
+    // 1: history table with a trigram full-text index (works for CJK substrings)
+    "CREATE TABLE history (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        created_at INTEGER NOT NULL,
+        original TEXT NOT NULL,
+        translated TEXT NOT NULL,
+        source_lang TEXT NOT NULL,
+        target_lang TEXT NOT NULL,
+        provider TEXT NOT NULL,
+        source_app TEXT,
+        latency_ms INTEGER NOT NULL
+    );
+    CREATE INDEX history_created_at ON history(created_at);
+    CREATE VIRTUAL TABLE history_fts USING fts5(
+        original, translated,
+        content = 'history', content_rowid = 'id', tokenize = 'trigram'
+    );
+    CREATE TRIGGER history_ai AFTER INSERT ON history BEGIN
+        INSERT INTO history_fts(rowid, original, translated)
+        VALUES (new.id, new.original, new.translated);
+    END;
+    CREATE TRIGGER history_ad AFTER DELETE ON history BEGIN
+        INSERT INTO history_fts(history_fts, rowid, original, translated)
+        VALUES ('delete', old.id, old.original, old.translated);
+    END;",
+    // 2: translation memory, keyed by normalized source text and language pair
+    "CREATE TABLE memory (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        source_lang TEXT NOT NULL,
+        target_lang TEXT NOT NULL,
+        source TEXT NOT NULL,
+        target TEXT NOT NULL,
+        updated_at INTEGER NOT NULL,
+        UNIQUE (source_lang, target_lang, source)
+    );
+    CREATE VIRTUAL TABLE memory_fts USING fts5(
+        source, content = 'memory', content_rowid = 'id', tokenize = 'trigram'
+    );
+    CREATE TRIGGER memory_ai AFTER INSERT ON memory BEGIN
+        INSERT INTO memory_fts(rowid, source) VALUES (new.id, new.source);
+    END;
+    CREATE TRIGGER memory_ad AFTER DELETE ON memory BEGIN
+        INSERT INTO memory_fts(memory_fts, rowid, source) VALUES ('delete', old.id, old.source);
+    END;
+    CREATE TRIGGER memory_au AFTER UPDATE ON memory BEGIN
+        INSERT INTO memory_fts(memory_fts, rowid, source) VALUES ('delete', old.id, old.source);
+        INSERT INTO memory_fts(rowid, source) VALUES (new.id, new.source);
+    END;
+    INSERT OR IGNORE INTO memory (source_lang, target_lang, source, target, updated_at)
+        SELECT source_lang, target_lang, normalize_text(original), translated, created_at
+        FROM history ORDER BY created_at DESC;",
+    // 3: key translation memory by formality too (SQLite cannot alter a UNIQUE constraint)
+    "CREATE TABLE memory_v3 (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        source_lang TEXT NOT NULL,
+        target_lang TEXT NOT NULL,
+        formality TEXT NOT NULL DEFAULT '',
+        source TEXT NOT NULL,
+        target TEXT NOT NULL,
+        updated_at INTEGER NOT NULL,
+        UNIQUE (source_lang, target_lang, formality, source)
+    );
+    INSERT INTO memory_v3 (id, source_lang, target_lang, source, target, updated_at)
+        SELECT id, source_lang, target_lang, source, target, updated_at FROM memory;
+    DROP TABLE memory;
+    ALTER TABLE memory_v3 RENAME TO memory;
+    CREATE TRIGGER memory_ai AFTER INSERT ON memory BEGIN
+        INSERT INTO memory_fts(rowid, source) VALUES (new.id, new.source);
+    END;
+    CREATE TRIGGER memory_ad AFTER DELETE ON memory BEGIN
+        INSERT INTO memory_fts(memory_fts, rowid, source) VALUES ('delete', old.id, old.source);
+    END;
+    CREATE TRIGGER memory_au AFTER UPDATE ON memory BEGIN
+        INSERT INTO memory_fts(memory_fts, rowid, source) VALUES ('delete', old.id, old.source);
+        INSERT INTO memory_fts(rowid, source) VALUES (new.id, new.source);
+    END;",
+    // 4: the alternative translation the user picked instead of the main one
+    "ALTER TABLE history ADD COLUMN chosen_style TEXT;
+    ALTER TABLE history ADD COLUMN chosen_text TEXT;",
+];
+
+/// Candidates fetched from the trigram index before scoring fuzzy matches
+const MEMORY_CANDIDATES: i64 = 50;
+
+/// The trigram tokenizer cannot match queries shorter than this
+const MIN_FTS_QUERY_CHARS: usize = 3;
+
+const ENTRY_COLUMNS: &str =
+    "h.id, h.created_at, h.original, h.translated, h.source_lang, h.target_lang, h.provider, h.source_app, h.latency_ms,
+     h.chosen_style, h.chosen_text";
+
+/// A stored translation
+#[derive(Serialize, Clone, Debug)]
+pub struct HistoryEntry {
+    pub id: i64,
+    /// Unix timestamp in milliseconds
+    pub created_at: i64,
+    pub original: String,
+    pub translated: String,
+    pub source_lang: String,
+    pub target_lang: String,
+    pub provider: String,
+    pub source_app: Option<String>,
+    pub latency_ms: i64,
+    /// Style of the alternative translation picked in the overlay, if any
+    pub chosen_style: Option<String>,
+    pub chosen_text: Option<String>,
+}
+
+impl HistoryEntry {
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(Self {
+            id: row.get(0)?,
+            created_at: row.get(1)?,
+            original: row.get(2)?,
+            translated: row.get(3)?,
+            source_lang: row.get(4)?,
+            target_lang: row.get(5)?,
+            provider: row.get(6)?,
+            source_app: row.get(7)?,
+            latency_ms: row.get(8)?,
+            chosen_style: row.get(9)?,
+            chosen_text: row.get(10)?,
+        })
+    }
+}
+
+/// Which entries to include in an export; every field is optional
+#[derive(Deserialize, Default, Clone, Debug)]
+pub struct HistoryFilter {
+    /// Inclusive lower bound, Unix milliseconds
+    pub from: Option<i64>,
+    /// Exclusive upper bound, Unix milliseconds
+    pub to: Option<i64>,
+    /// Matches the tag or any more specific tag ("zh" matches "zh-Hant")
+    pub source_lang: Option<String>,
+    pub target_lang: Option<String>,
+}
+
+/// Local SQLite store of past translations
+pub struct HistoryStore {
+    conn: Mutex<Connection>,
+}
+
+impl HistoryStore {
+    /// Open (or create) the history database at `path` and migrate it
+    pub fn open(path: &Path) -> Result<Self, String> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create history directory: {}", e))?;
+        }
+
+        let conn = Connection::open(path).map_err(|e| format!("Failed to open history database: {}", e))?;
+        Self::with_connection(conn)
+    }
+
+    fn with_connection(conn: Connection) -> Result<Self, String> {
+        // Lets migrations key translation memory the same way `remember` does
+        conn.create_scalar_function(
+            "normalize_text",
+            1,
+            FunctionFlags::SQLITE_UTF8 | FunctionFlags::SQLITE_DETERMINISTIC,
+            |ctx| Ok(memory::normalize(&ctx.get::<String>(0)?)),
+        )
+        .map_err(|e| format!("Failed to register history functions: {}", e))?;
+
+        let version: usize = conn
+            .query_row("PRAGMA user_version", [], |row| row.get(0))
+            .map_err(|e| format!("Failed to read history schema version: {}", e))?;
+
+        for (i, migration) in MIGRATIONS.iter().enumerate().skip(version) {
+            conn.execute_batch(&format!(
+                "BEGIN; {} PRAGMA user_version = {}; COMMIT;",
+                migration,
+                i + 1
+            ))
+            .map_err(|e| format!("Failed to migrate history database to v{}: {}", i + 1, e))?;
+        }
+
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    fn conn(&self) -> Result<std::sync::MutexGuard<'_, Connection>, String> {
+        self.conn
+            .lock()
+            .map_err(|e| format!("Failed to lock history database: {}", e))
+    }
+
+    /// Store a translation, returning its id
+    pub fn record(&self, result: &TranslationResult) -> Result<i64, String> {
+        let conn = self.conn()?;
+        conn.execute(
+            "INSERT INTO history (created_at, original, translated, source_lang, target_lang, provider, source_app, latency_ms)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                now_millis(),
+                result.original,
+                result.translated,
+                result.source_lang,
+                result.target_lang,
+                result.provider,
+                result.source_app,
+                result.latency_ms as i64,
+            ],
+        )
+        .map_err(|e| format!("Failed to save translation to history: {}", e))?;
+
+        Ok(conn.last_insert_rowid())
+    }
+
+    /// Record which alternative translation was picked for an entry
+    pub fn choose_alternative(&self, id: i64, style: &str, text: &str) -> Result<(), String> {
+        let conn = self.conn()?;
+        let updated = conn
+            .execute(
+                "UPDATE history SET chosen_style = ?2, chosen_text = ?3 WHERE id = ?1",
+                params![id, style, text],
+            )
+            .map_err(|e| format!("Failed to save chosen alternative: {}", e))?;
+        if updated == 0 {
+            return Err(format!("History entry {} not found", id));
+        }
+        Ok(())
+    }
+
+    /// Most recent entries first
+    pub fn list(&self, limit: usize, offset: usize) -> Result<Vec<HistoryEntry>, String> {
+        let conn = self.conn()?;
+        let mut stmt = conn
+            .prepare(&format!(
+                "SELECT {} FROM history h ORDER BY h.created_at DESC, h.id DESC LIMIT ?1 OFFSET ?2",
+                ENTRY_COLUMNS
+            ))
+            .map_err(|e| format!("Failed to query history: {}", e))?;
+
+        let entries = stmt
+            .query_map(params![limit as i64, offset as i64], HistoryEntry::from_row)
+            .and_then(|rows| rows.collect::<rusqlite::Result<Vec<_>>>())
+            .map_err(|e| format!("Failed to read history: {}", e))?;
+        Ok(entries)
+    }
+
+    /// Full-text search across original and translated text, most recent first
+    pub fn search(&self, query: &str, limit: usize) -> Result<Vec<HistoryEntry>, String> {
+        let query = query.trim();
+        if query.is_empty() {
+            return self.list(limit, 0);
+        }
+
+        let conn = self.conn()?;
+        let (sql, pattern) = if query.chars().count() >= MIN_FTS_QUERY_CHARS {
+            (
+                format!(
+                    "SELECT {} FROM history_fts JOIN history h ON h.id = history_fts.rowid
+                     WHERE history_fts MATCH ?1 ORDER BY h.created_at DESC LIMIT ?2",
+                    ENTRY_COLUMNS
+                ),
+                // Quote as a single phrase so user input is never parsed as FTS syntax
+                format!("\"{}\"", query.replace('"', "\"\"")),
+            )
+        } else {
+            (
+                format!(
+                    "SELECT {} FROM history h
+                     WHERE h.original LIKE ?1 ESCAPE '\\' OR h.translated LIKE ?1 ESCAPE '\\'
+                     ORDER BY h.created_at DESC LIMIT ?2",
+                    ENTRY_COLUMNS
+                ),
+                format!(
+                    "%{}%",
+                    query.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_")
+                ),
+            )
+        };
+
+        let mut stmt = conn
+            .prepare(&sql)
+            .map_err(|e| format!("Failed to search history: {}", e))?;
+        let entries = stmt
+            .query_map(params![pattern, limit as i64], HistoryEntry::from_row)
+            .and_then(|rows| rows.collect::<rusqlite::Result<Vec<_>>>())
+            .map_err(|e| format!("Failed to read history: {}", e))?;
+        Ok(entries)
+    }
+
+    /// All entries matching the filter, oldest first
+    pub fn filtered(&self, filter: &HistoryFilter) -> Result<Vec<HistoryEntry>, String> {
+        let conn = self.conn()?;
+        let mut stmt = conn
+            .prepare(&format!(
+                "SELECT {} FROM history h
+                 WHERE (?1 IS NULL OR h.created_at >= ?1)
+                   AND (?2 IS NULL OR h.created_at < ?2)
+                   AND (?3 IS NULL OR h.source_lang = ?3 COLLATE NOCASE OR h.source_lang LIKE ?3 || '-%')
+                   AND (?4 IS NULL OR h.target_lang = ?4 COLLATE NOCASE OR h.target_lang LIKE ?4 || '-%')
+                 ORDER BY h.created_at ASC, h.id ASC",
+                ENTRY_COLUMNS
+            ))
+            .map_err(|e| format!("Failed to query history: {}", e))?;
+
+        let entries = stmt
+            .query_map(
+                params![filter.from, filter.to, filter.source_lang, filter.target_lang],
+                HistoryEntry::from_row,
+            )
+            .and_then(|rows| rows.collect::<rusqlite::Result<Vec<_>>>())
+            .map_err(|e| format!("Failed to read history: {}", e))?;
+        Ok(entries)
+    }
+
+    /// Add a translation to the translation memory, replacing any earlier
+    /// translation of the same text into the same language
+    pub fn remember(
+        &self,
+        source_lang: &str,
+        target_lang: &str,
+        formality: Option<Formality>,
+        source: &str,
+        target: &str,
+    ) -> Result<(), String> {
+        let conn = self.conn()?;
+        conn.execute(
+            "INSERT INTO memory (source_lang, target_lang, formality, source, target, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+             ON CONFLICT (source_lang, target_lang, formality, source)
+             DO UPDATE SET target = excluded.target, updated_at = excluded.updated_at",
+            params![
+                source_lang,
+                target_lang,
+                formality.map(Formality::as_str).unwrap_or_default(),
+                memory::normalize(source),
+                target,
+                now_millis()
+            ],
+        )
+        .map_err(|e| format!("Failed to save translation memory: {}", e))?;
+        Ok(())
+    }
+
+    /// Exact and fuzzy translation memory matches for `text`, best first.
+    /// Fuzzy matches below [`memory::FUZZY_THRESHOLD`] are left out.
+    pub fn recall(
+        &self,
+        text: &str,
+        source_lang: &str,
+        target_lang: &str,
+        formality: Option<Formality>,
+        limit: usize,
+    ) -> Result<Vec<MemoryMatch>, String> {
+        let key = memory::normalize(text);
+        let formality = formality.map(Formality::as_str).unwrap_or_default();
+        let conn = self.conn()?;
+
+        let exact: Option<String> = conn
+            .query_row(
+                "SELECT target FROM memory
+                 WHERE source_lang = ?1 AND target_lang = ?2 AND formality = ?3 AND source = ?4",
+                params![source_lang, target_lang, formality, key],
+                |row| row.get(0),
+            )
+            .ok();
+        if let Some(target) = exact {
+            return Ok(vec![MemoryMatch {
+                source: key,
+                target,
+                score: 1.0,
+            }]);
+        }
+
+        let Some(query) = memory::trigram_query(&key) else {
+            return Ok(Vec::new());
+        };
+        let mut stmt = conn
+            .prepare(
+                "SELECT m.source, m.target FROM memory_fts JOIN memory m ON m.id = memory_fts.rowid
+                 WHERE memory_fts MATCH ?1 AND m.source_lang = ?2 AND m.target_lang = ?3 AND m.formality = ?4
+                 ORDER BY memory_fts.rank LIMIT ?5",
+            )
+            .map_err(|e| format!("Failed to query translation memory: {}", e))?;
+        let candidates = stmt
+            .query_map(params![query, source_lang, target_lang, formality, MEMORY_CANDIDATES], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+            })
+            .and_then(|rows| rows.collect::<rusqlite::Result<Vec<_>>>())
+            .map_err(|e| format!("Failed to read translation memory: {}", e))?;
+
+        let mut matches: Vec<MemoryMatch> = candidates
+            .into_iter()
+            .map(|(source, target)| MemoryMatch {
+                score: memory::similarity(&key, &source),
+                source,
+                target,
+            })
+            .filter(|m| m.score >= memory::FUZZY_THRESHOLD)
+            .collect();
+        matches.sort_by(|a, b| b.score.total_cmp(&a.score));
+        matches.truncate(limit);
+        Ok(matches)
+    }
+
+    /// Delete one entry and its translation memory, returning whether it existed
+    pub fn delete(&self, id: i64) -> Result<bool, String> {
+        let mut conn = self.conn()?;
+        let tx = conn
+            .transaction()
+            .map_err(|e| format!("Failed to delete history entry: {}", e))?;
+
+        let entry: Option<(String, String, String)> = tx
+            .query_row(
+                "SELECT original, source_lang, target_lang FROM history WHERE id = ?1",
+                params![id],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .optional()
+            .map_err(|e| format!("Failed to read history entry: {}", e))?;
+        let Some((original, source_lang, target_lang)) = entry else {
+            return Ok(false);
+        };
+
+        // Otherwise exact-match reuse would keep serving the deleted text
+        tx.execute(
+            "DELETE FROM memory WHERE source_lang = ?1 AND target_lang = ?2 AND source = ?3",
+            params![source_lang, target_lang, memory::normalize(&original)],
+        )
+        .map_err(|e| format!("Failed to delete translation memory: {}", e))?;
+        tx.execute("DELETE FROM history WHERE id = ?1", params![id])
+            .map_err(|e| format!("Failed to delete history entry: {}", e))?;
+        tx.commit()
+            .map_err(|e| format!("Failed to delete history entry: {}", e))?;
+        Ok(true)
+    }
+
+    /// Delete every entry and the translation memory, returning how many entries were removed
+    pub fn clear(&self) -> Result<usize, String> {
+        let mut conn = self.conn()?;
+        let tx = conn
+            .transaction()
+            .map_err(|e| format!("Failed to clear history: {}", e))?;
+        let cleared = tx
+            .execute("DELETE FROM history", [])
+            .map_err(|e| format!("Failed to clear history: {}", e))?;
+        tx.execute("DELETE FROM memory", [])
+            .map_err(|e| format!("Failed to clear translation memory: {}", e))?;
+        tx.commit().map_err(|e| format!("Failed to clear history: {}", e))?;
+        Ok(cleared)
+    }
+}
+
+/// Current time as a Unix timestamp in milliseconds
+pub fn now_millis() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn store() -> HistoryStore {
+        HistoryStore::with_connection(Connection::open_in_memory().unwrap()).unwrap()
+    }
+
+    fn insert(conn: &Connection, original: &str, translated: &str) -> i64 {
+        insert_at(conn, now_millis(), original, translated, "en")
+    }
+
+    fn insert_at(conn: &Connection, created_at: i64, original: &str, translated: &str, target_lang: &str) -> i64 {
+        conn.execute(
+            "INSERT INTO history (created_at, original, translated, source_lang, target_lang, provider, latency_ms)
+             VALUES (?1, ?2, ?3, 'ja', ?4, 'test', 0)",
+            params![created_at, original, translated, target_lang],
+        )
+        .unwrap();
+        conn.last_insert_rowid()
+    }
+
+    fn ids(entries: Vec<HistoryEntry>) -> Vec<i64> {
+        entries.into_iter().map(|entry| entry.id).collect()
+    }
+
+    fn exact(store: &HistoryStore, text: &str) -> Option<String> {
+        store
+            .recall(text, "ja", "en", None, 5)
+            .unwrap()
+            .into_iter()
+            .find(|m| m.score >= 1.0)
+            .map(|m| m.target)
+    }
+
+    #[test]
+    fn backfilled_memory_uses_the_same_normalization() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(&format!("{} PRAGMA user_version = 1;", MIGRATIONS[0])).unwrap();
+        insert(&conn, "  お疲れ様です。\n  よろしく   お願いします ", "Thanks for your work. Best regards.");
+
+        let store = HistoryStore::with_connection(conn).unwrap();
+        assert_eq!(
+            exact(&store, "お疲れ様です。 よろしく お願いします").as_deref(),
+            Some("Thanks for your work. Best regards.")
+        );
+    }
+
+    #[test]
+    fn deleting_an_entry_forgets_its_translation() {
+        let store = store();
+        let id = insert(&store.conn().unwrap(), "会議は三時です", "The meeting is at three");
+        insert(&store.conn().unwrap(), "資料を送ります", "I will send the materials");
+        store.remember("ja", "en", None, "会議は三時です", "The meeting is at three").unwrap();
+        store.remember("ja", "en", None, "資料を送ります", "I will send the materials").unwrap();
+
+        assert!(store.delete(id).unwrap());
+        assert!(!store.delete(id).unwrap());
+        assert_eq!(exact(&store, "会議は三時です"), None);
+        assert!(exact(&store, "資料を送ります").is_some());
+    }
+
+    #[test]
+    fn clearing_history_clears_memory() {
+        let store = store();
+        insert(&store.conn().unwrap(), "会議は三時です", "The meeting is at three");
+        store.remember("ja", "en", None, "会議は三時です", "The meeting is at three").unwrap();
+
+        assert_eq!(store.clear().unwrap(), 1);
+        assert_eq!(exact(&store, "会議は三時です"), None);
+        assert!(store.recall("会議は三時でした", "ja", "en", None, 5).unwrap().is_empty());
+    }
+
+    #[test]
+    fn lists_newest_first_with_limit_and_offset() {
+        let store = store();
+        let conn = store.conn().unwrap();
+        let first = insert_at(&conn, 1_000, "おはよう", "Good morning", "en");
+        let second = insert_at(&conn, 2_000, "こんにちは", "Hello", "en");
+        let third = insert_at(&conn, 3_000, "こんばんは", "Good evening", "en");
+        drop(conn);
+
+        assert_eq!(ids(store.list(2, 0).unwrap()), vec![third, second]);
+        assert_eq!(ids(store.list(2, 2).unwrap()), vec![first]);
+        assert!(store.list(2, 3).unwrap().is_empty());
+    }
+
+    #[test]
+    fn searches_with_the_trigram_index() {
+        let store = store();
+        let conn = store.conn().unwrap();
+        let meeting = insert_at(&conn, 1_000, "会議は三時からです", "The meeting starts at three", "en");
+        let room = insert_at(&conn, 2_000, "三時に会議室へ来てください", "Please come to the meeting room at three", "en");
+        insert_at(&conn, 3_000, "資料を送ります", "I will send the materials", "en");
+        drop(conn);
+
+        assert_eq!(ids(store.search("三時から", 10).unwrap()), vec![meeting]);
+        assert_eq!(ids(store.search("meeting", 10).unwrap()), vec![room, meeting]);
+        assert_eq!(ids(store.search("meeting", 1).unwrap()), vec![room]);
+        assert_eq!(store.search("  ", 10).unwrap().len(), 3);
+    }
+
+    #[test]
+    fn short_queries_fall_back_to_like() {
+        let store = store();
+        let conn = store.conn().unwrap();
+        let meeting = insert_at(&conn, 1_000, "会議は三時からです", "The meeting starts at three", "en");
+        let room = insert_at(&conn, 2_000, "三時に会議室へ来てください", "Please come to the meeting room at three", "en");
+        let percent = insert_at(&conn, 3_000, "進捗は100%です", "Progress is at 100%", "en");
+        drop(conn);
+
+        assert_eq!(ids(store.search("会議", 10).unwrap()), vec![room, meeting]);
+        assert_eq!(ids(store.search("0%", 10).unwrap()), vec![percent]);
+        assert!(store.search("_", 10).unwrap().is_empty());
+    }
+
+    #[test]
+    fn quotes_in_queries_are_not_fts_syntax() {
+        let store = store();
+        let quoted = insert(&store.conn().unwrap(), "彼は\"OK\"と言った", "He said \"OK\"");
+        insert(&store.conn().unwrap(), "OKです", "That's OK");
+
+        assert_eq!(ids(store.search("\"OK\"", 10).unwrap()), vec![quoted]);
+        assert_eq!(ids(store.search("\"", 10).unwrap()), vec![quoted]);
+        assert!(store.search("OK\" OR \"", 10).unwrap().is_empty());
+    }
+
+    #[test]
+    fn filters_by_time_and_language() {
+        let store = store();
+        let conn = store.conn().unwrap();
+        let english = insert_at(&conn, 1_000, "おはよう", "Good morning", "en");
+        let traditional = insert_at(&conn, 2_000, "こんにちは", "你好", "zh-Hant");
+        let simplified = insert_at(&conn, 3_000, "こんばんは", "晚上好", "zh");
+        drop(conn);
+
+        assert_eq!(ids(store.filtered(&HistoryFilter::default()).unwrap()), vec![english, traditional, simplified]);
+        let window = HistoryFilter {
+            from: Some(1_000),
+            to: Some(3_000),
+            ..Default::default()
+        };
+        assert_eq!(ids(store.filtered(&window).unwrap()), vec![english, traditional]);
+        let chinese = HistoryFilter {
+            target_lang: Some("zh".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(ids(store.filtered(&chinese).unwrap()), vec![traditional, simplified]);
+    }
+}
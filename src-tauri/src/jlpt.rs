@@ -0,0 +1,349 @@
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::OnceLock;
+
+use crate::dictionary::DictionaryStore;
+use crate::furigana::{self, ContentWord};
+
+/// Embedded word list: level, word, reading (blank for words written in kana)
+const WORD_LIST: &str = include_str!("../data/jlpt.tsv");
+
+/// Word list from `JLPT_WORD_LIST`, added to the embedded one
+static EXTRA_WORD_LIST: OnceLock<String> = OnceLock::new();
+
+/// Share of a passage's words a reader should know for it to be at their level
+const COVERAGE: f64 = 0.9;
+
+/// JLPT level, from easiest (N5) to hardest (N1)
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum JlptLevel {
+    N5,
+    N4,
+    N3,
+    N2,
+    N1,
+}
+
+impl JlptLevel {
+    const ALL: [JlptLevel; 5] = [JlptLevel::N5, JlptLevel::N4, JlptLevel::N3, JlptLevel::N2, JlptLevel::N1];
+
+    fn parse(level: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|candidate| format!("{:?}", candidate) == level)
+    }
+
+    /// KANJIDIC2 uses the pre-2010 levels 4 (easiest) to 1; old level 2
+    /// covered what is now N3 and N2, so it maps to the easier of the two
+    fn from_kanjidic(level: Option<u8>) -> Self {
+        match level {
+            Some(4) => JlptLevel::N5,
+            Some(3) => JlptLevel::N4,
+            Some(2) => JlptLevel::N3,
+            _ => JlptLevel::N1,
+        }
+    }
+
+    /// 0 for N5 up to 100 for N1
+    fn difficulty(self) -> f64 {
+        self as usize as f64 * 25.0
+    }
+}
+
+/// A piece of the text; content words carry their dictionary form and level
+#[derive(Serialize, Clone, Debug)]
+pub struct LevelSpan {
+    pub text: String,
+    /// Dictionary form, for content words only
+    pub base_form: Option<String>,
+    /// Estimated level; `None` for plain text and for words that could not be placed
+    pub level: Option<JlptLevel>,
+}
+
+/// Estimated difficulty of a Japanese passage for a learner
+#[derive(Serialize, Clone, Debug)]
+pub struct Difficulty {
+    /// Lowest level whose words cover 90% of the passage; `None` if it has no content words
+    pub level: Option<JlptLevel>,
+    /// Average word difficulty, from 0 (all N5) to 100 (all N1 or beyond)
+    pub score: u8,
+    /// The text split into plain pieces and levelled words, in order
+    pub spans: Vec<LevelSpan>,
+    /// Content words at each level, N5 first
+    pub counts: [usize; 5],
+    /// Content words neither in the word list nor estimable from their kanji
+    pub unlisted: usize,
+}
+
+struct WordList {
+    by_word: HashMap<&'static str, JlptLevel>,
+    by_reading: HashMap<&'static str, JlptLevel>,
+}
+
+/// Level, word and reading of each line of a word list
+fn list_entries(list: &str) -> impl Iterator<Item = (JlptLevel, &str, Option<&str>)> {
+    list.lines().filter(|line| !line.starts_with('#')).filter_map(|line| {
+        let mut fields = line.split('\t').map(str::trim);
+        let level = fields.next().and_then(JlptLevel::parse)?;
+        let word = fields.next().filter(|word| !word.is_empty())?;
+        Some((level, word, fields.next().filter(|reading| !reading.is_empty())))
+    })
+}
+
+fn word_list() -> &'static WordList {
+    static WORDS: OnceLock<WordList> = OnceLock::new();
+    WORDS.get_or_init(|| {
+        let mut list = WordList {
+            by_word: HashMap::new(),
+            by_reading: HashMap::new(),
+        };
+        let extra = EXTRA_WORD_LIST.get().map(String::as_str).unwrap_or_default();
+        for (level, word, reading) in list_entries(WORD_LIST).chain(list_entries(extra)) {
+            // A word listed at several levels counts at the easiest
+            list.by_word.entry(word).and_modify(|known| *known = (*known).min(level)).or_insert(level);
+            if let Some(reading) = reading {
+                list.by_reading.entry(reading).and_modify(|known| *known = (*known).min(level)).or_insert(level);
+            }
+        }
+        list
+    })
+}
+
+/// Add a word list in the embedded list's format (e.g. converted from Jonathan
+/// Waller's CC BY lists), returning its word count. Must run before the first
+/// assessment.
+pub fn load_word_list(path: &Path) -> Result<usize, String> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| format!("Cannot read JLPT word list {}: {}", path.display(), e))?;
+    let count = list_entries(&contents).count();
+    if count == 0 {
+        return Err(format!("{} has no JLPT words (expected level, word and reading columns)", path.display()));
+    }
+    EXTRA_WORD_LIST
+        .set(contents)
+        .map_err(|_| "A JLPT word list is already loaded".to_string())?;
+    Ok(count)
+}
+
+/// Level of a content word: from the word list, by its dictionary form or,
+/// for words written in kana (わかる), by reading; otherwise from its hardest
+/// kanji, if KANJIDIC2 has been imported
+fn level_of(word: &ContentWord, dictionary: Option<&DictionaryStore>) -> Option<JlptLevel> {
+    let list = word_list();
+    if let Some(level) = list.by_word.get(word.base_form.as_str()) {
+        return Some(*level);
+    }
+    if !word.base_form.chars().any(furigana::is_kanji) {
+        return list.by_reading.get(furigana::to_hiragana(&word.base_form).as_str()).copied();
+    }
+
+    let dictionary = dictionary?;
+    let mut hardest = JlptLevel::N5;
+    for c in word.base_form.chars().filter(|c| furigana::is_kanji(*c) && *c != '々') {
+        let info = dictionary.kanji(c).inspect_err(|e| eprintln!("⚠ {}", e)).ok()??;
+        hardest = hardest.max(JlptLevel::from_kanjidic(info.jlpt));
+    }
+    Some(hardest)
+}
+
+fn is_katakana_word(word: &str) -> bool {
+    word.chars().all(|c| matches!(c as u32, 0x30A1..=0x30FA | 0x30FC))
+}
+
+/// Level each content word of a Japanese passage and estimate how hard the
+/// passage is to read
+pub fn assess(text: &str, dictionary: Option<&DictionaryStore>) -> Result<Difficulty, String> {
+    let words = furigana::content_words(text)?;
+    Ok(assess_words(text, words, dictionary))
+}
+
+/// Level the given content words of the text and estimate its difficulty
+fn assess_words(text: &str, words: Vec<ContentWord>, dictionary: Option<&DictionaryStore>) -> Difficulty {
+    let mut spans = Vec::new();
+    let mut counts = [0; 5];
+    let mut unlisted = 0;
+    let mut total_difficulty = 0.0;
+    let mut scored = 0;
+    let mut position = 0;
+    for word in words {
+        if position < word.offset {
+            spans.push(LevelSpan {
+                text: text[position..word.offset].to_string(),
+                base_form: None,
+                level: None,
+            });
+        }
+        position = word.offset + word.surface.len();
+
+        let level = level_of(&word, dictionary);
+        match level {
+            Some(level) => {
+                counts[level as usize] += 1;
+                total_difficulty += level.difficulty();
+                scored += 1;
+            }
+            // Unlisted loanwords are usually easy for English speakers; other
+            // unlisted words are likely rarer than anything on the list
+            None if is_katakana_word(&word.base_form) => unlisted += 1,
+            None => {
+                unlisted += 1;
+                total_difficulty += JlptLevel::N1.difficulty();
+                scored += 1;
+            }
+        }
+        spans.push(LevelSpan {
+            text: word.surface,
+            base_form: Some(word.base_form),
+            level,
+        });
+    }
+    if position < text.len() {
+        spans.push(LevelSpan {
+            text: text[position..].to_string(),
+            base_form: None,
+            level: None,
+        });
+    }
+
+    // Unlisted words count against coverage, so a passage full of them is N1
+    let level = (scored > 0).then(|| {
+        let mut covered = 0;
+        JlptLevel::ALL
+            .into_iter()
+            .find(|level| {
+                covered += counts[*level as usize];
+                covered as f64 >= COVERAGE * scored as f64
+            })
+            .unwrap_or(JlptLevel::N1)
+    });
+    let score = if scored > 0 {
+        (total_difficulty / scored as f64).round() as u8
+    } else {
+        0
+    };
+
+    Difficulty {
+        level,
+        score,
+        spans,
+        counts,
+        unlisted,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Content words in the order given, each found after the previous one
+    fn words(text: &str, words: &[(&str, &str)]) -> Vec<ContentWord> {
+        let mut position = 0;
+        words
+            .iter()
+            .map(|(surface, base_form)| {
+                let offset = position + text[position..].find(surface).expect("word in text");
+                position = offset + surface.len();
+                ContentWord {
+                    surface: surface.to_string(),
+                    base_form: base_form.to_string(),
+                    reading: None,
+                    pos: "名詞".to_string(),
+                    offset,
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn every_level_is_listed() {
+        let mut counts = [0; 5];
+        for (level, _, _) in list_entries(WORD_LIST) {
+            counts[level as usize] += 1;
+        }
+        assert!(counts.iter().all(|count| *count > 300), "{:?}", counts);
+    }
+
+    #[test]
+    fn list_lines_are_parsed() {
+        let list = "# comment\nN3\t与える\tあたえる\nN5\tある\t\nN6\t何か\tなにか\nN2\t\n";
+        let entries: Vec<_> = list_entries(list).collect();
+        assert_eq!(
+            entries,
+            [(JlptLevel::N3, "与える", Some("あたえる")), (JlptLevel::N5, "ある", None)]
+        );
+    }
+
+    #[test]
+    fn words_are_levelled_by_form_or_reading() {
+        let text = "鉛筆をわかる";
+        let found: Vec<_> = words(text, &[("鉛筆", "鉛筆"), ("わかる", "わかる")])
+            .iter()
+            .map(|word| level_of(word, None))
+            .collect();
+        assert_eq!(found, [Some(JlptLevel::N5), Some(JlptLevel::N5)]);
+    }
+
+    #[test]
+    fn level_and_score_follow_the_words() {
+        let text = "鉛筆を与えた。";
+        let difficulty = assess_words(text, words(text, &[("鉛筆", "鉛筆"), ("与え", "与える")]), None);
+        assert_eq!(difficulty.level, Some(JlptLevel::N3));
+        assert_eq!(difficulty.score, 25);
+        assert_eq!(difficulty.counts, [1, 0, 1, 0, 0]);
+        assert_eq!(difficulty.unlisted, 0);
+
+        let spans: Vec<_> = difficulty.spans.iter().map(|span| (span.text.as_str(), span.level)).collect();
+        assert_eq!(
+            spans,
+            [
+                ("鉛筆", Some(JlptLevel::N5)),
+                ("を", None),
+                ("与え", Some(JlptLevel::N3)),
+                ("た。", None)
+            ]
+        );
+    }
+
+    #[test]
+    fn one_hard_word_in_ten_keeps_the_easy_level() {
+        let text = "猫犬山川水本学校行く来る潤う";
+        let list = [
+            ("猫", "猫"),
+            ("犬", "犬"),
+            ("山", "山"),
+            ("川", "川"),
+            ("水", "水"),
+            ("本", "本"),
+            ("学校", "学校"),
+            ("行く", "行く"),
+            ("来る", "来る"),
+            ("潤う", "潤う"),
+        ];
+        let difficulty = assess_words(text, words(text, &list), None);
+        assert_eq!(difficulty.level, Some(JlptLevel::N5));
+        assert_eq!(difficulty.score, 10);
+    }
+
+    #[test]
+    fn unlisted_words() {
+        // Loanwords are not scored; other unlisted words count as N1
+        let text = "コンピューターで猫";
+        let difficulty = assess_words(text, words(text, &[("コンピューター", "コンピューター"), ("猫", "猫")]), None);
+        assert_eq!(difficulty.unlisted, 1);
+        assert_eq!(difficulty.level, Some(JlptLevel::N5));
+        assert_eq!(difficulty.score, 0);
+
+        let text = "猫と鼈甲";
+        let difficulty = assess_words(text, words(text, &[("猫", "猫"), ("鼈甲", "鼈甲")]), None);
+        assert_eq!(difficulty.unlisted, 1);
+        assert_eq!(difficulty.level, Some(JlptLevel::N1));
+        assert_eq!(difficulty.score, 50);
+    }
+
+    #[test]
+    fn text_without_content_words() {
+        let difficulty = assess_words("ね。", Vec::new(), None);
+        assert_eq!(difficulty.level, None);
+        assert_eq!(difficulty.score, 0);
+        assert_eq!(difficulty.spans.len(), 1);
+    }
+}
@@ -0,0 +1,245 @@
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use serde::{Deserialize, Serialize};
+use std::io::BufRead;
+
+/// KANJIDIC2 from the Electronic Dictionary Research and Development Group
+pub const DEFAULT_KANJIDIC_URL: &str = "https://www.edrdg.org/kanjidic/kanjidic2.xml.gz";
+
+/// One kanji from KANJIDIC2
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct KanjiInfo {
+    pub literal: String,
+    /// Sino-Japanese readings, in katakana
+    pub on_readings: Vec<String>,
+    /// Native readings, in hiragana; okurigana follow a `.` (e.g. "た.べる")
+    pub kun_readings: Vec<String>,
+    /// Readings used only in names
+    pub nanori: Vec<String>,
+    /// English meanings
+    pub meanings: Vec<String>,
+    pub stroke_count: Option<u32>,
+    /// Old (pre-2010) JLPT level, 4 (easiest) to 1
+    pub jlpt: Option<u8>,
+    /// School grade: 1-6 kyōiku, 8 other jōyō, 9-10 jinmeiyō
+    pub grade: Option<u8>,
+    /// Rank among the 2,500 most frequent kanji in newspapers
+    pub frequency: Option<u32>,
+    pub radicals: Vec<Radical>,
+}
+
+/// Radical a kanji is indexed under
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Radical {
+    /// "classical" (Kangxi) or "nelson_c" (Nelson's classification, where it differs)
+    pub kind: String,
+    /// Kangxi radical number, 1 to 214
+    pub number: u32,
+    /// The radical itself, e.g. "⼈" for 9
+    pub character: Option<String>,
+}
+
+impl Radical {
+    fn new(kind: &str, number: u32) -> Self {
+        // The Kangxi Radicals block has the 214 radicals in order
+        let character = (1..=214)
+            .contains(&number)
+            .then(|| char::from_u32(0x2F00 + number - 1))
+            .flatten()
+            .map(String::from);
+        Self {
+            kind: kind.to_string(),
+            number,
+            character,
+        }
+    }
+}
+
+/// Stream characters out of KANJIDIC2 XML
+pub fn parse_kanjidic<R: BufRead>(
+    input: R,
+    mut on_kanji: impl FnMut(KanjiInfo) -> Result<(), String>,
+) -> Result<(), String> {
+    let mut reader = Reader::from_reader(input);
+    reader.config_mut().trim_text(true);
+
+    let mut buf = Vec::new();
+    let mut kanji: Option<KanjiInfo> = None;
+    let mut element: Vec<u8> = Vec::new();
+    // Attribute that qualifies the current element's text (r_type, m_lang or rad_type)
+    let mut qualifier: Option<String> = None;
+
+    loop {
+        let event = reader.read_event_into(&mut buf).map_err(|e| {
+            format!("Invalid KANJIDIC2 at position {}: {}", reader.error_position(), e)
+        })?;
+        match event {
+            Event::Start(e) => {
+                element = e.local_name().as_ref().to_vec();
+                let attribute = match element.as_slice() {
+                    b"character" => {
+                        kanji = Some(KanjiInfo::default());
+                        None
+                    }
+                    b"reading" => Some("r_type"),
+                    b"meaning" => Some("m_lang"),
+                    b"rad_value" => Some("rad_type"),
+                    _ => None,
+                };
+                qualifier = attribute.and_then(|name| {
+                    e.try_get_attribute(name)
+                        .ok()
+                        .flatten()
+                        .map(|value| String::from_utf8_lossy(&value.value).to_string())
+                });
+            }
+            Event::Text(text) => {
+                let Some(kanji) = kanji.as_mut() else { continue };
+                let text = text
+                    .unescape()
+                    .map_err(|e| format!("Invalid KANJIDIC2 text: {}", e))?
+                    .to_string();
+                match element.as_slice() {
+                    b"literal" => kanji.literal = text,
+                    b"reading" => match qualifier.as_deref() {
+                        Some("ja_on") => kanji.on_readings.push(text),
+                        Some("ja_kun") => kanji.kun_readings.push(text),
+                        _ => {}
+                    },
+                    // Meanings in other languages carry m_lang; English ones do not
+                    b"meaning" if qualifier.is_none() => kanji.meanings.push(text),
+                    b"nanori" => kanji.nanori.push(text),
+                    // Later stroke counts are common miscounts
+                    b"stroke_count" if kanji.stroke_count.is_none() => kanji.stroke_count = text.parse().ok(),
+                    b"jlpt" => kanji.jlpt = text.parse().ok(),
+                    b"grade" => kanji.grade = text.parse().ok(),
+                    b"freq" => kanji.frequency = text.parse().ok(),
+                    b"rad_value" => {
+                        if let (Some(kind), Ok(number)) = (qualifier.as_deref(), text.parse()) {
+                            kanji.radicals.push(Radical::new(kind, number));
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            Event::End(e) => {
+                if e.local_name().as_ref() == b"character" {
+                    if let Some(finished) = kanji.take().filter(|kanji| !kanji.literal.is_empty()) {
+                        on_kanji(finished)?;
+                    }
+                }
+                element.clear();
+                qualifier = None;
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+        buf.clear();
+    }
+
+    Ok(())
+}
+
+/// Distinct kanji in the text, in order of appearance (々 repeats the previous
+/// kanji and has no entry of its own)
+pub fn kanji_in(text: &str) -> Vec<char> {
+    let mut found: Vec<char> = Vec::new();
+    for c in text.chars() {
+        let is_kanji = matches!(c as u32, 0x3400..=0x4DBF | 0x4E00..=0x9FFF | 0xF900..=0xFAFF | 0x20000..=0x2FA1F);
+        if is_kanji && !found.contains(&c) {
+            found.push(c);
+        }
+    }
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KANJIDIC: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<kanjidic2>
+<header><file_version>4</file_version></header>
+<character>
+<literal>食</literal>
+<radical>
+<rad_value rad_type="classical">184</rad_value>
+<rad_value rad_type="nelson_c">9</rad_value>
+</radical>
+<misc>
+<grade>2</grade>
+<stroke_count>9</stroke_count>
+<stroke_count>10</stroke_count>
+<freq>328</freq>
+<jlpt>4</jlpt>
+</misc>
+<reading_meaning>
+<rmgroup>
+<reading r_type="pinyin">shi2</reading>
+<reading r_type="ja_on">ショク</reading>
+<reading r_type="ja_on">ジキ</reading>
+<reading r_type="ja_kun">く.う</reading>
+<reading r_type="ja_kun">た.べる</reading>
+<meaning>eat</meaning>
+<meaning>food</meaning>
+<meaning m_lang="fr">manger</meaning>
+<meaning m_lang="es">comer</meaning>
+</rmgroup>
+<nanori>あき</nanori>
+</reading_meaning>
+</character>
+<character>
+<literal>々</literal>
+<misc><stroke_count>3</stroke_count></misc>
+</character>
+</kanjidic2>"#;
+
+    fn parse(xml: &str) -> Vec<KanjiInfo> {
+        let mut found = Vec::new();
+        parse_kanjidic(xml.as_bytes(), |kanji| {
+            found.push(kanji);
+            Ok(())
+        })
+        .unwrap();
+        found
+    }
+
+    #[test]
+    fn characters_are_parsed() {
+        let found = parse(KANJIDIC);
+        assert_eq!(found.len(), 2);
+
+        let eat = &found[0];
+        assert_eq!(eat.literal, "食");
+        assert_eq!(eat.on_readings, ["ショク", "ジキ"]);
+        assert_eq!(eat.kun_readings, ["く.う", "た.べる"]);
+        assert_eq!(eat.nanori, ["あき"]);
+        assert_eq!(eat.stroke_count, Some(9));
+        assert_eq!(eat.grade, Some(2));
+        assert_eq!(eat.frequency, Some(328));
+        assert_eq!(eat.jlpt, Some(4));
+
+        assert_eq!(found[1].literal, "々");
+        assert!(found[1].meanings.is_empty());
+    }
+
+    #[test]
+    fn meanings_in_other_languages_are_skipped() {
+        assert_eq!(parse(KANJIDIC)[0].meanings, ["eat", "food"]);
+    }
+
+    #[test]
+    fn radicals_map_to_kangxi_characters() {
+        let radicals = &parse(KANJIDIC)[0].radicals;
+        assert_eq!(radicals.len(), 2);
+        assert_eq!((radicals[0].kind.as_str(), radicals[0].number), ("classical", 184));
+        assert_eq!(radicals[0].character.as_deref(), Some("⾷"));
+        assert_eq!(radicals[1].character.as_deref(), Some("⼈"));
+        assert_eq!(Radical::new("classical", 215).character, None);
+    }
+
+    #[test]
+    fn kanji_are_listed_once_in_order() {
+        assert_eq!(kanji_in("日々の食事と食後"), ['日', '食', '事', '後']);
+    }
+}
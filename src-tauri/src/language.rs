@@ -0,0 +1,393 @@
+/// Which languages the user reads natively and which they translate to and from
+#[derive(Clone, Debug)]
+pub struct LanguageConfig {
+    /// Language that foreign text is translated into, e.g. "en"
+    pub home_lang: String,
+    /// Languages the user writes in besides the home language, in order of
+    /// preference. Text in the home language is translated into the first one.
+    pub working_langs: Vec<String>,
+}
+
+impl LanguageConfig {
+    /// Load language preferences from `HOME_LANG` and `WORKING_LANGS`
+    /// (comma separated BCP-47 tags), defaulting to the English/Japanese pair
+    pub fn from_env() -> Self {
+        let home_lang = std::env::var("HOME_LANG")
+            .ok()
+            .and_then(|tag| normalize_tag(&tag))
+            .unwrap_or_else(|| "en".to_string());
+
+        let mut working_langs: Vec<String> = Vec::new();
+        let configured = std::env::var("WORKING_LANGS").unwrap_or_else(|_| "ja".to_string());
+        for tag in configured.split(',').filter(|tag| !tag.trim().is_empty()) {
+            match normalize_tag(tag) {
+                Some(tag) if same_language(&tag, &home_lang) => {}
+                Some(tag) if working_langs.contains(&tag) => {}
+                Some(tag) => working_langs.push(tag),
+                None => eprintln!("[WARN] Ignoring invalid language tag in WORKING_LANGS: '{}'", tag.trim()),
+            }
+        }
+
+        if working_langs.is_empty() {
+            let fallback = if same_language(&home_lang, "ja") { "en" } else { "ja" };
+            working_langs.push(fallback.to_string());
+        }
+
+        Self {
+            home_lang,
+            working_langs,
+        }
+    }
+
+    /// Home language followed by the working languages, used to break ties in detection
+    pub fn preferred(&self) -> Vec<String> {
+        std::iter::once(self.home_lang.clone())
+            .chain(self.working_langs.iter().cloned())
+            .collect()
+    }
+
+    /// Pick the translation target for text detected as `source`.
+    /// Foreign text goes to the home language; home-language text goes to the
+    /// preferred working language.
+    pub fn target_for(&self, source: &str) -> String {
+        if same_language(source, &self.home_lang) {
+            self.working_langs[0].clone()
+        } else {
+            self.home_lang.clone()
+        }
+    }
+}
+
+/// Normalize a BCP-47 language tag to canonical casing ("zh_hant_tw" -> "zh-Hant-TW").
+/// Returns `None` if the tag is not well formed.
+pub fn normalize_tag(tag: &str) -> Option<String> {
+    let tag = tag.trim().replace('_', "-");
+    let mut subtags = tag.split('-');
+
+    let language = subtags.next()?;
+    if !(2..=8).contains(&language.len()) || !language.chars().all(|c| c.is_ascii_alphabetic()) {
+        return None;
+    }
+
+    let mut normalized = language.to_ascii_lowercase();
+    for (i, subtag) in subtags.enumerate() {
+        if subtag.is_empty() || subtag.len() > 8 || !subtag.chars().all(|c| c.is_ascii_alphanumeric()) {
+            return None;
+        }
+
+        normalized.push('-');
+        if i == 0 && subtag.len() == 4 && subtag.chars().all(|c| c.is_ascii_alphabetic()) {
+            // Script subtag: title case
+            normalized.push_str(&subtag[..1].to_ascii_uppercase());
+            normalized.push_str(&subtag[1..].to_ascii_lowercase());
+        } else if subtag.len() == 2 && subtag.chars().all(|c| c.is_ascii_alphabetic()) {
+            // Region subtag: upper case
+            normalized.push_str(&subtag.to_ascii_uppercase());
+        } else {
+            normalized.push_str(&subtag.to_ascii_lowercase());
+        }
+    }
+
+    Some(normalized)
+}
+
+/// Primary language subtag of a tag ("zh-Hant-TW" -> "zh")
+pub fn primary_subtag(tag: &str) -> &str {
+    tag.split(['-', '_']).next().unwrap_or(tag)
+}
+
+/// Whether two tags refer to the same language, ignoring script and region
+pub fn same_language(a: &str, b: &str) -> bool {
+    primary_subtag(a).eq_ignore_ascii_case(primary_subtag(b))
+}
+
+/// Detections below this confidence are flagged as uncertain in the overlay
+pub const LOW_CONFIDENCE_THRESHOLD: f32 = 0.6;
+
+/// Result of language identification
+#[derive(Clone, Debug, PartialEq)]
+pub struct Detection {
+    /// BCP-47 code of the detected language
+    pub lang: String,
+    /// How sure the detector is, from 0.0 to 1.0
+    pub confidence: f32,
+}
+
+impl Detection {
+    fn new(lang: &str, confidence: f32) -> Self {
+        Self {
+            lang: lang.to_string(),
+            confidence: confidence.clamp(0.0, 1.0),
+        }
+    }
+
+    pub fn is_low_confidence(&self) -> bool {
+        self.confidence < LOW_CONFIDENCE_THRESHOLD
+    }
+}
+
+/// Characters used in Simplified or Traditional Chinese but not in modern Japanese
+const CHINESE_MARKERS: &str = "们这说个对么还没给让过问题为发现关经吗呢吧啊呀哪谁该请觉应实进车东书长门马鸟见页头岁爱边记认识话语读钱铁银错处气变总从虽們這說對麼沒讓發關經嗎覺應實將歲邊讀錢鐵來與會學國處氣變點體總當從雖裡";
+/// Characters specific to Traditional Chinese, used to pick `zh-Hant`
+const TRADITIONAL_MARKERS: &str = "們這說對麼沒讓發關經嗎覺應實將歲邊讀錢鐵來與會學國處氣變點體總當從雖裡";
+/// Shinjitai and kokuji that do not appear in Chinese text
+const JAPANESE_MARKERS: &str = "込働畑峠枠籾匂栃辻榊凪駅気広対売読楽変済図単県浜営桜黒歩鉄戦様拝払仏伝価児処帰従恵悪拠挙昼晩暁涙渋猟粋経絵継続緑縁聴脳臓薬覧豊賛辺郷鋭険隠雑霊験騒髪円発関覚応実歳銭譲労沢権歯塩焼駆検証団転軽厳圧囲壊専庁廃弾徳拡択斉栄毎満窓糸蔵観訳遅鉱録隣頼顔駄鶏黙齢";
+
+/// Identify the language of `text`.
+///
+/// CJK text is classified by script analysis: kana means Japanese, Hangul means
+/// Korean, and kanji-only text is split between Chinese and Japanese using
+/// characters that only occur in one of them. Everything else goes through
+/// whatlang's trigram model. `preferred` (home and working languages) breaks
+/// ties for ambiguous text.
+pub fn detect_language(text: &str, preferred: &[String]) -> Detection {
+    let mut kana = 0usize;
+    let mut han = 0usize;
+    let mut hangul = 0usize;
+    let mut other_letters = 0usize;
+    let mut zh_markers = 0usize;
+    let mut traditional_markers = 0usize;
+    let mut ja_markers = 0usize;
+
+    for c in text.chars() {
+        match c as u32 {
+            0x3040..=0x309F | // Hiragana
+            0x30A0..=0x30FF | // Katakana
+            0x31F0..=0x31FF | // Katakana Phonetic Extensions
+            0xFF66..=0xFF9F   // Halfwidth Katakana
+                => kana += 1,
+            0x4E00..=0x9FFF | // CJK Unified Ideographs
+            0x3400..=0x4DBF | // CJK Extension A
+            0xF900..=0xFAFF   // CJK Compatibility Ideographs
+                => {
+                han += 1;
+                if CHINESE_MARKERS.contains(c) {
+                    zh_markers += 1;
+                }
+                if TRADITIONAL_MARKERS.contains(c) {
+                    traditional_markers += 1;
+                }
+                if JAPANESE_MARKERS.contains(c) {
+                    ja_markers += 1;
+                }
+            }
+            0xAC00..=0xD7AF | // Hangul Syllables
+            0x1100..=0x11FF | // Hangul Jamo
+            0x3130..=0x318F   // Hangul Compatibility Jamo
+                => hangul += 1,
+            _ if c.is_alphabetic() => other_letters += 1,
+            _ => {}
+        }
+    }
+
+    let cjk = kana + han + hangul;
+    if cjk == 0 && other_letters == 0 {
+        return Detection::new(preferred.first().map(String::as_str).unwrap_or("en"), 0.0);
+    }
+
+    // One CJK character carries roughly as much text as four alphabetic letters
+    let cjk_share = cjk as f32 / (cjk as f32 + other_letters as f32 / 4.0);
+    if cjk_share < 0.5 {
+        return detect_alphabetic(text, preferred, 1.0 - cjk_share);
+    }
+
+    // Mixed-script text is less certain than pure CJK
+    let purity = 0.5 + cjk_share / 2.0;
+
+    if hangul > 0 && hangul >= kana + han / 2 {
+        let share = hangul as f32 / cjk as f32;
+        return Detection::new("ko", (0.7 + share * 0.3) * purity);
+    }
+
+    if kana > 0 {
+        // Japanese prose is typically 30-70% kana; even a little kana is a strong signal
+        let share = kana as f32 / (kana + han) as f32;
+        return Detection::new("ja", (0.8 + share.min(0.2)) * purity);
+    }
+
+    if han == 0 {
+        return Detection::new("ko", 0.5 * purity);
+    }
+
+    // Kanji-only text: weigh the script-specific characters
+    let zh_lang = if traditional_markers * 2 > zh_markers { "zh-Hant" } else { "zh" };
+    let marker_total = (zh_markers + ja_markers) as f32;
+    if zh_markers != ja_markers {
+        let margin = (zh_markers as f32 - ja_markers as f32).abs() / marker_total;
+        let coverage = (marker_total / han as f32).min(1.0);
+        let confidence = (0.5 + margin * 0.3 + coverage * 0.2) * purity;
+        let lang = if zh_markers > ja_markers { zh_lang } else { "ja" };
+        return Detection::new(lang, confidence);
+    }
+
+    // No evidence either way: fall back to whichever of the two the user works with
+    let lang = preferred
+        .iter()
+        .map(String::as_str)
+        .find(|tag| same_language(tag, "ja") || same_language(tag, "zh"))
+        .unwrap_or("ja");
+    Detection::new(lang, 0.4 * purity)
+}
+
+/// Detect languages written in alphabetic scripts with whatlang's trigram model
+fn detect_alphabetic(text: &str, preferred: &[String], purity: f32) -> Detection {
+    // Stray CJK characters would skew script detection
+    let text: String = text.chars().filter(|c| !is_cjk(*c)).collect();
+
+    let info = match whatlang::detect(&text) {
+        Some(info) => info,
+        None => return Detection::new(preferred.first().map(String::as_str).unwrap_or("en"), 0.0),
+    };
+
+    let detected = Detection::new(iso639_1(info.lang()), info.confidence() as f32 * purity);
+    if info.is_reliable() {
+        return detected;
+    }
+
+    // Short snippets are often misclassified; retry among the user's own languages
+    let allowlist: Vec<whatlang::Lang> = preferred.iter().filter_map(|tag| whatlang_lang(tag)).collect();
+    if allowlist.is_empty() {
+        return detected;
+    }
+
+    let preferred_info = whatlang::Detector::with_allowlist(allowlist)
+        .detect(&text)
+        .filter(|candidate| candidate.script() == info.script());
+
+    match preferred_info {
+        Some(candidate) => Detection::new(
+            iso639_1(candidate.lang()),
+            detected.confidence.max(candidate.confidence() as f32 * purity).min(LOW_CONFIDENCE_THRESHOLD),
+        ),
+        None => detected,
+    }
+}
+
+/// Whether a character is kana, Han or Hangul
+pub fn is_cjk(c: char) -> bool {
+    matches!(
+        c as u32,
+        0x3040..=0x30FF | // Hiragana and Katakana
+        0x31F0..=0x31FF | // Katakana Phonetic Extensions
+        0xFF66..=0xFF9F | // Halfwidth Katakana
+        0x3400..=0x4DBF | // CJK Extension A
+        0x4E00..=0x9FFF | // CJK Unified Ideographs
+        0xF900..=0xFAFF | // CJK Compatibility Ideographs
+        0xAC00..=0xD7AF | // Hangul Syllables
+        0x1100..=0x11FF | // Hangul Jamo
+        0x3130..=0x318F   // Hangul Compatibility Jamo
+    )
+}
+
+/// Two-letter code for a whatlang language, or its ISO 639-3 code if it has none
+fn iso639_1(lang: whatlang::Lang) -> &'static str {
+    ISO_639_CODES
+        .iter()
+        .find(|(three, _)| *three == lang.code())
+        .map(|(_, two)| *two)
+        .unwrap_or(lang.code())
+}
+
+/// whatlang language for a BCP-47 tag
+fn whatlang_lang(tag: &str) -> Option<whatlang::Lang> {
+    let primary = primary_subtag(tag).to_ascii_lowercase();
+    let code = ISO_639_CODES
+        .iter()
+        .find(|(_, two)| *two == primary)
+        .map(|(three, _)| *three)
+        .unwrap_or(primary.as_str());
+    whatlang::Lang::from_code(code)
+}
+
+/// ISO 639-3 codes used by whatlang mapped to ISO 639-1
+const ISO_639_CODES: &[(&str, &str)] = &[
+    ("afr", "af"), ("aka", "ak"), ("amh", "am"), ("ara", "ar"), ("aze", "az"),
+    ("bel", "be"), ("ben", "bn"), ("bul", "bg"), ("cat", "ca"), ("ces", "cs"),
+    ("cmn", "zh"), ("dan", "da"), ("deu", "de"), ("ell", "el"), ("eng", "en"),
+    ("epo", "eo"), ("est", "et"), ("fin", "fi"), ("fra", "fr"), ("guj", "gu"),
+    ("heb", "he"), ("hin", "hi"), ("hrv", "hr"), ("hun", "hu"), ("hye", "hy"),
+    ("ind", "id"), ("ita", "it"), ("jav", "jv"), ("jpn", "ja"), ("kan", "kn"),
+    ("kat", "ka"), ("khm", "km"), ("kor", "ko"), ("lat", "la"), ("lav", "lv"),
+    ("lit", "lt"), ("mal", "ml"), ("mar", "mr"), ("mkd", "mk"), ("mya", "my"),
+    ("nep", "ne"), ("nld", "nl"), ("nob", "nb"), ("ori", "or"), ("pan", "pa"),
+    ("pes", "fa"), ("pol", "pl"), ("por", "pt"), ("ron", "ro"), ("rus", "ru"),
+    ("sin", "si"), ("slk", "sk"), ("slv", "sl"), ("sna", "sn"), ("spa", "es"),
+    ("srp", "sr"), ("swe", "sv"), ("tam", "ta"), ("tel", "te"), ("tgl", "tl"),
+    ("tha", "th"), ("tuk", "tk"), ("tur", "tr"), ("ukr", "uk"), ("urd", "ur"),
+    ("uzb", "uz"), ("vie", "vi"), ("yid", "yi"), ("zul", "zu"),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn detect(text: &str) -> Detection {
+        detect_language(text, &["en".to_string()])
+    }
+
+    #[test]
+    fn kana_only_text_is_japanese() {
+        let detection = detect("ありがとうございます");
+        assert_eq!(detection.lang, "ja");
+        assert!(!detection.is_low_confidence());
+        assert_eq!(detect("コーヒー").lang, "ja");
+    }
+
+    #[test]
+    fn kanji_with_kana_is_japanese() {
+        assert_eq!(detect("明日の会議は三時からです。").lang, "ja");
+    }
+
+    #[test]
+    fn kanji_only_text_uses_script_specific_characters() {
+        assert_eq!(detect("駅前広場").lang, "ja");
+        assert_eq!(detect("我们明天见吧").lang, "zh");
+        assert_eq!(detect("我們這裡說話").lang, "zh-Hant");
+    }
+
+    #[test]
+    fn traditional_chinese_kanji_is_not_japanese() {
+        assert_eq!(detect("這個郵件地址").lang, "zh-Hant");
+        let detection = detect("郵件類別");
+        assert!(detection.is_low_confidence());
+        assert_eq!(detect_language("郵件類別", &["zh-Hant".to_string()]).lang, "zh-Hant");
+        assert_eq!(detect_language("浅色预览", &["zh".to_string()]).lang, "zh");
+    }
+
+    #[test]
+    fn ambiguous_kanji_prefers_working_language() {
+        let detection = detect_language("山川", &["en".to_string(), "zh".to_string()]);
+        assert_eq!(detection.lang, "zh");
+        assert!(detection.is_low_confidence());
+        assert_eq!(detect("山川").lang, "ja");
+    }
+
+    #[test]
+    fn hangul_is_korean() {
+        assert_eq!(detect("안녕하세요, 반갑습니다").lang, "ko");
+        // Korean with a few Hanja is still Korean
+        assert_eq!(detect("大韓民國 헌법 제1조는 다음과 같습니다").lang, "ko");
+    }
+
+    #[test]
+    fn alphabetic_text_uses_trigram_model() {
+        assert_eq!(detect("The meeting has been moved to Thursday afternoon.").lang, "en");
+        let french = detect_language("La réunion a été déplacée à jeudi après-midi, merci de prévenir l'équipe.", &[]);
+        assert_eq!(french.lang, "fr");
+    }
+
+    #[test]
+    fn text_without_letters_has_no_confidence() {
+        let detection = detect("12:30 - 14:00");
+        assert_eq!(detection.lang, "en");
+        assert_eq!(detection.confidence, 0.0);
+    }
+
+    #[test]
+    fn normalizes_tags() {
+        assert_eq!(normalize_tag("JA").as_deref(), Some("ja"));
+        assert_eq!(normalize_tag("zh_hant").as_deref(), Some("zh-Hant"));
+        assert_eq!(normalize_tag("pt-br").as_deref(), Some("pt-BR"));
+        assert!(same_language("en-US", "en"));
+    }
+}
@@ -1,17 +1,46 @@
+mod actions;
+mod anki;
+mod chunks;
 mod clipboard_manager;
 mod commands;
 mod config;
+mod dictionary;
+mod document;
+mod export;
+mod furigana;
+mod glossary;
+mod glossary_sync;
+mod grammar;
+mod history;
+mod jlpt;
+mod kanjidic;
+mod language;
+mod memory;
+mod mrkdwn;
+mod placeholders;
+mod quantities;
+mod review;
+mod segments;
+mod srs;
 mod translation;
 mod tray;
+mod verify;
+mod vocabulary;
 
 use clipboard_manager::SmartClipboard;
 use commands::AppState;
-use config::TranslationConfig;
-use std::sync::{Arc, Mutex};
+use config::{DictionaryConfig, FormalityConfig, HotkeyConfig, TeamGlossaryConfig, TranslationConfig};
+use dictionary::DictionaryStore;
+use glossary::Glossary;
+use glossary_sync::{TeamGlossary, TeamSource};
+use history::HistoryStore;
+use language::LanguageConfig;
+use vocabulary::VocabularyStore;
+use std::sync::{Arc, Mutex, RwLock};
 use tauri::Manager;
 
 #[cfg(not(any(target_os = "android", target_os = "ios")))]
-use tauri_plugin_global_shortcut::{Code, Modifiers, Shortcut, GlobalShortcutExt};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutState};
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
@@ -31,43 +60,143 @@ pub fn run() {
             println!("[INFO] Translation Config:");
             println!("  Worker URL: {}", translation_config.worker_url);
 
+            // Load hotkey bindings (after TranslationConfig has read .env)
+            let hotkeys = HotkeyConfig::from_env();
+            let languages = LanguageConfig::from_env();
+            println!("  Home language: {}", languages.home_lang);
+            println!("  Working languages: {}", languages.working_langs.join(", "));
+            let formality = FormalityConfig::from_env();
+
+            let data_dir = app
+                .path()
+                .app_data_dir()
+                .map_err(|e| format!("Failed to resolve app data directory: {}", e));
+
+            // Open translation history (the app keeps working without it)
+            let history = data_dir
+                .clone()
+                .and_then(|dir| HistoryStore::open(&dir.join("history.sqlite3")));
+            let history = match history {
+                Ok(store) => Some(store),
+                Err(e) => {
+                    eprintln!("⚠ {}", e);
+                    eprintln!("  Translations will not be saved to history.");
+                    None
+                }
+            };
+
+            // Open the personal word list (the app keeps working without it)
+            let vocabulary = data_dir
+                .clone()
+                .and_then(|dir| VocabularyStore::open(&dir.join("vocabulary.sqlite3")));
+            let vocabulary = match vocabulary {
+                Ok(store) => Some(store),
+                Err(e) => {
+                    eprintln!("⚠ {}", e);
+                    eprintln!("  Words cannot be saved to the word list.");
+                    None
+                }
+            };
+
+            // Load the saved glossary
+            let glossary_path = data_dir.as_ref().ok().map(|dir| dir.join("glossary.json"));
+            let glossary = match glossary_path.as_deref().map(Glossary::load) {
+                Some(Ok(glossary)) => glossary,
+                Some(Err(e)) => {
+                    eprintln!("⚠ {}", e);
+                    Glossary::default()
+                }
+                None => Glossary::default(),
+            };
+            println!("  Glossary terms: {}", glossary.terms.len());
+
+            // Shared team glossary, starting from the offline cache
+            let team_config = TeamGlossaryConfig::from_env();
+            let team_glossary = TeamGlossary::new(
+                team_config.source.as_deref().map(TeamSource::parse),
+                data_dir.as_ref().ok().map(|dir| dir.join("team-glossary.json")),
+            );
+            if let Some(source) = &team_config.source {
+                println!("  Team glossary: {} (refresh every {} min)", source, team_config.refresh_interval.as_secs() / 60);
+            }
+
+            // Offline dictionary for short Japanese terms (the app translates them without it)
+            let dictionary_config = DictionaryConfig::from_env();
+            let dictionary = data_dir.and_then(|dir| {
+                DictionaryStore::open(
+                    &dir.join("dictionary.sqlite3"),
+                    dictionary_config.source.as_deref().map(TeamSource::parse),
+                    dictionary_config.kanjidic_source.as_deref().map(TeamSource::parse),
+                )
+            });
+            let dictionary = match dictionary {
+                Ok(store) => Some(store),
+                Err(e) => {
+                    eprintln!("⚠ {}", e);
+                    eprintln!("  Short Japanese terms will be translated instead of looked up.");
+                    None
+                }
+            };
+            if let Some(path) = &dictionary_config.jlpt_word_list {
+                match jlpt::load_word_list(std::path::Path::new(path)) {
+                    Ok(count) => println!("  JLPT word list: {} ({} words)", path, count),
+                    Err(e) => eprintln!("⚠ {}", e),
+                }
+            }
+
             // Set up app state
             app.manage(AppState {
                 clipboard: Arc::new(Mutex::new(clipboard)),
                 translation_config,
+                hotkeys,
+                languages,
+                formality,
+                last_result: Mutex::new(None),
+                history,
+                glossary: RwLock::new(glossary),
+                glossary_path,
+                team_glossary,
+                dictionary,
+                vocabulary,
             });
 
-            // Register global hotkey (Cmd+J on Mac, Ctrl+J on Windows)
+            if app.state::<AppState>().team_glossary.is_configured() {
+                glossary_sync::spawn_refresh(app.handle(), team_config.refresh_interval);
+            }
+
+            // First run with a configured source: build the dictionary in the background
+            let needs_dictionary = dictionary_config.auto_import
+                && app.state::<AppState>().dictionary.as_ref().is_some_and(DictionaryStore::needs_import);
+            if needs_dictionary {
+                dictionary::spawn_import(app.handle());
+            }
+
+            // Register one global hotkey per configured action
             #[cfg(not(any(target_os = "android", target_os = "ios")))]
             {
-                let shortcut = if cfg!(target_os = "macos") {
-                    Shortcut::new(Some(Modifiers::META), Code::KeyJ)
-                } else {
-                    Shortcut::new(Some(Modifiers::CONTROL), Code::KeyJ)
-                };
-
-                // Try to register hotkey, but don't fail if it doesn't work
-                // First set up the handler, then register the shortcut
-                let handler_result = app.handle().global_shortcut().on_shortcut(shortcut, move |app_handle, _shortcut, _event| {
-                    let app_clone = app_handle.clone();
-                    tauri::async_runtime::spawn(async move {
-                        // Access state through app handle directly
-                        let state_guard = app_clone.state::<AppState>();
-                        let result = commands::translate_selection(app_clone.clone(), state_guard).await;
-                        if let Err(e) = result {
-                            eprintln!("Translation error: {}", e);
+                let bindings = app.state::<AppState>().hotkeys.bindings.clone();
+                for (action, shortcut_str) in bindings {
+                    let shortcut: Shortcut = match shortcut_str.parse() {
+                        Ok(shortcut) => shortcut,
+                        Err(e) => {
+                            eprintln!("⚠ Invalid hotkey '{}' for {:?}: {}", shortcut_str, action, e);
+                            continue;
+                        }
+                    };
+
+                    // Registering with a handler both installs the handler and the shortcut
+                    let action_name = format!("{:?}", action);
+                    let result = app.handle().global_shortcut().on_shortcut(shortcut, move |app_handle, _shortcut, event| {
+                        // Handlers fire on both press and release
+                        if event.state() == ShortcutState::Pressed {
+                            actions::dispatch(app_handle, action.clone());
                         }
                     });
-                });
-
-                if let Err(e) = handler_result {
-                    eprintln!("⚠ Failed to set up hotkey handler: {}", e);
-                } else {
-                    // Now try to register the shortcut
-                    match app.handle().global_shortcut().register(shortcut) {
-                        Ok(_) => println!("✓ Global hotkey Cmd+J registered successfully"),
+
+                    match result {
+                        Ok(_) => println!("✓ Global hotkey {} registered for {}", shortcut_str, action_name),
                         Err(e) => {
-                            eprintln!("⚠ Failed to register hotkey: {}.", e);
+                            eprintln!("⚠ Failed to register hotkey {}: {}.", shortcut_str, e);
                             eprintln!("  You'll need to grant Accessibility permissions in System Settings:");
                             eprintln!("  System Settings → Privacy & Security → Accessibility");
                             eprintln!("  Then add your terminal app to the list and restart.");
@@ -88,7 +217,35 @@ pub fn run() {
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
-            commands::translate_selection
+            commands::translate_selection,
+            commands::translate_text,
+            commands::review_text,
+            commands::get_language_settings,
+            commands::get_last_result,
+            commands::list_history,
+            commands::search_history,
+            commands::delete_history_entry,
+            commands::clear_history,
+            commands::export_history,
+            commands::import_glossary,
+            commands::list_glossary,
+            commands::clear_glossary,
+            commands::get_team_glossary_status,
+            commands::sync_team_glossary,
+            commands::verify_translation,
+            commands::get_readings,
+            commands::get_dictionary_status,
+            commands::update_dictionary,
+            commands::get_kanji_breakdown,
+            commands::explain_text,
+            commands::choose_alternative,
+            commands::extract_vocabulary,
+            commands::list_vocabulary,
+            commands::delete_vocabulary_word,
+            commands::export_vocabulary,
+            commands::get_due_cards,
+            commands::get_due_summary,
+            commands::grade_card
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
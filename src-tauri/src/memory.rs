@@ -0,0 +1,76 @@
+use serde::Serialize;
+
+/// Fuzzy matches below this similarity are not shown
+pub const FUZZY_THRESHOLD: f32 = 0.75;
+
+/// Fuzzy matching is skipped for texts longer than this (edit distance is quadratic)
+const MAX_FUZZY_CHARS: usize = 2000;
+
+/// Most trigrams used to look up fuzzy match candidates
+const MAX_QUERY_TRIGRAMS: usize = 48;
+
+/// A previously translated text similar to the one being translated
+#[derive(Serialize, Clone, Debug)]
+pub struct MemoryMatch {
+    pub source: String,
+    pub target: String,
+    /// Similarity to the current text, from 0.0 to 1.0 (1.0 is an exact match)
+    pub score: f32,
+}
+
+/// Key texts are stored and compared under: trimmed, with whitespace runs collapsed
+pub fn normalize(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Character-level edit distance similarity: 1.0 minus the Levenshtein
+/// distance divided by the longer length. Works per character, so it suits
+/// Japanese and Chinese text without word segmentation.
+pub fn similarity(a: &str, b: &str) -> f32 {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let longest = a.len().max(b.len());
+    if longest == 0 {
+        return 1.0;
+    }
+    if a.len().min(b.len()) as f32 / (longest as f32) < FUZZY_THRESHOLD || longest > MAX_FUZZY_CHARS {
+        // Too different in length to reach the threshold, or too long to compare
+        return 0.0;
+    }
+
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+    for (i, ca) in a.iter().enumerate() {
+        current[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+
+    1.0 - previous[b.len()] as f32 / longest as f32
+}
+
+/// FTS5 query matching any character trigram of `text`, or `None` if the
+/// text is too short for the trigram index
+pub fn trigram_query(text: &str) -> Option<String> {
+    let chars: Vec<char> = text.chars().collect();
+    if chars.len() < 3 || chars.len() > MAX_FUZZY_CHARS {
+        return None;
+    }
+
+    let mut trigrams: Vec<String> = chars.windows(3).map(|w| w.iter().collect()).collect();
+    trigrams.sort();
+    trigrams.dedup();
+
+    // A sample of the trigrams keeps queries for long texts small
+    let step = trigrams.len().div_ceil(MAX_QUERY_TRIGRAMS);
+    let terms: Vec<String> = trigrams
+        .iter()
+        .step_by(step.max(1))
+        .map(|trigram| format!("\"{}\"", trigram.replace('"', "\"\"")))
+        .collect();
+
+    Some(terms.join(" OR "))
+}
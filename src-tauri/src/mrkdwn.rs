@@ -0,0 +1,140 @@
+use regex::Regex;
+use std::sync::OnceLock;
+
+use crate::placeholders::Placeholders;
+
+/// Code blocks, inline code, `<...>` references (mentions, channels, links)
+/// and `:emoji:` shortcodes, which are kept whole
+fn atom_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| {
+        Regex::new(concat!(
+            r"```[\s\S]*?```",
+            r"|`[^`\n]+`",
+            r"|<(?P<target>[^<>|\s]+)\|(?P<label>[^<>\n]+)>",
+            r"|<[@#!][^<>\s]+>|<(?:https?|mailto):[^<>\s]+>",
+            // A digit-only run like 10:30: is a time, not an emoji
+            r"|:[a-z0-9_+'-]*[a-z_+'-][a-z0-9_+'-]*:",
+        ))
+        .expect("valid mrkdwn pattern")
+    })
+}
+
+/// `*bold*`, `_italic_` and `~strike~`, whose markers are kept but whose text is translated
+fn emphasis_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| {
+        Regex::new(r"\*[^\s*][^*\n]*?\*|\*[^\s*]\*|_[^\s_][^_\n]*?_|_[^\s_]_|~[^\s~][^~\n]*?~|~[^\s~]~")
+            .expect("valid emphasis pattern")
+    })
+}
+
+/// Replace Slack mrkdwn that the model tends to mangle with placeholders:
+/// code and code blocks, `<@U123>` mentions, `<#C123|channel>` and `<!here>`
+/// references, `<url>` links and `:emoji:` shortcodes are kept whole; the
+/// labels of `<url|label>` links and text inside `*bold*`, `_italic_` and
+/// `~strike~` are still translated, between placeholders for the markup.
+pub fn shield(text: &str, placeholders: &mut Placeholders) -> String {
+    let mut shielded = String::with_capacity(text.len());
+    let mut last = 0;
+    for caps in atom_pattern().captures_iter(text) {
+        let whole = caps.get(0).expect("match");
+        shielded.push_str(&text[last..whole.start()]);
+        match (caps.name("target"), caps.name("label")) {
+            // Channel names and user groups are names, not prose
+            (Some(target), Some(_)) if target.as_str().starts_with(['#', '@', '!']) => {
+                shielded.push_str(&placeholders.insert(whole.as_str()));
+            }
+            (Some(target), Some(label)) => {
+                shielded.push_str(&placeholders.insert(format!("<{}|", target.as_str())));
+                shielded.push_str(label.as_str());
+                shielded.push_str(&placeholders.insert(">"));
+            }
+            _ => shielded.push_str(&placeholders.insert(whole.as_str())),
+        }
+        last = whole.end();
+    }
+    shielded.push_str(&text[last..]);
+
+    shield_emphasis(&shielded, placeholders)
+}
+
+fn shield_emphasis(text: &str, placeholders: &mut Placeholders) -> String {
+    // Markers inside words (snake_case, 2*3*4) are not formatting
+    let is_word = |c: Option<char>| c.is_some_and(|c| c.is_ascii_alphanumeric());
+
+    let mut shielded = String::with_capacity(text.len());
+    let mut last = 0;
+    for m in emphasis_pattern().find_iter(text) {
+        let marker = &m.as_str()[..1];
+        let inner = &m.as_str()[1..m.len() - 1];
+        if is_word(text[..m.start()].chars().next_back())
+            || is_word(text[m.end()..].chars().next())
+            || inner.ends_with(char::is_whitespace)
+        {
+            continue;
+        }
+        shielded.push_str(&text[last..m.start()]);
+        shielded.push_str(&placeholders.insert(marker));
+        shielded.push_str(inner);
+        shielded.push_str(&placeholders.insert(marker));
+        last = m.end();
+    }
+    shielded.push_str(&text[last..]);
+    shielded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Shield the text, check what the model would see, and restore it unchanged
+    fn shielded(text: &str) -> String {
+        let mut placeholders = Placeholders::new();
+        let shielded = shield(text, &mut placeholders);
+        assert_eq!(placeholders.restore(&shielded), (text.to_string(), Vec::new()), "{}", text);
+        shielded
+    }
+
+    #[test]
+    fn mentions_and_channels_are_kept_whole() {
+        assert_eq!(shielded("<@U123> can you check?"), "⟦0⟧ can you check?");
+        assert_eq!(shielded("Posted in <#C1|general> and <!here>"), "Posted in ⟦0⟧ and ⟦1⟧");
+        assert_eq!(shielded("<!subteam^S1|@design> please"), "⟦0⟧ please");
+    }
+
+    #[test]
+    fn link_labels_are_translated() {
+        assert_eq!(shielded("See <https://example.com/a?b=1|the docs>"), "See ⟦0⟧the docs⟦1⟧");
+        assert_eq!(shielded("See <https://example.com>"), "See ⟦0⟧");
+    }
+
+    #[test]
+    fn code_is_kept_whole() {
+        let text = "Run this:\n```\nlet x = *y*;\n```\nthen `cargo test`";
+        assert_eq!(shielded(text), "Run this:\n⟦0⟧\nthen ⟦1⟧");
+    }
+
+    #[test]
+    fn emoji_are_not_times() {
+        assert_eq!(shielded("Meet at 10:30: bring :coffee: and :+1:"), "Meet at 10:30: bring ⟦0⟧ and ⟦1⟧");
+    }
+
+    #[test]
+    fn emphasis_markers_are_shielded_outside_words() {
+        assert_eq!(shielded("set my_var_name to 2*3*4"), "set my_var_name to 2*3*4");
+        assert_eq!(shielded("This is *important*"), "This is ⟦0⟧important⟦1⟧");
+        assert_eq!(shielded("これは*重要*です"), "これは⟦0⟧重要⟦1⟧です");
+        assert_eq!(shielded("_急ぎ_ と ~中止~"), "⟦0⟧急ぎ⟦1⟧ と ⟦2⟧中止⟦3⟧");
+    }
+
+    #[test]
+    fn dropped_markup_is_reported() {
+        let mut placeholders = Placeholders::new();
+        let shielded = shield("<@U123> *urgent*", &mut placeholders);
+        assert_eq!(shielded, "⟦0⟧ ⟦1⟧urgent⟦2⟧");
+        let (restored, missing) = placeholders.restore("[[1]]至急[[2]]");
+        assert_eq!(restored, "*至急*");
+        assert_eq!(missing, ["<@U123>"]);
+    }
+}
@@ -0,0 +1,59 @@
+use regex::Regex;
+use std::sync::OnceLock;
+
+/// Swaps protected spans for opaque tokens (`⟦0⟧`, `⟦1⟧`, ...) before text is
+/// sent to the model, and puts the intended text back afterwards
+#[derive(Default, Debug)]
+pub struct Placeholders {
+    values: Vec<String>,
+}
+
+/// Tokens as emitted, plus the `[[0]]` form some models rewrite them into
+fn token_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| {
+        Regex::new(r"⟦\s*(\d+)\s*⟧|\[\[\s*(\d+)\s*\]\]").expect("valid placeholder pattern")
+    })
+}
+
+impl Placeholders {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register the text a token should be restored to, returning the token
+    pub fn insert(&mut self, restore_to: impl Into<String>) -> String {
+        self.values.push(restore_to.into());
+        format!("⟦{}⟧", self.values.len() - 1)
+    }
+
+    /// Replace tokens in `translated` with their values. Returns the restored
+    /// text and the values whose tokens the model dropped.
+    pub fn restore(&self, translated: &str) -> (String, Vec<String>) {
+        let mut seen = vec![false; self.values.len()];
+
+        let restored = token_pattern().replace_all(translated, |caps: &regex::Captures| {
+            let index = caps
+                .get(1)
+                .or_else(|| caps.get(2))
+                .and_then(|m| m.as_str().parse::<usize>().ok());
+            match index.and_then(|i| self.values.get(i).map(|value| (i, value))) {
+                Some((i, value)) => {
+                    seen[i] = true;
+                    value.clone()
+                }
+                None => caps[0].to_string(),
+            }
+        });
+
+        let missing = self
+            .values
+            .iter()
+            .zip(seen)
+            .filter(|(_, seen)| !seen)
+            .map(|(value, _)| value.clone())
+            .collect();
+
+        (restored.into_owned(), missing)
+    }
+}
@@ -0,0 +1,623 @@
+use regex::{Captures, Regex};
+use std::ops::Range;
+use std::sync::OnceLock;
+
+/// A number, date, time, amount of money or percentage found in text
+#[derive(Clone, Debug)]
+pub struct Quantity {
+    /// As written (after full-width characters are normalized)
+    pub text: String,
+    pub value: Value,
+    /// Written as a bare kanji numeral or number word, which is often part of an
+    /// ordinary word (一緒, 千葉, "one of"); used to match, but never required
+    pub loose: bool,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Number(f64),
+    /// ISO 4217 code and amount
+    Currency(&'static str, f64),
+    Percent(f64),
+    /// Month/day pairs the date could mean; "3/4" is either March 4 or April 3
+    Date { year: Option<f64>, month_days: Vec<(f64, f64)> },
+    /// Minutes since midnight; without 午前/午後 or am/pm, 3:00 may also be 15:00
+    Time { minutes: f64, meridiem: bool },
+}
+
+/// Numbers, dates, times, amounts and percentages in the original that the
+/// translation drops or changes, and ones the translation adds
+pub fn check(original: &str, translated: &str) -> Vec<String> {
+    let original = extract(original);
+    let translated = extract(translated);
+    let mut used = vec![false; translated.len()];
+    let mut missing = Vec::new();
+
+    // Match the same kind of quantity first, then fall back to the same value
+    // (a plain "15" may have become "the 15th" or "15日")
+    for quantity in original.iter().filter(|q| !q.loose) {
+        let found = find_unused(&translated, &used, |t| same(&quantity.value, &t.value))
+            .or_else(|| find_unused(&translated, &used, |t| same_number(&quantity.value, &t.value)));
+        match found {
+            Some(i) => used[i] = true,
+            None => missing.push(quantity),
+        }
+    }
+    for quantity in original.iter().filter(|q| q.loose) {
+        if let Some(i) = find_unused(&translated, &used, |t| same(&quantity.value, &t.value) || same_number(&quantity.value, &t.value)) {
+            used[i] = true;
+        }
+    }
+
+    let mut extra: Vec<&Quantity> = translated
+        .iter()
+        .zip(&used)
+        .filter(|(q, used)| !**used && !q.loose && !matches!(q.value, Value::Number(_)))
+        .map(|(q, _)| q)
+        .collect();
+
+    let mut warnings = Vec::new();
+    for quantity in missing {
+        // A single changed value of the same kind is reported as a replacement
+        let replacements: Vec<usize> = extra
+            .iter()
+            .enumerate()
+            .filter(|(_, t)| std::mem::discriminant(&t.value) == std::mem::discriminant(&quantity.value))
+            .map(|(i, _)| i)
+            .collect();
+        if let [i] = replacements[..] {
+            let replacement = extra.remove(i);
+            warnings.push(format!(
+                "{} in the original became {} in the translation",
+                quantity.text, replacement.text
+            ));
+        } else {
+            warnings.push(format!("{} is missing from the translation", quantity.text));
+        }
+    }
+    for quantity in extra {
+        warnings.push(format!("{} in the translation is not in the original", quantity.text));
+    }
+
+    warnings
+}
+
+fn find_unused(quantities: &[Quantity], used: &[bool], matches: impl Fn(&Quantity) -> bool) -> Option<usize> {
+    quantities
+        .iter()
+        .enumerate()
+        .find(|(i, q)| !used[*i] && matches(q))
+        .map(|(i, _)| i)
+}
+
+fn approx_eq(a: f64, b: f64) -> bool {
+    (a - b).abs() <= 1e-9 * a.abs().max(b.abs()).max(1.0)
+}
+
+/// Whether two quantities say the same thing
+fn same(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        (Value::Number(a), Value::Number(b)) | (Value::Percent(a), Value::Percent(b)) => approx_eq(*a, *b),
+        (Value::Currency(a_code, a), Value::Currency(b_code, b)) => a_code == b_code && approx_eq(*a, *b),
+        (
+            Value::Date { year: a_year, month_days: a_days },
+            Value::Date { year: b_year, month_days: b_days },
+        ) => {
+            let years_agree = match (a_year, b_year) {
+                (Some(a), Some(b)) => approx_eq(*a, *b),
+                _ => true,
+            };
+            years_agree
+                && a_days
+                    .iter()
+                    .any(|(m, d)| b_days.iter().any(|(bm, bd)| approx_eq(*m, *bm) && approx_eq(*d, *bd)))
+        }
+        (
+            Value::Time { minutes: a, meridiem: a_meridiem },
+            Value::Time { minutes: b, meridiem: b_meridiem },
+        ) => {
+            if *a_meridiem && *b_meridiem {
+                approx_eq(*a, *b)
+            } else {
+                approx_eq(a % 720.0, b % 720.0)
+            }
+        }
+        _ => false,
+    }
+}
+
+/// Whether a plain number (or an amount whose unit was written differently)
+/// appears as part of the other quantity
+fn same_number(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        (Value::Number(a), other) | (other, Value::Number(a)) => numbers_in(other).iter().any(|n| approx_eq(*a, *n)),
+        (Value::Currency(_, a), Value::Currency(_, b)) => approx_eq(*a, *b),
+        _ => false,
+    }
+}
+
+fn numbers_in(value: &Value) -> Vec<f64> {
+    match value {
+        Value::Number(n) | Value::Currency(_, n) | Value::Percent(n) => vec![*n],
+        Value::Date { year, month_days } => year
+            .iter()
+            .copied()
+            .chain(month_days.iter().flat_map(|(m, d)| [*m, *d]))
+            .collect(),
+        Value::Time { minutes, .. } => {
+            let hour = (minutes / 60.0).floor();
+            vec![hour, hour % 12.0, minutes % 60.0]
+        }
+    }
+}
+
+/// Japanese/Chinese amount: digits or kanji numerals, optionally with 万/億/兆
+const AMOUNT: &str = r"(?:(?:\d{1,3}(?:,\d{3})+|\d+)(?:\.\d+)?|[〇一二三四五六七八九十百千])(?:[\d.〇一二三四五六七八九十百千万億兆]|,\d{3})*";
+/// Small number in a date or time: digits or kanji numerals
+const SMALL: &str = r"[\d〇一二三四五六七八九十]+";
+/// Scale words written after an amount in English
+const SCALE: &str = r"\s+(?:thousand|million|billion|trillion)";
+/// English month names, capitalized so "may" the verb is not a month
+const MONTH: &str = r"(?:January|February|March|April|May|June|July|August|September|October|November|December|Jan|Feb|Mar|Apr|Jun|Jul|Aug|Sept|Sep|Oct|Nov|Dec)";
+
+/// A pattern and how to read its captures
+type Rule = (Regex, fn(&Captures) -> Option<Value>);
+
+struct Patterns {
+    dates: Vec<Rule>,
+    times: Vec<Rule>,
+    currency_prefix: Regex,
+    currency_suffix: Regex,
+    percent: Regex,
+    wari: Regex,
+    number: Regex,
+    number_word: Regex,
+}
+
+fn patterns() -> &'static Patterns {
+    static PATTERNS: OnceLock<Patterns> = OnceLock::new();
+    PATTERNS.get_or_init(|| {
+        let re = |pattern: String| Regex::new(&pattern).expect("valid quantity pattern");
+        Patterns {
+            dates: vec![
+                // 2024年3月15日, 3月15号, 3월 15일
+                (
+                    re(format!(r"(?:({SMALL})\s*[年년]\s*)?({SMALL})\s*[月월]\s*({SMALL})\s*[日号일]")),
+                    |c| date(c.get(1), c.get(2), c.get(3)),
+                ),
+                // 2024年3月
+                (re(format!(r"({SMALL})\s*年\s*({SMALL})\s*月")), |c| date(c.get(1), c.get(2), None)),
+                // 2024-03-15, 2024/3/15
+                (
+                    re(r"(?-u:\b)(\d{4})[-/.](\d{1,2})[-/.](\d{1,2})(?-u:\b)".to_string()),
+                    |c| date(c.get(1), c.get(2), c.get(3)),
+                ),
+                // 3/15, 15/3/2024 (month and day order is ambiguous)
+                (
+                    re(r"(?-u:\b)(\d{1,2})/(\d{1,2})(?:/(\d{4}|\d{2}))?(?-u:\b)".to_string()),
+                    |c| {
+                        let first = number(c.get(1)?.as_str())?;
+                        let second = number(c.get(2)?.as_str())?;
+                        let year = c.get(3).and_then(|y| number(y.as_str())).map(full_year);
+                        let month_days: Vec<(f64, f64)> = [(first, second), (second, first)]
+                            .into_iter()
+                            .filter(|(m, d)| (1.0..=12.0).contains(m) && (1.0..=31.0).contains(d))
+                            .collect();
+                        (!month_days.is_empty()).then_some(Value::Date { year, month_days })
+                    },
+                ),
+                // March 15, March 15th, 2024
+                (
+                    re(format!(r"({MONTH})\.?\s+(\d{{1,2}})(?:st|nd|rd|th)?(?:,?\s+(\d{{4}}))?(?-u:\b)")),
+                    |c| english_date(c.get(1)?.as_str(), c.get(2)?.as_str(), c.get(3)),
+                ),
+                // 15 March, 15th of March 2024
+                (
+                    re(format!(r"(?-u:\b)(\d{{1,2}})(?:st|nd|rd|th)?\s+(?:of\s+)?({MONTH})\.?(?:,?\s+(\d{{4}}))?")),
+                    |c| english_date(c.get(2)?.as_str(), c.get(1)?.as_str(), c.get(3)),
+                ),
+            ],
+            times: vec![
+                // 午後3時半, 15時30分, 오후 3시
+                (
+                    re(format!(r"(午前|午後|오전|오후)?\s*({SMALL})\s*[時시]\s*(?:({SMALL})\s*[分분]|(半))?")),
+                    |c| {
+                        let minutes = match (c.get(3), c.get(4)) {
+                            (Some(m), _) => number(m.as_str())?,
+                            (None, Some(_)) => 30.0,
+                            (None, None) => 0.0,
+                        };
+                        let afternoon = c.get(1).map(|m| matches!(m.as_str(), "午後" | "오후"));
+                        time(number(c.get(2)?.as_str())?, minutes, afternoon)
+                    },
+                ),
+                // 15:30, 3:30 pm
+                (
+                    re(r"(?i)(?-u:\b)(\d{1,2}):(\d{2})(?:\s*([ap])\.?m(?-u:\b)\.?)?".to_string()),
+                    |c| {
+                        let afternoon = c.get(3).map(|m| m.as_str().eq_ignore_ascii_case("p"));
+                        time(number(c.get(1)?.as_str())?, number(c.get(2)?.as_str())?, afternoon)
+                    },
+                ),
+                // 3pm, 3 p.m.
+                (
+                    re(r"(?i)(?-u:\b)(\d{1,2})\s*([ap])\.?m(?-u:\b)\.?".to_string()),
+                    |c| {
+                        let afternoon = Some(c.get(2)?.as_str().eq_ignore_ascii_case("p"));
+                        time(number(c.get(1)?.as_str())?, 0.0, afternoon)
+                    },
+                ),
+            ],
+            currency_prefix: re(format!(
+                r"(US\$|\$|¥|€|£|₩|(?-u:\b)(?:USD|JPY|EUR|GBP|KRW|CNY|RMB)(?-u:\b))\s*({AMOUNT})(?:{SCALE}|(?:k|K|M|mn|bn|B)(?-u:\b))?"
+            )),
+            currency_suffix: re(format!(
+                r"(?i)({AMOUNT})(?:{SCALE})?\s*(円|ドル|ユーロ|ポンド|ウォン|원|元|人民元|(?-u:\b)(?:yen|dollars?|euros?|pounds?|won|yuan|usd|jpy|eur|gbp|krw|cny|rmb)(?-u:\b))"
+            )),
+            percent: re(format!(r"(?i)({AMOUNT})\s*(?:%|パーセント|퍼센트|(?-u:\b)per\s?cent(?-u:\b))")),
+            wari: re(format!(r"({SMALL})\s*割")),
+            number: re(format!(r"(?i)({AMOUNT})(?:{SCALE})?")),
+            number_word: re(
+                r"(?i)(?-u:\b)(zero|one|two|three|four|five|six|seven|eight|nine|ten|eleven|twelve)(?-u:\b)".to_string(),
+            ),
+        }
+    })
+}
+
+/// Find every quantity in the text, most specific kinds first
+pub fn extract(text: &str) -> Vec<Quantity> {
+    let text = normalize(text);
+    let patterns = patterns();
+    let mut found: Vec<(Range<usize>, Quantity)> = Vec::new();
+
+    let add = |found: &mut Vec<(Range<usize>, Quantity)>, range: Range<usize>, value: Value, loose: bool| {
+        if found.iter().any(|(taken, _)| taken.start < range.end && range.start < taken.end) {
+            return;
+        }
+        let quantity = Quantity {
+            text: text[range.clone()].trim().to_string(),
+            value,
+            loose,
+        };
+        found.push((range, quantity));
+    };
+
+    for (pattern, parse) in patterns.dates.iter().chain(&patterns.times) {
+        for captures in pattern.captures_iter(&text) {
+            let whole = captures.get(0).expect("whole match");
+            // 3時間 is a duration, not a time of day
+            if text[whole.end()..].starts_with('間') {
+                continue;
+            }
+            if let Some(value) = parse(&captures) {
+                // 一時 ("for a while") and 十分 ("enough") read like times
+                let loose = matches!(value, Value::Time { meridiem: false, .. }) && !has_digit(whole.as_str());
+                add(&mut found, whole.range(), value, loose);
+            }
+        }
+    }
+
+    for captures in patterns.currency_prefix.captures_iter(&text) {
+        let whole = captures.get(0).expect("whole match");
+        let code = currency_code(&captures[1]);
+        let scale = scale(whole.as_str());
+        if let (Some(code), Some(amount)) = (code, number(&captures[2])) {
+            add(&mut found, whole.range(), Value::Currency(code, amount * scale), false);
+        }
+    }
+    for captures in patterns.currency_suffix.captures_iter(&text) {
+        let whole = captures.get(0).expect("whole match");
+        let unit = captures.get(2).expect("currency unit");
+        let code = currency_code(unit.as_str());
+        let scale = scale(&text[whole.start()..unit.start()]);
+        if let (Some(code), Some(amount)) = (code, number(&captures[1])) {
+            add(&mut found, whole.range(), Value::Currency(code, amount * scale), false);
+        }
+    }
+
+    for captures in patterns.percent.captures_iter(&text) {
+        let whole = captures.get(0).expect("whole match");
+        if let Some(percent) = number(&captures[1]) {
+            add(&mut found, whole.range(), Value::Percent(percent), false);
+        }
+    }
+    for captures in patterns.wari.captures_iter(&text) {
+        let whole = captures.get(0).expect("whole match");
+        if let Some(tenths) = number(&captures[1]) {
+            add(&mut found, whole.range(), Value::Percent(tenths * 10.0), !has_digit(whole.as_str()));
+        }
+    }
+
+    for captures in patterns.number.captures_iter(&text) {
+        let whole = captures.get(0).expect("whole match");
+        if let Some(n) = number(&captures[1]) {
+            let value = Value::Number(n * scale(whole.as_str()));
+            add(&mut found, whole.range(), value, !has_digit(whole.as_str()));
+        }
+    }
+    for captures in patterns.number_word.captures_iter(&text) {
+        let whole = captures.get(0).expect("whole match");
+        if let Some(n) = number_word(&captures[1]) {
+            add(&mut found, whole.range(), Value::Number(n), true);
+        }
+    }
+
+    found.sort_by_key(|(range, _)| range.start);
+    found.into_iter().map(|(_, quantity)| quantity).collect()
+}
+
+/// Full-width digits and symbols (common in Japanese text) to ASCII
+fn normalize(text: &str) -> String {
+    text.chars()
+        .map(|c| match c {
+            '０'..='９' | '％' | '＄' | '：' | '／' | '，' | '．' | '－' => {
+                char::from_u32(c as u32 - 0xFEE0).unwrap_or(c)
+            }
+            '￥' => '¥',
+            c => c,
+        })
+        .collect()
+}
+
+fn has_digit(text: &str) -> bool {
+    text.chars().any(|c| c.is_ascii_digit())
+}
+
+fn kanji_digit(c: char) -> Option<u32> {
+    "〇一二三四五六七八九".chars().position(|k| k == c).map(|d| d as u32)
+}
+
+/// Parse digits, kanji numerals or a mix, with 万/億/兆: 1.5億, 3万5千, 二十五, 二〇二四
+pub fn number(text: &str) -> Option<f64> {
+    let text = text.trim_end_matches([',', '.']);
+    let mut total = 0.0;
+    let mut section = String::new();
+
+    for c in text.chars().filter(|&c| c != ',') {
+        let unit = match c {
+            '兆' => 1e12,
+            '億' => 1e8,
+            '万' => 1e4,
+            _ => {
+                section.push(c);
+                continue;
+            }
+        };
+        let value = if section.is_empty() { 1.0 } else { small_number(&section)? };
+        total += value * unit;
+        section.clear();
+    }
+    if !section.is_empty() {
+        total += small_number(&section)?;
+    }
+
+    (!text.is_empty()).then_some(total)
+}
+
+/// A number below 万: digits, positional kanji (二〇二四) or 千/百/十 places (三千五百)
+fn small_number(text: &str) -> Option<f64> {
+    if let Ok(value) = text.parse::<f64>() {
+        return Some(value);
+    }
+    if text.chars().all(|c| kanji_digit(c).is_some()) {
+        return Some(text.chars().filter_map(kanji_digit).fold(0.0, |n, d| n * 10.0 + d as f64));
+    }
+
+    let mut total = 0.0;
+    let mut digits = String::new();
+    for c in text.chars() {
+        let place = match c {
+            '千' => 1000.0,
+            '百' => 100.0,
+            '十' => 10.0,
+            _ => {
+                match kanji_digit(c) {
+                    Some(d) => digits.push(char::from_digit(d, 10)?),
+                    None => digits.push(c),
+                }
+                continue;
+            }
+        };
+        let value = if digits.is_empty() { 1.0 } else { digits.parse::<f64>().ok()? };
+        total += value * place;
+        digits.clear();
+    }
+    if !digits.is_empty() {
+        total += digits.parse::<f64>().ok()?;
+    }
+    Some(total)
+}
+
+/// Multiplier for an English scale word or suffix at the end of a match
+fn scale(text: &str) -> f64 {
+    let lower = text.to_lowercase();
+    let lower = lower.trim_end_matches(|c: char| !c.is_ascii_alphanumeric());
+    let words = [
+        ("thousand", 1e3),
+        ("million", 1e6),
+        ("billion", 1e9),
+        ("trillion", 1e12),
+        ("mn", 1e6),
+        ("bn", 1e9),
+        ("k", 1e3),
+        ("m", 1e6),
+        ("b", 1e9),
+    ];
+    for (word, multiplier) in words {
+        let Some(rest) = lower.strip_suffix(word) else { continue };
+        // Only a suffix straight after the number, or a whole word after a space
+        if rest.ends_with(|c: char| c.is_ascii_digit()) || (word.len() > 2 && rest.ends_with(char::is_whitespace)) {
+            return multiplier;
+        }
+    }
+    1.0
+}
+
+fn currency_code(symbol: &str) -> Option<&'static str> {
+    let code = match symbol.to_lowercase().as_str() {
+        "$" | "us$" | "usd" | "ドル" | "dollar" | "dollars" => "USD",
+        "¥" | "jpy" | "円" | "yen" => "JPY",
+        "€" | "eur" | "ユーロ" | "euro" | "euros" => "EUR",
+        "£" | "gbp" | "ポンド" | "pound" | "pounds" => "GBP",
+        "₩" | "krw" | "ウォン" | "원" | "won" => "KRW",
+        "cny" | "rmb" | "元" | "人民元" | "yuan" => "CNY",
+        _ => return None,
+    };
+    Some(code)
+}
+
+fn date(year: Option<regex::Match>, month: Option<regex::Match>, day: Option<regex::Match>) -> Option<Value> {
+    let month = number(month?.as_str())?;
+    let day = match day {
+        Some(day) => number(day.as_str())?,
+        None => 0.0,
+    };
+    let valid = (1.0..=12.0).contains(&month) && (0.0..=31.0).contains(&day);
+    valid.then(|| Value::Date {
+        year: year.and_then(|y| number(y.as_str())).map(full_year),
+        month_days: vec![(month, day)],
+    })
+}
+
+fn english_date(month: &str, day: &str, year: Option<regex::Match>) -> Option<Value> {
+    let month = match &month[..3] {
+        "Jan" => 1.0,
+        "Feb" => 2.0,
+        "Mar" => 3.0,
+        "Apr" => 4.0,
+        "May" => 5.0,
+        "Jun" => 6.0,
+        "Jul" => 7.0,
+        "Aug" => 8.0,
+        "Sep" => 9.0,
+        "Oct" => 10.0,
+        "Nov" => 11.0,
+        "Dec" => 12.0,
+        _ => return None,
+    };
+    let day = number(day)?;
+    (1.0..=31.0).contains(&day).then(|| Value::Date {
+        year: year.and_then(|y| number(y.as_str())),
+        month_days: vec![(month, day)],
+    })
+}
+
+/// Two-digit years are taken as 20xx
+fn full_year(year: f64) -> f64 {
+    if year < 100.0 {
+        year + 2000.0
+    } else {
+        year
+    }
+}
+
+fn time(hour: f64, minutes: f64, afternoon: Option<bool>) -> Option<Value> {
+    if !(0.0..=24.0).contains(&hour) || !(0.0..60.0).contains(&minutes) {
+        return None;
+    }
+    let hour = match afternoon {
+        Some(true) if hour < 12.0 => hour + 12.0,
+        Some(false) if hour == 12.0 => 0.0,
+        _ => hour,
+    };
+    Some(Value::Time {
+        minutes: hour * 60.0 + minutes,
+        meridiem: afternoon.is_some() || hour > 12.0,
+    })
+}
+
+fn number_word(word: &str) -> Option<f64> {
+    let words = [
+        "zero", "one", "two", "three", "four", "five", "six", "seven", "eight", "nine", "ten", "eleven", "twelve",
+    ];
+    let word = word.to_lowercase();
+    words.iter().position(|w| *w == word).map(|n| n as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn numbers_parse() {
+        let cases = [
+            ("15", 15.0),
+            ("1,234", 1234.0),
+            ("十五", 15.0),
+            ("二十五", 25.0),
+            ("三千五百", 3500.0),
+            ("二〇二四", 2024.0),
+            ("3万5千", 35000.0),
+            ("1.5億", 1.5e8),
+            ("十", 10.0),
+        ];
+        for (text, expected) in cases {
+            assert_eq!(number(text), Some(expected), "{}", text);
+        }
+    }
+
+    #[test]
+    fn values_are_read() {
+        let cases = [
+            ("3月15日", Value::Date { year: None, month_days: vec![(3.0, 15.0)] }),
+            ("March 15", Value::Date { year: None, month_days: vec![(3.0, 15.0)] }),
+            ("2024年3月15日", Value::Date { year: Some(2024.0), month_days: vec![(3.0, 15.0)] }),
+            ("1万円", Value::Currency("JPY", 10000.0)),
+            ("¥10,000", Value::Currency("JPY", 10000.0)),
+            ("$2.5 million", Value::Currency("USD", 2.5e6)),
+            ("2割", Value::Percent(20.0)),
+            ("20%", Value::Percent(20.0)),
+            ("午後3時半", Value::Time { minutes: 930.0, meridiem: true }),
+            ("3:30 pm", Value::Time { minutes: 930.0, meridiem: true }),
+        ];
+        for (text, expected) in cases {
+            let found = extract(text);
+            assert_eq!(found.len(), 1, "{}: {:?}", text, found);
+            assert_eq!(found[0].value, expected, "{}", text);
+        }
+    }
+
+    #[test]
+    fn equivalent_quantities_are_not_flagged() {
+        let cases = [
+            ("3月15日に会いましょう", "Let's meet on March 15"),
+            ("会費は1万円です", "The fee is ¥10,000"),
+            ("売上が2割増えた", "Sales rose 20%"),
+            ("十五人が参加します", "15 people will attend"),
+            ("午後3時半に出発", "Leaving at 3:30 pm"),
+            ("3/4までに提出", "Submit by April 3"),
+            ("3時間かかります", "It takes 3 hours"),
+        ];
+        for (original, translated) in cases {
+            assert_eq!(check(original, translated), Vec::<String>::new(), "{} / {}", original, translated);
+        }
+    }
+
+    #[test]
+    fn changed_quantities_are_flagged() {
+        let cases = [
+            ("3月15日に会いましょう", "Let's meet on March 16", "3月15日 in the original became March 16 in the translation"),
+            ("会費は1万円です", "The fee is ¥1,000", "1万円 in the original became ¥1,000 in the translation"),
+            ("売上が2割増えた", "Sales rose 25%", "2割 in the original became 25% in the translation"),
+            ("会費は1万円です", "The fee is small", "1万円 is missing from the translation"),
+            ("明日会いましょう", "Let's meet on March 16", "March 16 in the translation is not in the original"),
+        ];
+        for (original, translated, warning) in cases {
+            assert_eq!(check(original, translated), [warning], "{} / {}", original, translated);
+        }
+    }
+
+    #[test]
+    fn ordinary_words_are_not_numbers() {
+        let cases = [
+            ("一緒に行きましょう", "Let's go together"),
+            ("それで十分です", "That is enough"),
+            ("一時的な問題です", "It is a temporary problem"),
+            ("千葉に住んでいます", "I live in Chiba"),
+        ];
+        for (original, translated) in cases {
+            assert!(extract(original).iter().all(|q| q.loose), "{}", original);
+            assert_eq!(check(original, translated), Vec::<String>::new(), "{}", original);
+        }
+    }
+}
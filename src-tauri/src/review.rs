@@ -0,0 +1,302 @@
+use serde::Serialize;
+use std::ops::Range;
+
+/// Politeness level of a Japanese text
+#[derive(Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Politeness {
+    /// Plain form (だ/である, dictionary-form verbs)
+    Casual,
+    /// です/ます
+    Polite,
+    /// 尊敬語/謙譲語 on top of です/ます
+    Honorific,
+    /// Plain and polite sentences mixed
+    Mixed,
+}
+
+/// Something to fix in a draft
+#[derive(Serialize, Clone, Debug)]
+pub struct ReviewIssue {
+    pub problem: String,
+    pub suggestion: Option<String>,
+}
+
+/// A piece of the draft; annotated pieces point into the issue list
+#[derive(Serialize, Clone, Debug)]
+pub struct ReviewSpan {
+    pub text: String,
+    pub issue: Option<usize>,
+}
+
+/// Review of a Japanese draft, shown in the overlay
+#[derive(Serialize, Clone)]
+pub struct ReviewResult {
+    pub original: String,
+    pub politeness: Politeness,
+    /// The draft split into plain and annotated pieces, in order
+    pub spans: Vec<ReviewSpan>,
+    pub issues: Vec<ReviewIssue>,
+    /// The whole draft rewritten naturally, if the review service suggested one
+    pub rewrite: Option<String>,
+    /// The draft translated out of Japanese: into the home language, or the first working language if that is Japanese
+    pub back_translation: String,
+    pub back_translation_lang: String,
+    /// Model that reviewed the draft, or "local" if only the built-in checks ran
+    pub provider: String,
+}
+
+/// 尊敬語/謙譲語 and 丁重語 forms
+const HONORIFIC_MARKERS: &[&str] = &[
+    "いらっしゃ", "おっしゃ", "召し上が", "ご覧にな", "なさい", "なさっ", "申し上げ", "申しま",
+    "参りま", "いたしま", "致しま", "存じ", "拝見", "伺い", "伺っ", "させていただ", "させて頂",
+    "お越し", "ございま", "くださいませ", "しており",
+];
+
+/// です/ます forms
+const POLITE_MARKERS: &[&str] = &[
+    "です", "ます", "ました", "ません", "でした", "ましょう", "ください", "下さい",
+];
+
+/// Sentence-ending punctuation; a sentence keeps its punctuation
+fn is_sentence_end(c: char) -> bool {
+    matches!(c, '。' | '！' | '？' | '!' | '?' | '\n')
+}
+
+/// Byte ranges of the sentences in `text`, including trailing punctuation
+pub fn sentences(text: &str) -> Vec<Range<usize>> {
+    let mut ranges = Vec::new();
+    let mut start = 0;
+    let mut chars = text.char_indices().peekable();
+
+    while let Some((i, c)) = chars.next() {
+        if is_sentence_end(c) {
+            // Keep runs like "！？" or "。\n" with the sentence they end
+            let mut end = i + c.len_utf8();
+            while let Some(&(j, next)) = chars.peek() {
+                if !is_sentence_end(next) {
+                    break;
+                }
+                end = j + next.len_utf8();
+                chars.next();
+            }
+            ranges.push(start..end);
+            start = end;
+        }
+    }
+    if start < text.len() {
+        ranges.push(start..text.len());
+    }
+
+    ranges
+        .into_iter()
+        .filter(|range| text[range.clone()].chars().any(|c| c.is_alphanumeric()))
+        .collect()
+}
+
+/// Politeness of a single sentence
+pub fn sentence_politeness(sentence: &str) -> Politeness {
+    if HONORIFIC_MARKERS.iter().any(|marker| sentence.contains(marker)) {
+        Politeness::Honorific
+    } else if POLITE_MARKERS.iter().any(|marker| sentence.contains(marker)) {
+        Politeness::Polite
+    } else {
+        Politeness::Casual
+    }
+}
+
+/// Overall politeness, and sentences whose register does not match the rest
+pub fn assess(text: &str) -> (Politeness, Vec<(Range<usize>, ReviewIssue)>) {
+    let levels: Vec<(Range<usize>, Politeness)> = sentences(text)
+        .into_iter()
+        .map(|range| {
+            let level = sentence_politeness(&text[range.clone()]);
+            (range, level)
+        })
+        .collect();
+
+    let casual = levels.iter().filter(|(_, level)| *level == Politeness::Casual).count();
+    let formal = levels.len() - casual;
+
+    if formal == 0 {
+        return (Politeness::Casual, Vec::new());
+    }
+    if casual == 0 {
+        let level = if levels.iter().any(|(_, level)| *level == Politeness::Honorific) {
+            Politeness::Honorific
+        } else {
+            Politeness::Polite
+        };
+        return (level, Vec::new());
+    }
+
+    // Flag whichever register is in the minority
+    let flag_casual = formal >= casual;
+    let issues = levels
+        .into_iter()
+        .filter(|(_, level)| (*level == Politeness::Casual) == flag_casual)
+        .map(|(range, _)| {
+            let problem = if flag_casual {
+                "Plain form in an otherwise polite message; use です/ます here too"
+            } else {
+                "Polite form in an otherwise casual message"
+            };
+            (
+                range,
+                ReviewIssue {
+                    problem: problem.to_string(),
+                    suggestion: None,
+                },
+            )
+        })
+        .collect();
+
+    (Politeness::Mixed, issues)
+}
+
+/// First occurrence of `needle` in `text` that does not overlap an already flagged range
+pub fn locate(text: &str, needle: &str, taken: &[Range<usize>]) -> Option<Range<usize>> {
+    if needle.is_empty() {
+        return None;
+    }
+    text.match_indices(needle)
+        .map(|(start, found)| start..start + found.len())
+        .find(|range| !taken.iter().any(|t| t.start < range.end && range.start < t.end))
+}
+
+/// Split `text` into plain and annotated spans. Issues without a range are
+/// listed but not annotated; overlapping ranges keep the first one given.
+pub fn annotate(text: &str, flagged: Vec<(Option<Range<usize>>, ReviewIssue)>) -> (Vec<ReviewSpan>, Vec<ReviewIssue>) {
+    let mut ranges: Vec<(Range<usize>, usize)> = Vec::new();
+    let mut issues = Vec::new();
+
+    for (range, issue) in flagged {
+        let index = issues.len();
+        issues.push(issue);
+        if let Some(range) = range {
+            if !ranges.iter().any(|(r, _)| r.start < range.end && range.start < r.end) {
+                ranges.push((range, index));
+            }
+        }
+    }
+    ranges.sort_by_key(|(range, _)| range.start);
+
+    let mut spans = Vec::new();
+    let mut position = 0;
+    for (range, index) in ranges {
+        if position < range.start {
+            spans.push(ReviewSpan {
+                text: text[position..range.start].to_string(),
+                issue: None,
+            });
+        }
+        spans.push(ReviewSpan {
+            text: text[range.clone()].to_string(),
+            issue: Some(index),
+        });
+        position = range.end;
+    }
+    if position < text.len() {
+        spans.push(ReviewSpan {
+            text: text[position..].to_string(),
+            issue: None,
+        });
+    }
+
+    (spans, issues)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn issue(problem: &str) -> ReviewIssue {
+        ReviewIssue {
+            problem: problem.to_string(),
+            suggestion: None,
+        }
+    }
+
+    fn texts<'a>(text: &'a str, ranges: &[Range<usize>]) -> Vec<&'a str> {
+        ranges.iter().map(|range| &text[range.clone()]).collect()
+    }
+
+    #[test]
+    fn splits_sentences_keeping_punctuation_runs() {
+        let text = "今日は晴れ。明日は雨！？\nまた明日";
+        assert_eq!(texts(text, &sentences(text)), vec!["今日は晴れ。", "明日は雨！？\n", "また明日"]);
+
+        let text = "はい。\n\n「」本当?!";
+        assert_eq!(texts(text, &sentences(text)), vec!["はい。\n\n", "「」本当?!"]);
+        let text = "はい。……。";
+        assert_eq!(texts(text, &sentences(text)), vec!["はい。"]);
+        assert!(sentences("").is_empty());
+    }
+
+    #[test]
+    fn detects_sentence_register() {
+        assert_eq!(sentence_politeness("明日行く。"), Politeness::Casual);
+        assert_eq!(sentence_politeness("明日行きます。"), Politeness::Polite);
+        assert_eq!(sentence_politeness("資料を拝見しました。"), Politeness::Honorific);
+    }
+
+    #[test]
+    fn assesses_consistent_drafts() {
+        assert_eq!(assess("明日行く。資料も送る。").0, Politeness::Casual);
+        assert_eq!(assess("明日行きます。資料を送ります。").0, Politeness::Polite);
+        let (level, issues) = assess("明日伺います。資料を送ります。");
+        assert_eq!(level, Politeness::Honorific);
+        assert!(issues.is_empty());
+    }
+
+    #[test]
+    fn flags_the_minority_register() {
+        let text = "明日行きます。資料を送ります。よろしく。";
+        let (level, issues) = assess(text);
+        assert_eq!(level, Politeness::Mixed);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(&text[issues[0].0.clone()], "よろしく。");
+        assert!(issues[0].1.problem.starts_with("Plain form"));
+
+        let text = "明日行く。資料送る。お願いします。";
+        let (level, issues) = assess(text);
+        assert_eq!(level, Politeness::Mixed);
+        assert_eq!(texts(text, &issues.iter().map(|(range, _)| range.clone()).collect::<Vec<_>>()), vec!["お願いします。"]);
+        assert!(issues[0].1.problem.starts_with("Polite form"));
+    }
+
+    #[test]
+    fn locate_skips_taken_ranges() {
+        let text = "はい、はい、はい";
+        let first = locate(text, "はい", &[]).unwrap();
+        assert_eq!(first, 0..6);
+        let second = locate(text, "はい", std::slice::from_ref(&first)).unwrap();
+        assert_eq!(&text[..second.start], "はい、");
+        // Overlapping a taken range counts as taken, not just starting inside it
+        assert_eq!(locate(text, "い、は", std::slice::from_ref(&first)).map(|r| r.start), Some(12));
+        assert_eq!(locate(text, "はい", &[first, second, 18..24]), None);
+        assert_eq!(locate(text, "", &[]), None);
+        assert_eq!(locate(text, "いいえ", &[]), None);
+    }
+
+    #[test]
+    fn annotate_lists_unplaced_notes_without_spans() {
+        let text = "明日は会議です。";
+        let flagged = vec![
+            (Some(6..12), issue("meeting")),
+            (None, issue("tone")),
+            (Some(9..15), issue("overlapping")),
+        ];
+        let (spans, issues) = annotate(text, flagged);
+
+        let problems: Vec<&str> = issues.iter().map(|issue| issue.problem.as_str()).collect();
+        assert_eq!(problems, vec!["meeting", "tone", "overlapping"]);
+        let pieces: Vec<(&str, Option<usize>)> = spans.iter().map(|span| (span.text.as_str(), span.issue)).collect();
+        assert_eq!(pieces, vec![("明日", None), ("は会", Some(0)), ("議です。", None)]);
+
+        let (spans, issues) = annotate(text, vec![(None, issue("tone"))]);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(spans.len(), 1);
+        assert_eq!(spans[0].issue, None);
+    }
+}
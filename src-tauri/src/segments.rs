@@ -0,0 +1,364 @@
+use regex::{Match, Regex};
+use std::sync::OnceLock;
+
+use crate::language::{detect_language, is_cjk, primary_subtag};
+
+/// A piece of captured text, either natural language or something to keep as-is
+#[derive(Clone, Debug, PartialEq)]
+pub enum Segment {
+    /// Kept exactly as written: whitespace, URLs, code, emails, bare punctuation
+    Verbatim(String),
+    /// Natural-language text and its detected language
+    Text { text: String, lang: String },
+}
+
+impl Segment {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Segment::Verbatim(text) | Segment::Text { text, .. } => text,
+        }
+    }
+}
+
+/// Latin runs inside CJK sentences with at least this many words are treated as
+/// separate segments (quoted English); shorter ones (code names) stay inline
+const MIN_EMBEDDED_WORDS: usize = 3;
+
+/// Opening and closing quote or bracket pairs
+const QUOTE_PAIRS: &[(char, char)] = &[('「', '」'), ('『', '』'), ('（', '）'), ('(', ')'), ('"', '"')];
+
+/// URLs, emails and inline or fenced code
+fn protected_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| {
+        Regex::new(concat!(
+            r"```[\s\S]*?```",
+            r"|`[^`\n]+`",
+            r"|(?:https?://|www\.)[^\s<>`「」『』（）【】、。]+",
+            r"|[\w.+-]+@[\w-]+(?:\.[\w-]+)+",
+        ))
+        .expect("valid protected span pattern")
+    })
+}
+
+/// Split text into language segments, keeping URLs, code and whitespace verbatim.
+/// Adjacent segments in the same language are merged so each gets translated
+/// with its full context.
+pub fn split_segments(text: &str, preferred: &[String]) -> Vec<Segment> {
+    let mut segments = Vec::new();
+    let mut last = 0;
+
+    for m in protected_pattern().find_iter(text) {
+        let end = protected_end(&m);
+        push_prose(&text[last..m.start()], preferred, &mut segments);
+        segments.push(Segment::Verbatim(text[m.start()..end].to_string()));
+        last = end;
+    }
+    push_prose(&text[last..], preferred, &mut segments);
+
+    merge_adjacent(segments)
+}
+
+/// Whether the segments contain more than one language
+pub fn is_multilingual(segments: &[Segment]) -> bool {
+    let mut langs = segments.iter().filter_map(|segment| match segment {
+        Segment::Text { lang, .. } => Some(primary_subtag(lang)),
+        Segment::Verbatim(_) => None,
+    });
+
+    match langs.next() {
+        Some(first) => langs.any(|lang| !lang.eq_ignore_ascii_case(first)),
+        None => false,
+    }
+}
+
+/// Whether the text needs per-segment translation instead of a single request:
+/// it mixes languages or contains URLs or code that must survive untouched
+pub fn needs_segmenting(segments: &[Segment]) -> bool {
+    is_multilingual(segments)
+        || segments
+            .iter()
+            .any(|segment| matches!(segment, Segment::Verbatim(text) if protected_pattern().is_match(text)))
+}
+
+/// Trailing sentence punctuation is not part of a URL
+fn protected_end(m: &Match) -> usize {
+    let matched = m.as_str();
+    if matched.starts_with('`') {
+        return m.end();
+    }
+    let trimmed = matched.trim_end_matches(['.', ',', ';', ':', '!', '?', ')', '\'', '"']);
+    m.start() + trimmed.len()
+}
+
+/// Split prose into sentences and embedded foreign runs, detecting each one
+fn push_prose(prose: &str, preferred: &[String], segments: &mut Vec<Segment>) {
+    for sentence in split_sentences(prose) {
+        for piece in split_embedded_runs(sentence) {
+            push_piece(piece, preferred, segments);
+        }
+    }
+}
+
+/// Push a piece of prose with its surrounding whitespace split off as verbatim
+fn push_piece(piece: &str, preferred: &[String], segments: &mut Vec<Segment>) {
+    let core = piece.trim();
+    if core.is_empty() {
+        if !piece.is_empty() {
+            segments.push(Segment::Verbatim(piece.to_string()));
+        }
+        return;
+    }
+
+    let leading = &piece[..piece.len() - piece.trim_start().len()];
+    let trailing = &piece[piece.trim_end().len()..];
+
+    if !leading.is_empty() {
+        segments.push(Segment::Verbatim(leading.to_string()));
+    }
+    if core.chars().any(char::is_alphabetic) {
+        let lang = detect_language(core, preferred).lang;
+        segments.push(Segment::Text {
+            text: core.to_string(),
+            lang,
+        });
+    } else {
+        segments.push(Segment::Verbatim(core.to_string()));
+    }
+    if !trailing.is_empty() {
+        segments.push(Segment::Verbatim(trailing.to_string()));
+    }
+}
+
+/// Split at 。！？!? and newlines, and at '.' followed by whitespace
+fn split_sentences(text: &str) -> Vec<&str> {
+    let mut sentences = Vec::new();
+    let mut start = 0;
+    let mut chars = text.char_indices().peekable();
+
+    while let Some((i, c)) = chars.next() {
+        let next = chars.peek().map(|(_, next)| *next);
+        let boundary = match c {
+            '。' | '！' | '？' | '!' | '?' | '\n' => true,
+            '.' => next.is_none() || next.is_some_and(char::is_whitespace),
+            _ => false,
+        };
+
+        // Keep closing quotes and brackets with the sentence they end
+        if boundary && !matches!(next, Some('」' | '』' | '）' | ')' | '"' | '\'')) {
+            let end = i + c.len_utf8();
+            sentences.push(&text[start..end]);
+            start = end;
+        }
+    }
+    if start < text.len() {
+        sentences.push(&text[start..]);
+    }
+
+    sentences
+}
+
+/// Cut long Latin-script runs (e.g. quoted English) out of a CJK sentence
+fn split_embedded_runs(sentence: &str) -> Vec<&str> {
+    if !sentence.chars().any(is_cjk) {
+        return vec![sentence];
+    }
+
+    // Byte ranges of maximal runs without CJK characters
+    let mut runs = Vec::new();
+    let mut run_start = None;
+    for (i, c) in sentence.char_indices() {
+        match (is_cjk(c), run_start) {
+            (false, None) => run_start = Some(i),
+            (true, Some(start)) => {
+                runs.push((start, i));
+                run_start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(start) = run_start {
+        runs.push((start, sentence.len()));
+    }
+
+    let mut pieces = Vec::new();
+    let mut last = 0;
+    for (start, end) in runs {
+        let run = &sentence[start..end];
+        let words = run
+            .split_whitespace()
+            .filter(|word| word.chars().any(char::is_alphabetic))
+            .count();
+        if words < MIN_EMBEDDED_WORDS {
+            continue;
+        }
+
+        // Trim surrounding quotes and brackets back into the CJK text
+        let core_start = match run.find(|c: char| c.is_alphanumeric()) {
+            Some(offset) => start + offset,
+            None => continue,
+        };
+        let mut core_end = run
+            .rfind(|c: char| c.is_alphanumeric() || matches!(c, '.' | '!' | '?'))
+            .map(|offset| start + offset + 1)
+            .unwrap_or(end);
+
+        // Keep a closing bracket with its opening one
+        let core = &sentence[core_start..core_end];
+        if let Some(close) = sentence[core_end..].chars().next() {
+            let opened = QUOTE_PAIRS
+                .iter()
+                .any(|(open, pair_close)| *pair_close == close && core.contains(*open));
+            if opened {
+                core_end += close.len_utf8();
+            }
+        }
+
+        if core_start > last {
+            pieces.push(&sentence[last..core_start]);
+        }
+        pieces.push(&sentence[core_start..core_end]);
+        last = core_end;
+    }
+    if last < sentence.len() {
+        pieces.push(&sentence[last..]);
+    }
+
+    pieces
+}
+
+/// Merge neighbouring verbatim segments, and text segments in the same
+/// language that are separated only by whitespace
+fn merge_adjacent(segments: Vec<Segment>) -> Vec<Segment> {
+    let mut merged: Vec<Segment> = Vec::new();
+
+    for segment in segments {
+        match (merged.last_mut(), segment) {
+            (Some(Segment::Verbatim(prev)), Segment::Verbatim(text)) => prev.push_str(&text),
+            (Some(Segment::Text { text: prev, lang: prev_lang }), Segment::Text { text, lang })
+                if primary_subtag(prev_lang) == primary_subtag(&lang) =>
+            {
+                prev.push_str(&text)
+            }
+            (_, segment) => merged.push(segment),
+        }
+
+        // Fold "text, whitespace, text" in the same language into one segment
+        let len = merged.len();
+        if len >= 3 {
+            if let (
+                Segment::Text { lang: first, .. },
+                Segment::Verbatim(gap),
+                Segment::Text { lang: second, .. },
+            ) = (&merged[len - 3], &merged[len - 2], &merged[len - 1])
+            {
+                if gap.trim().is_empty() && primary_subtag(first) == primary_subtag(second) {
+                    let tail: String = merged
+                        .drain(len - 2..)
+                        .map(|segment| segment.as_str().to_string())
+                        .collect();
+                    if let Some(Segment::Text { text, .. }) = merged.last_mut() {
+                        text.push_str(&tail);
+                    }
+                }
+            }
+        }
+    }
+
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn split(text: &str) -> Vec<Segment> {
+        split_segments(text, &["en".to_string(), "ja".to_string()])
+    }
+
+    fn text(text: &str, lang: &str) -> Segment {
+        Segment::Text {
+            text: text.to_string(),
+            lang: lang.to_string(),
+        }
+    }
+
+    fn verbatim(text: &str) -> Segment {
+        Segment::Verbatim(text.to_string())
+    }
+
+    fn joined(segments: &[Segment]) -> String {
+        segments.iter().map(Segment::as_str).collect()
+    }
+
+    #[test]
+    fn splits_sentences_by_language() {
+        let input = "The build failed on the main branch. ビルドが失敗しました。";
+        let segments = split(input);
+        assert_eq!(
+            segments,
+            vec![
+                text("The build failed on the main branch.", "en"),
+                verbatim(" "),
+                text("ビルドが失敗しました。", "ja"),
+            ]
+        );
+        assert!(is_multilingual(&segments));
+    }
+
+    #[test]
+    fn keeps_urls_and_code_verbatim() {
+        let input = "Please check the deployment logs at https://example.com/logs.";
+        let segments = split(input);
+        assert_eq!(
+            segments,
+            vec![
+                text("Please check the deployment logs at", "en"),
+                verbatim(" https://example.com/logs."),
+            ]
+        );
+        assert!(needs_segmenting(&segments));
+
+        let input = "Run `cargo build --release` before you push the branch";
+        let segments = split(input);
+        assert!(segments.contains(&verbatim(" `cargo build --release` ")));
+        assert_eq!(joined(&segments), input);
+    }
+
+    #[test]
+    fn cuts_quoted_english_out_of_japanese() {
+        let input = "彼は「I will be late today」と言いました。";
+        let segments = split(input);
+        assert_eq!(
+            segments,
+            vec![
+                text("彼は「", "ja"),
+                text("I will be late today", "en"),
+                text("」と言いました。", "ja"),
+            ]
+        );
+    }
+
+    #[test]
+    fn short_latin_runs_stay_inline() {
+        let segments = split("このPRをレビューしてください。");
+        assert_eq!(segments, vec![text("このPRをレビューしてください。", "ja")]);
+        assert!(!needs_segmenting(&segments));
+    }
+
+    #[test]
+    fn merges_same_language_across_whitespace() {
+        let merged = merge_adjacent(vec![
+            text("First.", "en"),
+            verbatim(" "),
+            text("Second.", "en-US"),
+            verbatim("\n"),
+            verbatim("---"),
+            text("三番目。", "ja"),
+        ]);
+        assert_eq!(
+            merged,
+            vec![text("First. Second.", "en"), verbatim("\n---"), text("三番目。", "ja")]
+        );
+    }
+}
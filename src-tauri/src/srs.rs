@@ -0,0 +1,131 @@
+use serde::Serialize;
+
+/// Ease factor of a card that has never been reviewed
+pub const INITIAL_EASE: f64 = 2.5;
+
+/// SM-2 never lets the ease factor drop below this
+const MIN_EASE: f64 = 1.3;
+
+/// A forgotten card comes back this soon, to be relearned in the same session
+const RELEARN_DELAY_MS: i64 = 10 * 60 * 1000;
+
+const DAY_MS: i64 = 24 * 60 * 60 * 1000;
+
+/// Lowest grade that counts as remembered
+const PASSING_GRADE: u8 = 3;
+
+/// Scheduling state of one card
+#[derive(Serialize, Clone, Copy, Debug, PartialEq)]
+pub struct Schedule {
+    pub ease: f64,
+    /// Days until the next review after a successful one; 0 while (re)learning
+    pub interval_days: i64,
+    /// Successful reviews in a row
+    pub repetitions: i64,
+    pub lapses: i64,
+    /// Unix milliseconds when the card is next due
+    pub due_at: i64,
+}
+
+impl Default for Schedule {
+    fn default() -> Self {
+        Self {
+            ease: INITIAL_EASE,
+            interval_days: 0,
+            repetitions: 0,
+            lapses: 0,
+            due_at: 0,
+        }
+    }
+}
+
+/// Schedule the next review after grading a card from 0 (blackout) to 5
+/// (perfect recall), following SuperMemo 2: intervals of 1 and 6 days, then
+/// the previous interval times the ease factor. A grade below 3 starts the
+/// card over and shows it again shortly, without changing its ease.
+pub fn grade(schedule: Schedule, grade: u8, now: i64) -> Result<Schedule, String> {
+    if grade > 5 {
+        return Err(format!("Grade must be 0 to 5, not {}", grade));
+    }
+
+    if grade < PASSING_GRADE {
+        return Ok(Schedule {
+            ease: schedule.ease,
+            interval_days: 0,
+            repetitions: 0,
+            lapses: schedule.lapses + i64::from(schedule.repetitions > 0),
+            due_at: now + RELEARN_DELAY_MS,
+        });
+    }
+
+    let q = f64::from(grade);
+    let ease = (schedule.ease + 0.1 - (5.0 - q) * (0.08 + (5.0 - q) * 0.02)).max(MIN_EASE);
+    let interval_days = match schedule.repetitions {
+        0 => 1,
+        1 => 6,
+        _ => ((schedule.interval_days as f64) * ease).round() as i64,
+    };
+    Ok(Schedule {
+        ease,
+        interval_days,
+        repetitions: schedule.repetitions + 1,
+        lapses: schedule.lapses,
+        due_at: now + interval_days * DAY_MS,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: i64 = 1_700_000_000_000;
+
+    fn review(grades: &[u8]) -> Schedule {
+        grades
+            .iter()
+            .fold(Schedule::default(), |schedule, &g| grade(schedule, g, NOW).unwrap())
+    }
+
+    #[test]
+    fn intervals_grow_from_one_and_six_days() {
+        assert_eq!(review(&[4]).interval_days, 1);
+        assert_eq!(review(&[4, 4]).interval_days, 6);
+        // Grade 4 leaves the ease at 2.5
+        assert_eq!(review(&[4, 4, 4]).interval_days, 15);
+
+        let perfect = review(&[5, 5, 5]);
+        assert!((perfect.ease - 2.8).abs() < 1e-9);
+        assert_eq!(perfect.interval_days, (6.0 * perfect.ease).round() as i64);
+        assert_eq!(perfect.repetitions, 3);
+        assert_eq!(perfect.due_at, NOW + 17 * DAY_MS);
+    }
+
+    #[test]
+    fn ease_never_drops_below_the_floor() {
+        let hard = review(&[3, 3, 3, 3, 3, 3, 3, 3, 3, 3]);
+        assert_eq!(hard.ease, MIN_EASE);
+        assert!(hard.interval_days > 6);
+    }
+
+    #[test]
+    fn lapses_start_the_card_over_without_changing_ease() {
+        let learned = review(&[5, 4, 4]);
+        let forgotten = grade(learned, 1, NOW).unwrap();
+        assert_eq!(forgotten.repetitions, 0);
+        assert_eq!(forgotten.interval_days, 0);
+        assert_eq!(forgotten.lapses, 1);
+        assert_eq!(forgotten.ease, learned.ease);
+        assert_eq!(forgotten.due_at, NOW + RELEARN_DELAY_MS);
+
+        // Failing a card that was never learned is not a lapse
+        assert_eq!(review(&[0]).lapses, 0);
+        assert_eq!(review(&[0, 0, 2]).ease, INITIAL_EASE);
+        assert_eq!(grade(forgotten, 4, NOW).unwrap().interval_days, 1);
+    }
+
+    #[test]
+    fn grades_above_five_are_rejected() {
+        assert!(grade(Schedule::default(), 6, NOW).is_err());
+        assert!(grade(Schedule::default(), 5, NOW).is_ok());
+    }
+}
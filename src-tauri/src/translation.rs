@@ -1,22 +1,88 @@
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
+use futures::{StreamExt, TryStreamExt};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::time::Duration;
+use crate::chunks::{self, Chunk};
 use crate::config::TranslationConfig;
+use crate::grammar::{self, GrammarExplanation};
+use crate::language::same_language;
+use crate::segments::Segment;
 
 /// Request structure for Worker API
 #[derive(Serialize)]
 struct WorkerRequest {
     text: String,
     target_lang: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    source_lang: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    formality: Option<Formality>,
+    /// Number of alternative translations to return besides the main one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    alternatives: Option<usize>,
+    /// Text just before this one in a longer document, for reference only
+    #[serde(skip_serializing_if = "Option::is_none")]
+    context: Option<String>,
+}
+
+/// Chunks, segments or text nodes of one text translated at the same time
+const MAX_CONCURRENT_CHUNKS: usize = 3;
+
+/// Register of Japanese output: plain form, です/ます, or 尊敬語/謙譲語
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Formality {
+    Casual,
+    Polite,
+    Honorific,
+}
+
+impl Formality {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "casual" | "plain" => Some(Formality::Casual),
+            "polite" | "desu-masu" => Some(Formality::Polite),
+            "honorific" | "keigo" => Some(Formality::Honorific),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Formality::Casual => "casual",
+            Formality::Polite => "polite",
+            Formality::Honorific => "honorific",
+        }
+    }
+
+    /// Formality only changes the output for languages with grammatical registers
+    pub fn applies_to(target_lang: &str) -> bool {
+        same_language(target_lang, "ja")
+    }
 }
 
 /// Response structure from Worker API
 #[derive(Deserialize)]
 struct WorkerResponse {
     translation: String,
-    #[allow(dead_code)]
     model: String,
     #[allow(dead_code)]
     detected_lang: Option<String>,
+    /// Only present when alternatives were asked for
+    #[serde(default)]
+    alternatives: Vec<Alternative>,
+}
+
+/// Another way to translate the same text, with how it differs
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Alternative {
+    /// Short label, e.g. "literal", "natural", "concise"
+    pub style: String,
+    pub text: String,
+    /// Nuance compared with the other translations
+    #[serde(default)]
+    pub note: String,
 }
 
 /// Error response from Worker API
@@ -24,71 +90,179 @@ struct WorkerResponse {
 struct WorkerError {
     error: String,
     #[serde(default)]
+    #[allow(dead_code)]
     code: Option<String>,
 }
 
-/// Detect if text is Japanese or English
-pub fn detect_language(text: &str) -> &'static str {
-    let japanese_chars = text.chars().filter(|c| {
-        matches!(
-            *c as u32,
-            0x3040..=0x309F | // Hiragana
-            0x30A0..=0x30FF | // Katakana
-            0x4E00..=0x9FAF | // CJK Unified Ideographs
-            0x3400..=0x4DBF   // CJK Extension A
-        )
-    }).count();
-
-    let total_chars = text.chars().filter(|c| !c.is_whitespace()).count();
-
-    // If more than 10% are Japanese characters, consider it Japanese
-    if total_chars > 0 && (japanese_chars as f32 / total_chars as f32) > 0.1 {
-        "ja"
-    } else {
-        "en"
-    }
+/// A translation and the model that produced it
+#[derive(Clone, Debug)]
+pub struct Translation {
+    pub text: String,
+    pub model: String,
+    /// Alternative translations, best first
+    pub alternatives: Vec<Alternative>,
 }
 
-/// Get target language based on detected source
-pub fn get_target_language(detected: &str) -> &'static str {
-    match detected {
-        "ja" => "en",
-        _ => "ja",
+/// Translate text using Cloudflare Worker proxy, in chunks if it is long
+pub async fn translate(
+    text: &str,
+    source_lang: Option<&str>,
+    target_lang: &str,
+    formality: Option<Formality>,
+    config: &TranslationConfig,
+) -> Result<Translation, String> {
+    if chunks::needs_chunking(text) {
+        let chunks = chunks::split(text, chunks::MAX_CHUNK_CHARS);
+        return translate_chunks(&chunks, source_lang, target_lang, formality, config, |_, _| {}).await;
     }
+    request_translation(text, source_lang, target_lang, formality, 0, None, config).await
 }
 
-/// Translate text using Cloudflare Worker proxy
-pub async fn translate(text: &str, target_lang: &str, config: &TranslationConfig) -> Result<String, String> {
-    let client = reqwest::Client::builder()
-        .timeout(Duration::from_secs(30))
-        .build()
-        .map_err(|e| format!("HTTP client error: {}", e))?;
+/// Translate text and ask for `count` ranked alternative translations in the
+/// same request. A Worker without alternatives support returns none.
+pub async fn translate_with_alternatives(
+    text: &str,
+    source_lang: Option<&str>,
+    target_lang: &str,
+    formality: Option<Formality>,
+    count: usize,
+    config: &TranslationConfig,
+) -> Result<Translation, String> {
+    request_translation(text, source_lang, target_lang, formality, count, None, config).await
+}
+
+/// Translate the chunks of a long text, a few at a time, and join the results
+/// in order. Each chunk is sent with the end of the one before it as context.
+/// `on_progress` is called with the number of chunks done and the total.
+pub async fn translate_chunks(
+    chunks: &[Chunk],
+    source_lang: Option<&str>,
+    target_lang: &str,
+    formality: Option<Formality>,
+    config: &TranslationConfig,
+    on_progress: impl Fn(usize, usize) + Sync,
+) -> Result<Translation, String> {
+    let done = AtomicUsize::new(0);
+    let (done, on_progress) = (&done, &on_progress);
+
+    // Collected first so the stream holds plain futures, which keeps it `Send`
+    let requests: Vec<_> = chunks
+        .iter()
+        .enumerate()
+        .map(|(i, chunk)| async move {
+            let translation = request_translation(
+                &chunk.text,
+                source_lang,
+                target_lang,
+                formality,
+                0,
+                chunk.context.as_deref(),
+                config,
+            )
+            .await
+            .map_err(|e| format!("Part {} of {}: {}", i + 1, chunks.len(), e))?;
+            on_progress(done.fetch_add(1, Ordering::SeqCst) + 1, chunks.len());
+            Ok::<_, String>(translation)
+        })
+        .collect();
+    let translations: Vec<Translation> = futures::stream::iter(requests)
+        .buffered(MAX_CONCURRENT_CHUNKS)
+        .try_collect()
+        .await?;
 
+    let texts: Vec<String> = translations.iter().map(|translation| translation.text.clone()).collect();
+    Ok(Translation {
+        text: chunks::join(chunks, &texts),
+        model: translations
+            .first()
+            .map(|translation| translation.model.clone())
+            .unwrap_or_else(|| "none".to_string()),
+        alternatives: Vec::new(),
+    })
+}
+
+async fn request_translation(
+    text: &str,
+    source_lang: Option<&str>,
+    target_lang: &str,
+    formality: Option<Formality>,
+    alternatives: usize,
+    context: Option<&str>,
+    config: &TranslationConfig,
+) -> Result<Translation, String> {
     let request = WorkerRequest {
         text: text.to_string(),
         target_lang: target_lang.to_string(),
+        source_lang: source_lang.map(str::to_string),
+        formality,
+        alternatives: Some(alternatives).filter(|&count| count > 0),
+        context: context.map(str::to_string),
     };
 
+    let worker_response: WorkerResponse = post_worker(&config.worker_url, &request).await?;
+
+    let translation = worker_response.translation.trim().to_string();
+
+    if translation.is_empty() {
+        return Err("Empty translation response".to_string());
+    }
+
+    // Drop empty alternatives and ones that only repeat the main translation
+    let mut alternatives: Vec<Alternative> = Vec::new();
+    for mut alternative in worker_response.alternatives {
+        alternative.text = alternative.text.trim().to_string();
+        if !alternative.text.is_empty()
+            && alternative.text != translation
+            && !alternatives.iter().any(|other| other.text == alternative.text)
+        {
+            alternatives.push(alternative);
+        }
+    }
+    alternatives.truncate(request.alternatives.unwrap_or(0));
+
+    Ok(Translation {
+        text: translation,
+        model: worker_response.model,
+        alternatives,
+    })
+}
+
+/// POST a JSON request to the Worker and parse its JSON response
+async fn post_worker<T: Serialize, R: DeserializeOwned>(url: &str, request: &T) -> Result<R, String> {
+    post_worker_with_status(url, request).await.map_err(|(_, e)| e)
+}
+
+/// `post_worker`, with the HTTP status of error responses alongside the message
+async fn post_worker_with_status<T: Serialize, R: DeserializeOwned>(
+    url: &str,
+    request: &T,
+) -> Result<R, (Option<reqwest::StatusCode>, String)> {
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(30))
+        .build()
+        .map_err(|e| (None, format!("HTTP client error: {}", e)))?;
+
     let response = client
-        .post(&config.worker_url)
-        .json(&request)
+        .post(url)
+        .json(request)
         .send()
         .await
         .map_err(|e| {
-            if e.is_connect() {
+            let message = if e.is_connect() {
                 "Cannot reach translation service. Check your internet connection.".to_string()
             } else if e.is_timeout() {
                 "Translation request timed out. Try again.".to_string()
             } else {
                 format!("Translation request failed: {}", e)
-            }
+            };
+            (None, message)
         })?;
 
     let status = response.status();
 
     // Handle rate limiting
     if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
-        return Err("Rate limit exceeded. Please wait a moment and try again.".to_string());
+        return Err((Some(status), "Rate limit exceeded. Please wait a moment and try again.".to_string()));
     }
 
     // Handle other errors
@@ -100,19 +274,230 @@ pub async fn translate(text: &str, target_lang: &str, config: &TranslationConfig
                 error: format!("HTTP error: {}", status),
                 code: None,
             });
-        return Err(format!("Translation error: {}", error_body.error));
+        return Err((Some(status), format!("Translation error: {}", error_body.error)));
     }
 
-    let worker_response: WorkerResponse = response
+    response
         .json()
         .await
-        .map_err(|e| format!("Failed to parse response: {}", e))?;
+        .map_err(|e| (None, format!("Failed to parse response: {}", e)))
+}
 
-    let translation = worker_response.translation.trim().to_string();
+/// URL of a Worker endpoint other than plain translation, e.g. `/review`
+fn worker_endpoint(config: &TranslationConfig, path: &str) -> String {
+    format!("{}/{}", config.worker_url.trim_end_matches('/'), path)
+}
 
-    if translation.is_empty() {
-        return Err("Empty translation response".to_string());
+/// Request for the Worker's `/review` endpoint
+#[derive(Serialize)]
+struct ReviewRequest {
+    text: String,
+    /// Language to write problems and explanations in
+    explain_lang: String,
+}
+
+/// Phrasing the Worker flagged in a draft
+#[derive(Deserialize, Clone, Debug)]
+pub struct ReviewNote {
+    /// Exact excerpt of the draft
+    pub text: String,
+    pub problem: String,
+    #[serde(default)]
+    pub suggestion: Option<String>,
+}
+
+/// Response from the Worker's `/review` endpoint
+#[derive(Deserialize, Clone, Debug)]
+pub struct DraftReview {
+    #[serde(default)]
+    pub notes: Vec<ReviewNote>,
+    /// The whole draft rewritten naturally at the same politeness level
+    #[serde(default)]
+    pub rewrite: Option<String>,
+    pub model: String,
+}
+
+/// Ask the Worker to review a Japanese draft for awkward or unnatural phrasing
+pub async fn review(text: &str, explain_lang: &str, config: &TranslationConfig) -> Result<DraftReview, String> {
+    let request = ReviewRequest {
+        text: text.to_string(),
+        explain_lang: explain_lang.to_string(),
+    };
+    post_worker(&worker_endpoint(config, "review"), &request).await
+}
+
+/// Request for the Worker's `/explain` endpoint
+#[derive(Serialize)]
+struct ExplainRequest<'a> {
+    text: String,
+    /// Language to write glosses, functions and translations in
+    explain_lang: String,
+    /// JSON Schema the response must follow
+    schema: &'a serde_json::Value,
+}
+
+/// Ask the Worker for a structured grammar breakdown of a Japanese sentence.
+/// The response is validated against `grammar::schema()`.
+pub async fn explain(text: &str, explain_lang: &str, config: &TranslationConfig) -> Result<GrammarExplanation, String> {
+    let request = ExplainRequest {
+        text: text.to_string(),
+        explain_lang: explain_lang.to_string(),
+        schema: grammar::schema(),
+    };
+    let response: serde_json::Value = post_worker(&worker_endpoint(config, "explain"), &request).await?;
+    grammar::parse_explanation(response)
+}
+
+/// Request for the Worker's `/batch` endpoint
+#[derive(Serialize)]
+struct BatchRequest<'a> {
+    texts: &'a [String],
+    target_lang: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    source_lang: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    formality: Option<Formality>,
+}
+
+/// Translations in the same order as the request's texts
+#[derive(Deserialize)]
+struct BatchResponse {
+    translations: Vec<String>,
+    model: String,
+}
+
+/// Translate the pieces of one document, a batch of neighbouring pieces per
+/// request so the model sees them together. Long documents take several
+/// batches, a few at a time; a piece too long for a batch is chunked on its
+/// own. Returns the translations, in order, and the model.
+pub async fn translate_batch(
+    texts: &[String],
+    source_lang: Option<&str>,
+    target_lang: &str,
+    formality: Option<Formality>,
+    config: &TranslationConfig,
+) -> Result<(Vec<String>, String), String> {
+    let batches = chunks::batches(texts, chunks::MAX_CHUNK_CHARS);
+    let requests: Vec<_> = batches
+        .iter()
+        .map(|batch| request_batch(&texts[batch.clone()], source_lang, target_lang, formality, config))
+        .collect();
+    let results: Vec<(Vec<String>, String)> = futures::stream::iter(requests)
+        .buffered(MAX_CONCURRENT_CHUNKS)
+        .try_collect()
+        .await?;
+
+    let model = results
+        .first()
+        .map(|(_, model)| model.clone())
+        .unwrap_or_else(|| "none".to_string());
+    Ok((results.into_iter().flat_map(|(translations, _)| translations).collect(), model))
+}
+
+/// Translate one batch with the Worker's `/batch` endpoint. Falls back to one
+/// request per piece if the Worker has no such endpoint.
+async fn request_batch(
+    texts: &[String],
+    source_lang: Option<&str>,
+    target_lang: &str,
+    formality: Option<Formality>,
+    config: &TranslationConfig,
+) -> Result<(Vec<String>, String), String> {
+    if let [text] = texts {
+        if chunks::needs_chunking(text) {
+            let translation = translate(text, source_lang, target_lang, formality, config).await?;
+            return Ok((vec![translation.text], translation.model));
+        }
+    }
+
+    let request = BatchRequest {
+        texts,
+        target_lang: target_lang.to_string(),
+        source_lang: source_lang.map(str::to_string),
+        formality,
+    };
+    match post_worker_with_status::<_, BatchResponse>(&worker_endpoint(config, "batch"), &request).await {
+        Ok(response) if response.translations.len() == texts.len() => {
+            let translations = response
+                .translations
+                .iter()
+                .map(|translation| translation.trim().to_string())
+                .collect();
+            return Ok((translations, response.model));
+        }
+        Ok(response) => {
+            return Err(format!(
+                "Batch translation returned {} texts for {}",
+                response.translations.len(),
+                texts.len()
+            ))
+        }
+        // Only a Worker without the endpoint is worth one request per text;
+        // other failures (timeouts, rate limits) would just repeat
+        Err((Some(reqwest::StatusCode::NOT_FOUND), _)) => {
+            eprintln!("⚠ The translation service has no batch endpoint; translating one by one");
+        }
+        Err((_, e)) => return Err(e),
     }
 
-    Ok(translation)
+    let requests: Vec<_> = texts
+        .iter()
+        .map(|text| translate(text, source_lang, target_lang, formality, config))
+        .collect();
+    let translations: Vec<Translation> = futures::stream::iter(requests)
+        .buffered(MAX_CONCURRENT_CHUNKS)
+        .try_collect()
+        .await?;
+    let model = translations
+        .first()
+        .map(|translation| translation.model.clone())
+        .unwrap_or_else(|| "none".to_string());
+    Ok((translations.into_iter().map(|translation| translation.text).collect(), model))
+}
+
+/// Translate only the segments that are not already in the target language,
+/// reassembling them in order with everything else kept verbatim
+pub async fn translate_segments(
+    segments: &[Segment],
+    target_lang: &str,
+    formality: Option<Formality>,
+    config: &TranslationConfig,
+) -> Result<Translation, String> {
+    let requests: Vec<_> = segments
+        .iter()
+        .map(|segment| async move {
+            match segment {
+                Segment::Text { text, lang } if !same_language(lang, target_lang) => {
+                    translate(text, Some(lang), target_lang, formality, config).await.map(Some)
+                }
+                _ => Ok(None),
+            }
+        })
+        .collect();
+
+    let translations: Vec<Option<Translation>> = futures::stream::iter(requests)
+        .buffered(MAX_CONCURRENT_CHUNKS)
+        .try_collect()
+        .await?;
+
+    let text = segments
+        .iter()
+        .zip(&translations)
+        .map(|(segment, translation)| match translation {
+            Some(translation) => translation.text.as_str(),
+            None => segment.as_str(),
+        })
+        .collect();
+    let model = translations
+        .iter()
+        .flatten()
+        .map(|translation| translation.model.clone())
+        .next()
+        .unwrap_or_else(|| "none".to_string());
+
+    Ok(Translation {
+        text,
+        model,
+        alternatives: Vec::new(),
+    })
 }
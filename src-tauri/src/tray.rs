@@ -4,6 +4,7 @@ use tauri::{
     AppHandle, Manager,
 };
 
+use crate::actions::{self, Action};
 use crate::commands::AppState;
 
 /// Creates and initializes the system tray icon with menu
@@ -13,8 +14,16 @@ pub fn create_tray(app: &AppHandle) -> Result<(), String> {
 
     // Register menu event handler on the app
     app.on_menu_event(move |app, event| match event.id().as_ref() {
-        "show_hide" => handle_show_hide(app),
-        "translate" => handle_translate(app),
+        "show_hide" => actions::dispatch(app, Action::ToggleOverlay),
+        "translate" => actions::dispatch(app, Action::Translate),
+        "translate_replace" => actions::dispatch(app, Action::TranslateAndReplace),
+        "explain" => actions::dispatch(app, Action::ExplainGrammar),
+        "review" => actions::dispatch(app, Action::ReviewDraft),
+        "show_last" => actions::dispatch(app, Action::ShowLastResult),
+        "history" => actions::dispatch(app, Action::OpenHistory),
+        "glossary" => actions::dispatch(app, Action::OpenGlossary),
+        "vocabulary" => actions::dispatch(app, Action::OpenVocabulary),
+        "practice" => actions::dispatch(app, Action::PracticeWords),
         "settings" => handle_settings(app),
         "quit" => handle_quit(app),
         _ => {}
@@ -22,7 +31,7 @@ pub fn create_tray(app: &AppHandle) -> Result<(), String> {
 
     // Create the tray icon
     let _tray = TrayIconBuilder::with_id("main-tray")
-        .tooltip("Japanese-English Translator (Cmd+J)")
+        .tooltip("Japanese-English Translator")
         .icon(app.default_window_icon().unwrap().clone())
         .menu(&menu)
         .show_menu_on_left_click(false) // Don't show menu on left click
@@ -37,7 +46,7 @@ pub fn create_tray(app: &AppHandle) -> Result<(), String> {
                 } = event
                 {
                     println!("[DEBUG] Tray icon left-clicked - toggling window");
-                    handle_show_hide(&app_handle);
+                    actions::dispatch(&app_handle, Action::ToggleOverlay);
                 }
                 // Right click: Menu shows automatically (handled by Tauri)
             }
@@ -56,10 +65,45 @@ fn build_tray_menu(app: &AppHandle) -> Result<Menu<tauri::Wry>, String> {
         .build(app)
         .map_err(|e| format!("Failed to create show/hide item: {}", e))?;
 
-    let translate = MenuItemBuilder::with_id("translate", "Translate Selection (Cmd+J)")
+    let translate = MenuItemBuilder::with_id("translate", menu_label(app, "Translate Selection", &Action::Translate))
         .build(app)
         .map_err(|e| format!("Failed to create translate item: {}", e))?;
 
+    let translate_replace = MenuItemBuilder::with_id(
+        "translate_replace",
+        menu_label(app, "Translate and Replace", &Action::TranslateAndReplace),
+    )
+    .build(app)
+    .map_err(|e| format!("Failed to create translate and replace item: {}", e))?;
+
+    let explain = MenuItemBuilder::with_id("explain", menu_label(app, "Explain Grammar", &Action::ExplainGrammar))
+        .build(app)
+        .map_err(|e| format!("Failed to create explain item: {}", e))?;
+
+    let review = MenuItemBuilder::with_id("review", menu_label(app, "Review Japanese Draft", &Action::ReviewDraft))
+        .build(app)
+        .map_err(|e| format!("Failed to create review item: {}", e))?;
+
+    let show_last = MenuItemBuilder::with_id("show_last", menu_label(app, "Show Last Translation", &Action::ShowLastResult))
+        .build(app)
+        .map_err(|e| format!("Failed to create show last item: {}", e))?;
+
+    let history = MenuItemBuilder::with_id("history", menu_label(app, "History...", &Action::OpenHistory))
+        .build(app)
+        .map_err(|e| format!("Failed to create history item: {}", e))?;
+
+    let glossary = MenuItemBuilder::with_id("glossary", menu_label(app, "Glossary...", &Action::OpenGlossary))
+        .build(app)
+        .map_err(|e| format!("Failed to create glossary item: {}", e))?;
+
+    let vocabulary = MenuItemBuilder::with_id("vocabulary", menu_label(app, "Word List...", &Action::OpenVocabulary))
+        .build(app)
+        .map_err(|e| format!("Failed to create word list item: {}", e))?;
+
+    let practice = MenuItemBuilder::with_id("practice", menu_label(app, "Practice Words...", &Action::PracticeWords))
+        .build(app)
+        .map_err(|e| format!("Failed to create practice item: {}", e))?;
+
     let settings = MenuItemBuilder::with_id("settings", "Settings...")
         .build(app)
         .map_err(|e| format!("Failed to create settings item: {}", e))?;
@@ -72,7 +116,15 @@ fn build_tray_menu(app: &AppHandle) -> Result<Menu<tauri::Wry>, String> {
     let menu = MenuBuilder::new(app)
         .item(&show_hide)
         .item(&translate)
+        .item(&translate_replace)
+        .item(&explain)
+        .item(&review)
+        .item(&show_last)
         .separator()
+        .item(&history)
+        .item(&glossary)
+        .item(&vocabulary)
+        .item(&practice)
         .item(&settings)
         .separator()
         .item(&quit)
@@ -82,57 +134,15 @@ fn build_tray_menu(app: &AppHandle) -> Result<Menu<tauri::Wry>, String> {
     Ok(menu)
 }
 
-/// Toggles main window visibility
-fn handle_show_hide(app: &AppHandle) {
-    let result: Result<(), String> = (|| {
-        let window = app
-            .get_webview_window("main")
-            .ok_or("Main window not found")?;
-
-        let is_visible = window
-            .is_visible()
-            .map_err(|e| format!("Failed to check visibility: {}", e))?;
-
-        if is_visible {
-            println!("[DEBUG] Hiding main window");
-            window
-                .hide()
-                .map_err(|e| format!("Failed to hide window: {}", e))?;
-        } else {
-            println!("[DEBUG] Showing main window");
-            window
-                .show()
-                .map_err(|e| format!("Failed to show window: {}", e))?;
-            window
-                .set_focus()
-                .map_err(|e| format!("Failed to focus window: {}", e))?;
-        }
-
-        Ok(())
-    })();
-
-    if let Err(e) = result {
-        eprintln!("❌ Show/Hide error: {}", e);
+/// Appends the configured hotkey to a menu label, e.g. "Translate Selection (CmdOrCtrl+J)"
+fn menu_label(app: &AppHandle, label: &str, action: &Action) -> String {
+    let state = app.state::<AppState>();
+    match state.hotkeys.shortcut_for(action) {
+        Some(shortcut) => format!("{} ({})", label, shortcut),
+        None => label.to_string(),
     }
 }
 
-/// Triggers the translation workflow (same as Cmd+J)
-fn handle_translate(app: &AppHandle) {
-    let app_clone = app.clone();
-    tauri::async_runtime::spawn(async move {
-        println!("[DEBUG] Tray menu: Triggering translation");
-
-        // Access state and call translate_selection command
-        let app_clone2 = app_clone.clone();
-        let state_guard = app_clone.state::<AppState>();
-        let result = crate::commands::translate_selection(app_clone2, state_guard).await;
-
-        if let Err(e) = result {
-            eprintln!("❌ Translation error from tray: {}", e);
-        }
-    });
-}
-
 /// Shows settings dialog (placeholder for future implementation)
 fn handle_settings(_app: &AppHandle) {
     println!("[DEBUG] Tray menu: Settings clicked");
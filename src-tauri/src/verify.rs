@@ -0,0 +1,250 @@
+use regex::Regex;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use crate::language::{is_cjk, primary_subtag};
+
+/// Back-translations less similar to the original than this are flagged
+pub const SIMILARITY_THRESHOLD: f32 = 0.35;
+
+/// Result of translating a translation back into the source language
+#[derive(Serialize, Clone, Debug)]
+pub struct BackTranslationCheck {
+    pub back_translation: String,
+    /// Similarity of the back-translation to the original, from 0.0 to 1.0
+    pub similarity: f32,
+    /// Specific problems found, e.g. a dropped negation or name
+    pub issues: Vec<String>,
+    /// The translation may be wrong; the overlay asks the user to double-check
+    pub suspicious: bool,
+}
+
+/// Compare the original with its back-translation. Numbers, dates and amounts are
+/// compared with the translation itself by `quantities::check`.
+pub fn compare(original: &str, back_translation: &str, source_lang: &str) -> BackTranslationCheck {
+    let similarity = dice(&tokens(original), &tokens(back_translation));
+    let mut issues = Vec::new();
+
+    let negated = has_negation(original, source_lang);
+    if negated != has_negation(back_translation, source_lang) {
+        issues.push(if negated {
+            "The original is negative but the back-translation is not; a negation may have been dropped".to_string()
+        } else {
+            "The back-translation is negative but the original is not; a negation may have been added".to_string()
+        });
+    }
+
+    let back_lower = back_translation.to_lowercase();
+    for name in names(original) {
+        if !back_lower.contains(&name.to_lowercase()) {
+            issues.push(format!("\"{}\" did not survive the back-translation", name));
+        }
+    }
+
+    BackTranslationCheck {
+        back_translation: back_translation.to_string(),
+        similarity,
+        suspicious: similarity < SIMILARITY_THRESHOLD || !issues.is_empty(),
+        issues,
+    }
+}
+
+/// Character bigrams for CJK and Hangul text (no spaces between words),
+/// lower-cased words otherwise
+fn tokens(text: &str) -> Vec<String> {
+    let is_dense = |c: char| is_cjk(c) || ('\u{AC00}'..='\u{D7AF}').contains(&c);
+    if text.chars().any(is_dense) {
+        let chars: Vec<char> = text.chars().filter(|c| c.is_alphanumeric()).collect();
+        chars.windows(2).map(|pair| pair.iter().collect()).collect()
+    } else {
+        text.split(|c: char| !c.is_alphanumeric() && c != '\'')
+            .filter(|word| !word.is_empty())
+            .map(str::to_lowercase)
+            .collect()
+    }
+}
+
+/// Sørensen–Dice coefficient over token multisets
+fn dice(a: &[String], b: &[String]) -> f32 {
+    if a.is_empty() && b.is_empty() {
+        return 1.0;
+    }
+
+    let mut remaining: HashMap<&str, usize> = HashMap::new();
+    for token in b {
+        *remaining.entry(token.as_str()).or_default() += 1;
+    }
+    let mut shared = 0;
+    for token in a {
+        if let Some(count) = remaining.get_mut(token.as_str()).filter(|count| **count > 0) {
+            *count -= 1;
+            shared += 1;
+        }
+    }
+
+    2.0 * shared as f32 / (a.len() + b.len()) as f32
+}
+
+/// Whether the text contains a negation in the given language
+fn has_negation(text: &str, lang: &str) -> bool {
+    static ENGLISH: OnceLock<Regex> = OnceLock::new();
+    // Words that contain a marker without negating anything (少ない "few",
+    // 未来 "future", 주말 "weekend") are blanked out before matching
+    let (markers, false_friends): (&[&str], &[&str]) = match primary_subtag(lang) {
+        "ja" => (
+            &["ない", "ません", "なかった", "なく", "ず、", "ずに", "無い"],
+            &["少な", "危な", "汚な", "幼な", "切な", "儚な", "間もな", "まもな"],
+        ),
+        "zh" => (
+            &["不", "没", "沒", "别", "別", "无", "無", "未"],
+            &[
+                "未来", "未來", "无论", "無論", "不过", "不過", "不仅", "不僅", "不管", "特别", "特別", "区别", "區別",
+                "分别", "分別", "别人", "別人", "告别", "告別", "沉没", "沉沒", "淹没", "淹沒",
+            ],
+        ),
+        "ko" => (&["않", "안 ", "못", "없", "지 말", "지 마"], &["잘못"]),
+        "en" => {
+            let pattern = ENGLISH.get_or_init(|| {
+                Regex::new(r"(?i)\b(?:not|no|never|none|nothing|nobody|neither|nor|without|cannot)\b|n't\b")
+                    .expect("valid negation pattern")
+            });
+            return pattern.is_match(text);
+        }
+        _ => return false,
+    };
+
+    let mut text = text.to_string();
+    for word in false_friends {
+        text = text.replace(word, " ");
+    }
+    markers.iter().any(|marker| text.contains(marker))
+}
+
+/// Likely names and identifiers in Latin script: capitalized words that do not
+/// start a sentence, acronyms and words mixing letters and digits. Japanese and
+/// Chinese have no spaces, so CJK characters separate words too.
+fn names(text: &str) -> Vec<String> {
+    let mut found = Vec::new();
+    let mut sentence_start = true;
+    let mut word = String::new();
+
+    for c in text.chars().chain([' ']) {
+        let is_dense = is_cjk(c)
+            || ('\u{AC00}'..='\u{D7AF}').contains(&c)
+            || ('\u{3000}'..='\u{303F}').contains(&c)
+            || ('\u{FF00}'..='\u{FFEF}').contains(&c);
+        if !c.is_whitespace() && !is_dense {
+            word.push(c);
+            continue;
+        }
+
+        if !word.is_empty() {
+            let trimmed = word.trim_matches(|c: char| !c.is_alphanumeric());
+            let is_latin = !trimmed.is_empty() && trimmed.chars().all(|c| c.is_ascii_alphanumeric());
+            if is_latin {
+                let capitalized = trimmed.chars().next().is_some_and(|c| c.is_ascii_uppercase());
+                let acronym =
+                    trimmed.len() > 1 && trimmed.chars().all(|c| c.is_ascii_uppercase() || c.is_ascii_digit());
+                let mixed =
+                    trimmed.chars().any(|c| c.is_ascii_digit()) && trimmed.chars().any(|c| c.is_ascii_alphabetic());
+                let is_name = (capitalized && !sentence_start) || acronym || mixed;
+                if is_name && !found.iter().any(|name: &String| name == trimmed) {
+                    found.push(trimmed.to_string());
+                }
+            }
+            sentence_start = word.ends_with(['.', '!', '?', ':']);
+            word.clear();
+        }
+        if is_dense {
+            // Latin words inside CJK text keep their own capitalization
+            sentence_start = matches!(c, '。' | '！' | '？' | '：');
+        }
+    }
+
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(text: &str) -> Vec<String> {
+        text.split(' ').map(str::to_string).collect()
+    }
+
+    #[test]
+    fn dice_counts_repeated_tokens_once_per_match() {
+        assert_eq!(dice(&[], &[]), 1.0);
+        assert_eq!(dice(&words("a b"), &words("a b")), 1.0);
+        assert_eq!(dice(&words("a a a b"), &words("a b b")), 2.0 * 2.0 / 7.0);
+        assert_eq!(dice(&words("a"), &words("b")), 0.0);
+    }
+
+    #[test]
+    fn negations_are_found() {
+        let cases = [
+            ("ja", "明日は行かない"),
+            ("ja", "問題ありません"),
+            ("ja", "連絡せずに帰った"),
+            ("ja", "時間がなかった"),
+            ("zh", "我明天不去"),
+            ("zh", "他还没有回复"),
+            ("zh", "尚未确认"),
+            ("ko", "내일은 가지 않아요"),
+            ("ko", "시간이 없어요"),
+            ("ko", "걱정하지 마세요"),
+            ("en", "We don't ship on Sundays"),
+            ("en", "This is not final"),
+        ];
+        for (lang, text) in cases {
+            assert!(has_negation(text, lang), "{} should be negative", text);
+        }
+    }
+
+    #[test]
+    fn words_containing_markers_are_not_negations() {
+        let cases = [
+            ("ja", "参加者は少ないです"),
+            ("ja", "その道は危ないよ"),
+            ("ja", "間もなく到着します"),
+            ("ja", "死ぬほど嬉しい"),
+            ("zh", "未来会更好"),
+            ("zh", "这个特别好"),
+            ("zh", "不过我同意"),
+            ("ko", "주말에 만나요"),
+            ("ko", "말씀하신 대로 할게요"),
+            ("ko", "안에 들어오세요"),
+            ("en", "Notice the nothingness-free banner"),
+            ("fr", "Je ne sais pas"),
+        ];
+        for (lang, text) in cases {
+            assert!(!has_negation(text, lang), "{} should not be negative", text);
+        }
+    }
+
+    #[test]
+    fn names_in_spaced_text() {
+        assert_eq!(names("Meeting with John at ACME. Thanks, Mary"), ["John", "ACME", "Mary"]);
+        assert_eq!(names("Please call. Thanks for v2 of the doc"), ["v2"]);
+    }
+
+    #[test]
+    fn names_in_unspaced_text() {
+        assert_eq!(names("明日Appleの田中さんとiPhone15の件で会議します"), ["Apple", "iPhone15"]);
+        assert_eq!(names("请把PDF发给Tom。谢谢"), ["PDF", "Tom"]);
+    }
+
+    #[test]
+    fn dropped_names_and_negations_are_issues() {
+        let check = compare("明日Appleの会議には行かない", "明日の会議には行きます", "ja");
+        assert!(check.suspicious);
+        assert_eq!(check.issues.len(), 2);
+        assert!(check.issues[1].contains("Apple"));
+
+        let check = compare("明日Appleの会議には行かない", "明日Appleの会議には行かない", "ja");
+        assert!(!check.suspicious);
+        assert!(check.issues.is_empty());
+        assert_eq!(check.similarity, 1.0);
+    }
+}
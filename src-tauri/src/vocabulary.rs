@@ -0,0 +1,531 @@
+use rusqlite::{params, Connection, Row};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::Mutex;
+
+use crate::dictionary::DictionaryStore;
+use crate::furigana::{self, ContentWord};
+use crate::history::now_millis;
+use crate::review;
+use crate::srs::{self, Schedule};
+
+/// Schema migrations, applied in order and tracked with `PRAGMA user_version`
+const MIGRATIONS: &[&str] = &[
+    // 1: personal word list, one row per word and reading
+    "CREATE TABLE words (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        word TEXT NOT NULL,
+        reading TEXT NOT NULL DEFAULT '',
+        gloss TEXT,
+        pos TEXT NOT NULL,
+        surface TEXT NOT NULL,
+        sentence TEXT NOT NULL,
+        translation TEXT NOT NULL,
+        added_at INTEGER NOT NULL,
+        UNIQUE (word, reading)
+    );",
+    // 2: spaced-repetition scheduling; new words are due straight away
+    "ALTER TABLE words ADD COLUMN ease REAL NOT NULL DEFAULT 2.5;
+    ALTER TABLE words ADD COLUMN interval_days INTEGER NOT NULL DEFAULT 0;
+    ALTER TABLE words ADD COLUMN repetitions INTEGER NOT NULL DEFAULT 0;
+    ALTER TABLE words ADD COLUMN lapses INTEGER NOT NULL DEFAULT 0;
+    ALTER TABLE words ADD COLUMN due_at INTEGER NOT NULL DEFAULT 0;
+    ALTER TABLE words ADD COLUMN reviewed_at INTEGER;
+    CREATE INDEX words_due_at ON words(due_at);",
+];
+
+const WORD_COLUMNS: &str = "id, word, reading, gloss, pos, surface, sentence, translation, added_at";
+
+const CARD_COLUMNS: &str = "id, word, reading, gloss, pos, surface, sentence, translation, added_at,
+     ease, interval_days, repetitions, lapses, due_at, reviewed_at";
+
+/// A word in the personal word list
+#[derive(Serialize, Clone, Debug)]
+pub struct VocabularyItem {
+    pub id: i64,
+    /// Dictionary form, e.g. 食べる
+    pub word: String,
+    /// Hiragana reading of the dictionary form; empty if unknown
+    pub reading: String,
+    /// English meaning from JMdict, if available
+    pub gloss: Option<String>,
+    /// Part of speech, e.g. 動詞
+    pub pos: String,
+    /// As it appeared in the sentence, e.g. 食べ
+    pub surface: String,
+    /// The sentence the word was met in
+    pub sentence: String,
+    /// Translation of the text the sentence came from
+    pub translation: String,
+    /// Unix timestamp in milliseconds
+    pub added_at: i64,
+}
+
+impl VocabularyItem {
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(Self {
+            id: row.get(0)?,
+            word: row.get(1)?,
+            reading: row.get(2)?,
+            gloss: row.get(3)?,
+            pos: row.get(4)?,
+            surface: row.get(5)?,
+            sentence: row.get(6)?,
+            translation: row.get(7)?,
+            added_at: row.get(8)?,
+        })
+    }
+}
+
+/// A word to practise, with its review schedule
+#[derive(Serialize, Clone, Debug)]
+pub struct Card {
+    #[serde(flatten)]
+    pub item: VocabularyItem,
+    #[serde(flatten)]
+    pub schedule: Schedule,
+    /// Unix milliseconds of the last review; `None` for new words
+    pub reviewed_at: Option<i64>,
+}
+
+impl Card {
+    fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(Self {
+            item: VocabularyItem::from_row(row)?,
+            schedule: Schedule {
+                ease: row.get(9)?,
+                interval_days: row.get(10)?,
+                repetitions: row.get(11)?,
+                lapses: row.get(12)?,
+                due_at: row.get(13)?,
+            },
+            reviewed_at: row.get(14)?,
+        })
+    }
+}
+
+/// How much practice is waiting
+#[derive(Serialize, Clone, Debug)]
+pub struct DueSummary {
+    pub due: i64,
+    /// Due words that have never been reviewed
+    pub new: i64,
+    pub total: i64,
+}
+
+/// A word found in a text, before it is saved
+#[derive(Clone, Debug)]
+pub struct NewWord {
+    pub word: String,
+    pub reading: String,
+    pub gloss: Option<String>,
+    pub pos: String,
+    pub surface: String,
+    pub sentence: String,
+}
+
+/// Deck formats the word list can be exported to
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum DeckFormat {
+    /// Anki package, imported with File → Import
+    Apkg,
+    /// Tab-separated text with Anki import headers
+    Tsv,
+}
+
+impl DeckFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            DeckFormat::Apkg => "apkg",
+            DeckFormat::Tsv => "txt",
+        }
+    }
+}
+
+/// Local SQLite store of the personal word list
+pub struct VocabularyStore {
+    conn: Mutex<Connection>,
+}
+
+impl VocabularyStore {
+    /// Open (or create) the word list database at `path` and migrate it
+    pub fn open(path: &Path) -> Result<Self, String> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create vocabulary directory: {}", e))?;
+        }
+
+        let conn = Connection::open(path).map_err(|e| format!("Failed to open vocabulary database: {}", e))?;
+        let version: usize = conn
+            .query_row("PRAGMA user_version", [], |row| row.get(0))
+            .map_err(|e| format!("Failed to read vocabulary schema version: {}", e))?;
+        for (i, migration) in MIGRATIONS.iter().enumerate().skip(version) {
+            conn.execute_batch(&format!(
+                "BEGIN; {} PRAGMA user_version = {}; COMMIT;",
+                migration,
+                i + 1
+            ))
+            .map_err(|e| format!("Failed to migrate vocabulary database to v{}: {}", i + 1, e))?;
+        }
+
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    fn conn(&self) -> Result<std::sync::MutexGuard<'_, Connection>, String> {
+        self.conn
+            .lock()
+            .map_err(|e| format!("Failed to lock vocabulary database: {}", e))
+    }
+
+    /// Add words that are not in the list yet, returning the ones added.
+    /// Words already in the list keep the sentence they were first met in.
+    pub fn add(&self, words: &[NewWord], translation: &str) -> Result<Vec<VocabularyItem>, String> {
+        let mut conn = self.conn()?;
+        let tx = conn
+            .transaction()
+            .map_err(|e| format!("Failed to save vocabulary: {}", e))?;
+
+        let mut added = Vec::new();
+        {
+            let mut insert = tx
+                .prepare(&format!(
+                    "INSERT INTO words (word, reading, gloss, pos, surface, sentence, translation, added_at)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+                     ON CONFLICT (word, reading) DO NOTHING
+                     RETURNING {}",
+                    WORD_COLUMNS
+                ))
+                .map_err(|e| format!("Failed to save vocabulary: {}", e))?;
+            let added_at = now_millis();
+            for word in words {
+                let mut rows = insert
+                    .query_map(
+                        params![word.word, word.reading, word.gloss, word.pos, word.surface, word.sentence, translation, added_at],
+                        VocabularyItem::from_row,
+                    )
+                    .map_err(|e| format!("Failed to save vocabulary: {}", e))?;
+                if let Some(item) = rows.next() {
+                    added.push(item.map_err(|e| format!("Failed to save vocabulary: {}", e))?);
+                }
+            }
+        }
+
+        tx.commit().map_err(|e| format!("Failed to save vocabulary: {}", e))?;
+        Ok(added)
+    }
+
+    /// The whole word list, most recently added first
+    pub fn list(&self) -> Result<Vec<VocabularyItem>, String> {
+        let conn = self.conn()?;
+        let mut stmt = conn
+            .prepare(&format!("SELECT {} FROM words ORDER BY added_at DESC, id DESC", WORD_COLUMNS))
+            .map_err(|e| format!("Failed to query vocabulary: {}", e))?;
+        let items = stmt
+            .query_map([], VocabularyItem::from_row)
+            .and_then(|rows| rows.collect::<rusqlite::Result<Vec<_>>>())
+            .map_err(|e| format!("Failed to read vocabulary: {}", e))?;
+        Ok(items)
+    }
+
+    /// Words due for review by `now`, most overdue first, with new words after
+    /// words already being learned
+    pub fn due_cards(&self, now: i64, limit: usize) -> Result<Vec<Card>, String> {
+        let conn = self.conn()?;
+        let mut stmt = conn
+            .prepare(&format!(
+                "SELECT {} FROM words WHERE due_at <= ?1
+                 ORDER BY reviewed_at IS NULL, due_at, id LIMIT ?2",
+                CARD_COLUMNS
+            ))
+            .map_err(|e| format!("Failed to query due words: {}", e))?;
+        let cards = stmt
+            .query_map(params![now, limit as i64], Card::from_row)
+            .and_then(|rows| rows.collect::<rusqlite::Result<Vec<_>>>())
+            .map_err(|e| format!("Failed to read due words: {}", e))?;
+        Ok(cards)
+    }
+
+    pub fn due_summary(&self, now: i64) -> Result<DueSummary, String> {
+        let conn = self.conn()?;
+        conn.query_row(
+            "SELECT COUNT(*) FILTER (WHERE due_at <= ?1),
+                    COUNT(*) FILTER (WHERE due_at <= ?1 AND reviewed_at IS NULL),
+                    COUNT(*)
+             FROM words",
+            [now],
+            |row| {
+                Ok(DueSummary {
+                    due: row.get(0)?,
+                    new: row.get(1)?,
+                    total: row.get(2)?,
+                })
+            },
+        )
+        .map_err(|e| format!("Failed to count due words: {}", e))
+    }
+
+    /// Grade a review of a word from 0 to 5 and schedule its next review
+    pub fn grade(&self, id: i64, grade: u8, now: i64) -> Result<Card, String> {
+        let conn = self.conn()?;
+        let card = conn
+            .query_row(
+                &format!("SELECT {} FROM words WHERE id = ?1", CARD_COLUMNS),
+                [id],
+                Card::from_row,
+            )
+            .map_err(|e| match e {
+                rusqlite::Error::QueryReturnedNoRows => format!("Word {} not found", id),
+                e => format!("Failed to read word: {}", e),
+            })?;
+
+        let schedule = srs::grade(card.schedule, grade, now)?;
+        conn.execute(
+            "UPDATE words SET ease = ?2, interval_days = ?3, repetitions = ?4, lapses = ?5, due_at = ?6, reviewed_at = ?7
+             WHERE id = ?1",
+            params![
+                id,
+                schedule.ease,
+                schedule.interval_days,
+                schedule.repetitions,
+                schedule.lapses,
+                schedule.due_at,
+                now
+            ],
+        )
+        .map_err(|e| format!("Failed to save review: {}", e))?;
+
+        Ok(Card {
+            schedule,
+            reviewed_at: Some(now),
+            ..card
+        })
+    }
+
+    /// Delete a word, returning whether it existed
+    pub fn delete(&self, id: i64) -> Result<bool, String> {
+        let conn = self.conn()?;
+        let deleted = conn
+            .execute("DELETE FROM words WHERE id = ?1", [id])
+            .map_err(|e| format!("Failed to delete word: {}", e))?;
+        Ok(deleted > 0)
+    }
+}
+
+/// Notable words in Japanese text: content words written with kanji, or
+/// katakana loanwords, deduplicated by dictionary form. Each comes with the
+/// sentence it appears in and, if JMdict is available, an English gloss.
+pub fn extract(japanese: &str, dictionary: Option<&DictionaryStore>) -> Result<Vec<NewWord>, String> {
+    let sentences = review::sentences(japanese);
+    let mut words: Vec<NewWord> = Vec::new();
+
+    for word in furigana::content_words(japanese)? {
+        if !is_notable(&word) || words.iter().any(|other| other.word == word.base_form) {
+            continue;
+        }
+
+        let sentence = sentences
+            .iter()
+            .find(|range| range.contains(&word.offset))
+            .map_or(japanese, |range| &japanese[range.clone()])
+            .trim()
+            .to_string();
+        let gloss = dictionary
+            .and_then(|dictionary| dictionary.lookup(&word.base_form, 1).inspect_err(|e| eprintln!("⚠ {}", e)).ok())
+            .and_then(|entries| entries.first().map(|entry| entry.summary()))
+            .filter(|gloss| !gloss.is_empty());
+
+        words.push(NewWord {
+            word: word.base_form,
+            reading: word.reading.unwrap_or_default(),
+            gloss,
+            pos: word.pos,
+            surface: word.surface,
+            sentence,
+        });
+    }
+
+    Ok(words)
+}
+
+/// Worth studying: has a kanji, or is a katakana word of two or more characters
+fn is_notable(word: &ContentWord) -> bool {
+    let is_katakana = |c: char| matches!(c as u32, 0x30A1..=0x30FA | 0x30FC);
+    word.base_form.chars().any(furigana::is_kanji)
+        || (word.base_form.chars().count() >= 2 && word.base_form.chars().all(is_katakana))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn store(name: &str) -> (VocabularyStore, PathBuf) {
+        let path = std::env::temp_dir().join(format!("vocabulary-{}-{}.sqlite3", name, std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        (VocabularyStore::open(&path).unwrap(), path)
+    }
+
+    fn new_word(word: &str, reading: &str, sentence: &str) -> NewWord {
+        NewWord {
+            word: word.to_string(),
+            reading: reading.to_string(),
+            gloss: None,
+            pos: "名詞".to_string(),
+            surface: word.to_string(),
+            sentence: sentence.to_string(),
+        }
+    }
+
+    fn content_word(base_form: &str) -> ContentWord {
+        ContentWord {
+            surface: base_form.to_string(),
+            base_form: base_form.to_string(),
+            reading: None,
+            pos: "名詞".to_string(),
+            offset: 0,
+        }
+    }
+
+    #[test]
+    fn adds_only_new_words_and_keeps_the_first_sentence() {
+        let (store, path) = store("add");
+        let added = store
+            .add(
+                &[
+                    new_word("会議", "かいぎ", "会議は三時です。"),
+                    new_word("資料", "しりょう", "資料を送ります。"),
+                    new_word("会議", "かいぎ", "会議室はどこですか。"),
+                ],
+                "The meeting is at three.",
+            )
+            .unwrap();
+        let words: Vec<&str> = added.iter().map(|item| item.word.as_str()).collect();
+        assert_eq!(words, vec!["会議", "資料"]);
+        assert_eq!(added[0].translation, "The meeting is at three.");
+
+        let added = store
+            .add(
+                &[
+                    new_word("会議", "かいぎ", "明日も会議です。"),
+                    new_word("会議", "", "明日も会議です。"),
+                    new_word("出張", "しゅっちょう", "来週は出張です。"),
+                ],
+                "There is a meeting tomorrow too.",
+            )
+            .unwrap();
+        assert_eq!(added.len(), 2);
+        assert_eq!(added[0].reading, "");
+
+        let meeting = store
+            .list()
+            .unwrap()
+            .into_iter()
+            .find(|item| item.word == "会議" && item.reading == "かいぎ")
+            .unwrap();
+        assert_eq!(meeting.sentence, "会議は三時です。");
+        assert_eq!(meeting.translation, "The meeting is at three.");
+
+        drop(store);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn lists_most_recent_first() {
+        let (store, path) = store("list");
+        store.add(&[new_word("会議", "かいぎ", "会議です。")], "").unwrap();
+        store.add(&[new_word("資料", "しりょう", "資料です。"), new_word("出張", "しゅっちょう", "出張です。")], "").unwrap();
+
+        let words: Vec<String> = store.list().unwrap().into_iter().map(|item| item.word).collect();
+        assert_eq!(words, vec!["出張", "資料", "会議"]);
+        assert!(store.delete(store.list().unwrap()[0].id).unwrap());
+        assert_eq!(store.list().unwrap().len(), 2);
+
+        drop(store);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn notable_words_have_kanji_or_are_katakana() {
+        assert!(is_notable(&content_word("食べる")));
+        assert!(is_notable(&content_word("コーヒー")));
+        assert!(is_notable(&content_word("ネット")));
+        assert!(!is_notable(&content_word("ヤ")));
+        assert!(!is_notable(&content_word("すごい")));
+        assert!(!is_notable(&content_word("ググる")));
+    }
+
+    const DAY_MS: i64 = 24 * 60 * 60 * 1000;
+
+    fn ids(cards: &[Card]) -> Vec<i64> {
+        cards.iter().map(|card| card.item.id).collect()
+    }
+
+    #[test]
+    fn grades_are_saved() {
+        let (store, path) = store("grade");
+        let id = store.add(&[new_word("会議", "かいぎ", "会議です。")], "").unwrap()[0].id;
+
+        let graded = store.grade(id, 4, 1_000).unwrap();
+        assert_eq!(graded.reviewed_at, Some(1_000));
+        assert_eq!(graded.schedule.repetitions, 1);
+        assert_eq!(graded.schedule.due_at, 1_000 + DAY_MS);
+
+        let saved = &store.due_cards(1_000 + DAY_MS, 10).unwrap()[0];
+        assert_eq!(saved.schedule, graded.schedule);
+        assert_eq!(saved.reviewed_at, Some(1_000));
+
+        let graded = store.grade(id, 5, 1_000 + DAY_MS).unwrap();
+        assert_eq!(graded.schedule.interval_days, 6);
+        assert_eq!(graded.schedule.repetitions, 2);
+
+        assert!(store.grade(id, 6, 2_000 + DAY_MS).is_err());
+        assert_eq!(store.due_cards(1_000 + 7 * DAY_MS, 10).unwrap()[0].schedule, graded.schedule);
+
+        drop(store);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn grading_an_unknown_word_fails() {
+        let (store, path) = store("unknown");
+        assert_eq!(store.grade(999, 4, 1_000).unwrap_err(), "Word 999 not found");
+
+        drop(store);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn due_cards_put_new_words_last() {
+        let (store, path) = store("due");
+        let added = store
+            .add(
+                &[
+                    new_word("会議", "かいぎ", "会議です。"),
+                    new_word("資料", "しりょう", "資料です。"),
+                    new_word("出張", "しゅっちょう", "出張です。"),
+                ],
+                "",
+            )
+            .unwrap();
+        let (meeting, materials, trip) = (added[0].id, added[1].id, added[2].id);
+        store.grade(meeting, 4, 1_000).unwrap();
+        // Forgotten, so due again in a few minutes
+        store.grade(materials, 1, 1_000).unwrap();
+
+        assert_eq!(ids(&store.due_cards(2_000, 10).unwrap()), vec![trip]);
+        let summary = store.due_summary(2_000).unwrap();
+        assert_eq!((summary.due, summary.new, summary.total), (1, 1, 3));
+
+        let later = 1_000 + DAY_MS;
+        assert_eq!(ids(&store.due_cards(later, 10).unwrap()), vec![materials, meeting, trip]);
+        assert_eq!(ids(&store.due_cards(later, 2).unwrap()), vec![materials, meeting]);
+        let summary = store.due_summary(later).unwrap();
+        assert_eq!((summary.due, summary.new, summary.total), (3, 1, 3));
+
+        drop(store);
+        let _ = std::fs::remove_file(&path);
+    }
+}
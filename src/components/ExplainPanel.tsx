@@ -0,0 +1,110 @@
+"use client"
+
+import { useState } from "react"
+import { cn } from "@/lib/utils"
+
+export interface ExplainResult {
+  original: string
+  explain_lang: string
+  explanation: {
+    phrases: { text: string; reading: string | null; role: string; gloss: string }[]
+    grammar: {
+      text: string
+      kind: "particle" | "conjugation" | "auxiliary" | "expression"
+      base_form: string | null
+      function: string
+    }[]
+    literal_translation: string
+    natural_translation: string
+    notes: string[]
+    model: string
+  }
+}
+
+const KIND_LABELS: Record<ExplainResult["explanation"]["grammar"][number]["kind"], string> = {
+  particle: "particle",
+  conjugation: "conjugation",
+  auxiliary: "auxiliary",
+  expression: "expression",
+}
+
+interface ExplainPanelProps {
+  result: ExplainResult
+  onClose: () => void
+}
+
+export function ExplainPanel({ result, onClose }: ExplainPanelProps) {
+  const [selectedPhrase, setSelectedPhrase] = useState<number | null>(null)
+  const { explanation } = result
+  const phrase = selectedPhrase === null ? null : explanation.phrases[selectedPhrase]
+
+  return (
+    <div className="flex flex-col gap-3" style={{ userSelect: "text" }}>
+      {/* Header */}
+      <div className="flex items-center justify-between">
+        <span className="text-sm font-bold text-foreground">Grammar</span>
+        <button
+          onClick={onClose}
+          className="w-6 h-6 rounded-full bg-muted/50 hover:bg-muted flex items-center justify-center text-muted-foreground hover:text-foreground transition-colors"
+        >
+          ×
+        </button>
+      </div>
+
+      {/* Sentence split into phrases */}
+      <div className="flex flex-wrap gap-1">
+        {explanation.phrases.map((p, i) => (
+          <button
+            key={i}
+            onClick={() => setSelectedPhrase(selectedPhrase === i ? null : i)}
+            className={cn(
+              "flex flex-col items-center px-1.5 py-0.5 rounded-md border border-border transition-colors",
+              selectedPhrase === i ? "bg-ai-light/20" : "bg-muted/30 hover:bg-muted",
+            )}
+          >
+            {p.reading && <span className="text-[10px] text-muted-foreground">{p.reading}</span>}
+            <span className="text-sm text-foreground">{p.text}</span>
+            <span className="text-[10px] text-ai-light">{p.role}</span>
+          </button>
+        ))}
+      </div>
+      {phrase && (
+        <p className="text-xs text-foreground">
+          <span className="font-bold">{phrase.text}</span> — {phrase.gloss}
+        </p>
+      )}
+
+      {/* Particles and conjugations */}
+      {explanation.grammar.length > 0 && (
+        <ul className="flex flex-col gap-1 text-xs max-h-40 overflow-y-auto pr-1">
+          {explanation.grammar.map((point, i) => (
+            <li key={i} className="flex items-baseline gap-2">
+              <span className="text-foreground font-bold shrink-0">{point.text}</span>
+              <span className="text-[10px] text-muted-foreground shrink-0">
+                {KIND_LABELS[point.kind]}
+                {point.base_form && point.base_form !== point.text && ` ← ${point.base_form}`}
+              </span>
+              <span className="text-foreground">{point.function}</span>
+            </li>
+          ))}
+        </ul>
+      )}
+
+      {/* Literal vs natural translation */}
+      <div className="flex flex-col gap-1 pt-2 border-t border-border">
+        <span className="text-[10px] text-muted-foreground">Literal</span>
+        <p className="text-xs text-foreground">{explanation.literal_translation}</p>
+        <span className="text-[10px] text-muted-foreground">Natural</span>
+        <p className="text-sm text-foreground">{explanation.natural_translation}</p>
+      </div>
+
+      {explanation.notes.length > 0 && (
+        <ul className="text-xs text-muted-foreground list-disc pl-4">
+          {explanation.notes.map((note) => (
+            <li key={note}>{note}</li>
+          ))}
+        </ul>
+      )}
+    </div>
+  )
+}
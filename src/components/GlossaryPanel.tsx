@@ -0,0 +1,190 @@
+"use client"
+
+import { useState, useEffect } from "react"
+import { invoke } from "@tauri-apps/api/core"
+import { RefreshCw, Upload } from "lucide-react"
+
+export interface TermEntry {
+  source_lang: string
+  target_lang: string
+  source: string
+  target: string | null
+}
+
+interface TeamGlossaryStatus {
+  source: string | null
+  terms: number
+  synced_at: number | null
+  error: string | null
+}
+
+interface DictionaryStatus {
+  entries: number
+  kanji: number
+  source: string | null
+  imported_at: number | null
+  importing: boolean
+  error: string | null
+}
+
+interface GlossaryPanelProps {
+  onClose: () => void
+}
+
+export function GlossaryPanel({ onClose }: GlossaryPanelProps) {
+  const [terms, setTerms] = useState<TermEntry[]>([])
+  const [path, setPath] = useState("")
+  const [message, setMessage] = useState<string | null>(null)
+  const [team, setTeam] = useState<TeamGlossaryStatus | null>(null)
+  const [dictionary, setDictionary] = useState<DictionaryStatus | null>(null)
+
+  const load = async () => {
+    try {
+      setTerms(await invoke<TermEntry[]>("list_glossary"))
+    } catch (e) {
+      setMessage(String(e))
+    }
+  }
+
+  useEffect(() => {
+    load()
+    invoke<TeamGlossaryStatus>("get_team_glossary_status").then(setTeam)
+    invoke<DictionaryStatus | null>("get_dictionary_status").then(setDictionary)
+  }, [])
+
+  const notImported = dictionary !== null && dictionary.entries === 0 && dictionary.kanji === 0
+
+  const handleUpdateDictionary = async () => {
+    if (dictionary) setDictionary({ ...dictionary, importing: true })
+    try {
+      setDictionary(await invoke<DictionaryStatus>("update_dictionary"))
+    } catch (e) {
+      setDictionary(await invoke<DictionaryStatus | null>("get_dictionary_status"))
+    }
+  }
+
+  const handleSync = async () => {
+    try {
+      setTeam(await invoke<TeamGlossaryStatus>("sync_team_glossary"))
+    } catch (e) {
+      setTeam(await invoke<TeamGlossaryStatus>("get_team_glossary_status"))
+    }
+  }
+
+  const handleImport = async () => {
+    if (!path.trim()) return
+    try {
+      const count = await invoke<number>("import_glossary", { path: path.trim() })
+      setMessage(`Imported ${count} terms`)
+      setPath("")
+      await load()
+    } catch (e) {
+      setMessage(String(e))
+    }
+  }
+
+  const handleClear = async () => {
+    await invoke("clear_glossary")
+    setTerms([])
+  }
+
+  return (
+    <div className="flex flex-col gap-3" style={{ userSelect: "text" }}>
+      {/* Header */}
+      <div className="flex items-center justify-between">
+        <span className="text-sm font-bold text-foreground">Glossary</span>
+        <div className="flex items-center gap-2">
+          <button
+            onClick={handleClear}
+            disabled={terms.length === 0}
+            className="px-2 py-1 rounded-md text-xs text-muted-foreground hover:text-foreground hover:bg-muted disabled:opacity-40 transition-colors"
+          >
+            Clear all
+          </button>
+          <button
+            onClick={onClose}
+            className="w-6 h-6 rounded-full bg-muted/50 hover:bg-muted flex items-center justify-center text-muted-foreground hover:text-foreground transition-colors"
+          >
+            ×
+          </button>
+        </div>
+      </div>
+
+      {/* Team glossary */}
+      {team?.source && (
+        <div className="flex items-center gap-2 text-xs text-muted-foreground">
+          <span className="flex-1 break-all">
+            Team: {team.terms} terms from {team.source}
+            {team.synced_at && ` · checked ${new Date(team.synced_at).toLocaleTimeString()}`}
+          </span>
+          <button onClick={handleSync} title="Check for updates">
+            <RefreshCw className="w-3.5 h-3.5 hover:text-foreground" />
+          </button>
+        </div>
+      )}
+      {team?.error && <p className="text-xs text-kincha break-all">{team.error}</p>}
+
+      {/* Offline dictionary for short terms */}
+      {dictionary && (
+        <div className="flex items-center gap-2 text-xs text-muted-foreground">
+          <span className="flex-1 break-all">
+            {dictionary.importing
+              ? "Dictionary: importing JMdict..."
+              : notImported
+                ? "Dictionary: not downloaded (JMdict and KANJIDIC2 from edrdg.org, about 12 MB)"
+                : `Dictionary: ${dictionary.entries.toLocaleString()} JMdict entries, ${dictionary.kanji.toLocaleString()} kanji`}
+            {dictionary.imported_at && ` · updated ${new Date(dictionary.imported_at).toLocaleDateString()}`}
+          </span>
+          {notImported && !dictionary.importing ? (
+            <button
+              onClick={handleUpdateDictionary}
+              className="px-2 py-0.5 rounded border border-border hover:text-foreground"
+            >
+              Download
+            </button>
+          ) : (
+            <button onClick={handleUpdateDictionary} disabled={dictionary.importing} title="Download again">
+              <RefreshCw className="w-3.5 h-3.5 hover:text-foreground" />
+            </button>
+          )}
+        </div>
+      )}
+      {dictionary?.error && <p className="text-xs text-kincha break-all">{dictionary.error}</p>}
+
+      {/* Import */}
+      <div className="flex items-center gap-2 px-3 py-1.5 rounded-lg bg-muted/50 border border-border">
+        <input
+          value={path}
+          onChange={(e) => setPath(e.target.value)}
+          onKeyDown={(e) => e.key === "Enter" && handleImport()}
+          placeholder="Path to a .csv or .tbx file"
+          className="flex-1 bg-transparent text-sm outline-none"
+          autoFocus
+        />
+        <button onClick={handleImport} title="Import">
+          <Upload className="w-4 h-4 text-muted-foreground hover:text-foreground" />
+        </button>
+      </div>
+
+      {message && <p className="text-xs text-muted-foreground break-all">{message}</p>}
+
+      {/* Terms */}
+      <ul className="flex flex-col gap-1 max-h-56 overflow-y-auto pr-1 text-xs">
+        {terms.length === 0 && <li className="text-muted-foreground text-center py-4">No personal glossary terms yet</li>}
+        {terms.map((term) => (
+          <li
+            key={`${term.source_lang}:${term.target_lang}:${term.source}`}
+            className="flex items-center gap-2 p-1.5 rounded-md bg-muted/30"
+          >
+            <span className="text-[10px] text-muted-foreground w-16 shrink-0">
+              {term.source_lang.toUpperCase()} → {term.target_lang.toUpperCase()}
+            </span>
+            <span className="text-foreground">{term.source}</span>
+            <span className="text-muted-foreground">→</span>
+            <span className="text-foreground">{term.target ?? <em className="text-muted-foreground">keep as is</em>}</span>
+          </li>
+        ))}
+      </ul>
+    </div>
+  )
+}
@@ -0,0 +1,211 @@
+"use client"
+
+import { useState, useEffect } from "react"
+import { invoke } from "@tauri-apps/api/core"
+import { writeText } from "@tauri-apps/plugin-clipboard-manager"
+import { Copy, Download, Search, Trash2 } from "lucide-react"
+
+export interface HistoryEntry {
+  id: number
+  created_at: number
+  original: string
+  translated: string
+  source_lang: string
+  target_lang: string
+  provider: string
+  source_app: string | null
+  latency_ms: number
+  chosen_style: string | null
+  chosen_text: string | null
+}
+
+type ExportFormat = "csv" | "jsonl" | "tmx"
+
+interface ExportSummary {
+  path: string
+  count: number
+}
+
+interface HistoryPanelProps {
+  onClose: () => void
+}
+
+export function HistoryPanel({ onClose }: HistoryPanelProps) {
+  const [query, setQuery] = useState("")
+  const [entries, setEntries] = useState<HistoryEntry[]>([])
+  const [error, setError] = useState<string | null>(null)
+  const [showExport, setShowExport] = useState(false)
+  const [exportFormat, setExportFormat] = useState<ExportFormat>("csv")
+  const [exportFrom, setExportFrom] = useState("")
+  const [exportTo, setExportTo] = useState("")
+  const [exportSource, setExportSource] = useState("")
+  const [exportTarget, setExportTarget] = useState("")
+  const [exportMessage, setExportMessage] = useState<string | null>(null)
+
+  const load = async (search: string) => {
+    try {
+      const results = search.trim()
+        ? await invoke<HistoryEntry[]>("search_history", { query: search })
+        : await invoke<HistoryEntry[]>("list_history")
+      setEntries(results)
+      setError(null)
+    } catch (e) {
+      setError(String(e))
+    }
+  }
+
+  // Debounce searches while typing
+  useEffect(() => {
+    const timeout = setTimeout(() => load(query), 200)
+    return () => clearTimeout(timeout)
+  }, [query])
+
+  const handleDelete = async (id: number) => {
+    await invoke("delete_history_entry", { id })
+    setEntries((current) => current.filter((entry) => entry.id !== id))
+  }
+
+  const handleClear = async () => {
+    await invoke("clear_history")
+    setEntries([])
+  }
+
+  const handleExport = async () => {
+    // Date inputs are local days; the "to" day is inclusive
+    const from = exportFrom ? new Date(`${exportFrom}T00:00:00`).getTime() : null
+    const to = exportTo ? new Date(`${exportTo}T00:00:00`).getTime() + 24 * 60 * 60 * 1000 : null
+    try {
+      const summary = await invoke<ExportSummary>("export_history", {
+        format: exportFormat,
+        filter: {
+          from,
+          to,
+          source_lang: exportSource || null,
+          target_lang: exportTarget || null,
+        },
+      })
+      setExportMessage(`Exported ${summary.count} entries to ${summary.path}`)
+    } catch (e) {
+      setExportMessage(String(e))
+    }
+  }
+
+  return (
+    <div className="flex flex-col gap-3" style={{ userSelect: "text" }}>
+      {/* Header */}
+      <div className="flex items-center justify-between">
+        <span className="text-sm font-bold text-foreground">History</span>
+        <div className="flex items-center gap-2">
+          <button
+            onClick={() => setShowExport(!showExport)}
+            title="Export"
+            className="px-2 py-1 rounded-md text-xs text-muted-foreground hover:text-foreground hover:bg-muted transition-colors"
+          >
+            <Download className="w-3.5 h-3.5" />
+          </button>
+          <button
+            onClick={handleClear}
+            disabled={entries.length === 0}
+            className="px-2 py-1 rounded-md text-xs text-muted-foreground hover:text-foreground hover:bg-muted disabled:opacity-40 transition-colors"
+          >
+            Clear all
+          </button>
+          <button
+            onClick={onClose}
+            className="w-6 h-6 rounded-full bg-muted/50 hover:bg-muted flex items-center justify-center text-muted-foreground hover:text-foreground transition-colors"
+          >
+            ×
+          </button>
+        </div>
+      </div>
+
+      {/* Export options */}
+      {showExport && (
+        <div className="flex flex-col gap-2 p-2 rounded-lg bg-muted/30 text-xs">
+          <div className="flex items-center gap-2">
+            <select
+              value={exportFormat}
+              onChange={(e) => setExportFormat(e.target.value as ExportFormat)}
+              className="px-1.5 py-1 rounded-md bg-muted/50 border border-border"
+            >
+              <option value="csv">CSV</option>
+              <option value="jsonl">JSON Lines</option>
+              <option value="tmx">TMX 1.4</option>
+            </select>
+            <input type="date" value={exportFrom} onChange={(e) => setExportFrom(e.target.value)} className="bg-transparent" />
+            <span>–</span>
+            <input type="date" value={exportTo} onChange={(e) => setExportTo(e.target.value)} className="bg-transparent" />
+          </div>
+          <div className="flex items-center gap-2">
+            <input
+              value={exportSource}
+              onChange={(e) => setExportSource(e.target.value)}
+              placeholder="from (e.g. ja)"
+              className="w-24 px-1.5 py-1 rounded-md bg-muted/50 border border-border outline-none"
+            />
+            <span>→</span>
+            <input
+              value={exportTarget}
+              onChange={(e) => setExportTarget(e.target.value)}
+              placeholder="to (e.g. en)"
+              className="w-24 px-1.5 py-1 rounded-md bg-muted/50 border border-border outline-none"
+            />
+            <button
+              onClick={handleExport}
+              className="ml-auto px-3 py-1 rounded-md bg-gradient-to-r from-ai-iro to-ai-light text-cream font-semibold"
+            >
+              Export
+            </button>
+          </div>
+          {exportMessage && <p className="text-muted-foreground break-all">{exportMessage}</p>}
+        </div>
+      )}
+
+      {/* Search */}
+      <div className="flex items-center gap-2 px-3 py-1.5 rounded-lg bg-muted/50 border border-border">
+        <Search className="w-4 h-4 text-muted-foreground" />
+        <input
+          value={query}
+          onChange={(e) => setQuery(e.target.value)}
+          placeholder="Search original or translated text"
+          className="flex-1 bg-transparent text-sm outline-none"
+          autoFocus
+        />
+      </div>
+
+      {error && <p className="text-xs text-red-500">{error}</p>}
+
+      {/* Entries */}
+      <ul className="flex flex-col gap-2 max-h-56 overflow-y-auto pr-1">
+        {entries.length === 0 && !error && (
+          <li className="text-xs text-muted-foreground text-center py-4">No translations yet</li>
+        )}
+        {entries.map((entry) => (
+          <li key={entry.id} className="group flex flex-col gap-1 p-2 rounded-lg bg-muted/30">
+            <div className="flex items-center justify-between text-[10px] text-muted-foreground">
+              <span>
+                {entry.source_lang.toUpperCase()} → {entry.target_lang.toUpperCase()}
+                {entry.source_app && ` · ${entry.source_app}`} · {new Date(entry.created_at).toLocaleString()}
+              </span>
+              <span className="flex gap-1 opacity-0 group-hover:opacity-100 transition-opacity">
+                <button onClick={() => writeText(entry.translated)} title="Copy translation">
+                  <Copy className="w-3 h-3 hover:text-foreground" />
+                </button>
+                <button onClick={() => handleDelete(entry.id)} title="Delete">
+                  <Trash2 className="w-3 h-3 hover:text-foreground" />
+                </button>
+              </span>
+            </div>
+            <p className="text-xs text-muted-foreground line-clamp-2">{entry.original}</p>
+            <p className="text-sm text-foreground line-clamp-3">{entry.translated}</p>
+            {entry.chosen_text && (
+              <p className="text-xs text-ai-light line-clamp-3">
+                Used ({entry.chosen_style}): {entry.chosen_text}
+              </p>
+            )}
+          </li>
+        ))}
+      </ul>
+    </div>
+  )
+}
@@ -0,0 +1,130 @@
+"use client"
+
+import { useState, useEffect } from "react"
+import { invoke } from "@tauri-apps/api/core"
+import type { VocabularyItem } from "./VocabularyPanel"
+
+interface Card extends VocabularyItem {
+  ease: number
+  interval_days: number
+  repetitions: number
+  lapses: number
+  due_at: number
+  reviewed_at: number | null
+}
+
+// SM-2 grades behind each answer button
+const GRADES = [
+  { label: "Again", grade: 1 },
+  { label: "Hard", grade: 3 },
+  { label: "Good", grade: 4 },
+  { label: "Easy", grade: 5 },
+]
+
+interface PracticePanelProps {
+  onClose: () => void
+}
+
+export function PracticePanel({ onClose }: PracticePanelProps) {
+  const [cards, setCards] = useState<Card[]>([])
+  const [loaded, setLoaded] = useState(false)
+  const [revealed, setRevealed] = useState(false)
+  const [reviewed, setReviewed] = useState(0)
+  const [error, setError] = useState<string | null>(null)
+
+  const load = async () => {
+    try {
+      setCards(await invoke<Card[]>("get_due_cards"))
+      setError(null)
+    } catch (e) {
+      setError(String(e))
+    }
+    setLoaded(true)
+  }
+
+  useEffect(() => {
+    load()
+  }, [])
+
+  const card = cards[0]
+
+  const handleGrade = async (grade: number) => {
+    if (!card) return
+    try {
+      const graded = await invoke<Card>("grade_card", { id: card.id, grade })
+      setReviewed((count) => count + 1)
+      setRevealed(false)
+      // Forgotten words come back at the end of the session
+      const rest = cards.slice(1)
+      setCards(graded.interval_days === 0 ? [...rest, graded] : rest)
+      if (rest.length === 0 && graded.interval_days > 0) load()
+    } catch (e) {
+      setError(String(e))
+    }
+  }
+
+  return (
+    <div className="flex flex-col gap-3">
+      {/* Header */}
+      <div className="flex items-center justify-between">
+        <span className="text-sm font-bold text-foreground">Practice</span>
+        <div className="flex items-center gap-2">
+          <span className="text-xs text-muted-foreground">
+            {reviewed} reviewed · {cards.length} left
+          </span>
+          <button
+            onClick={onClose}
+            className="w-6 h-6 rounded-full bg-muted/50 hover:bg-muted flex items-center justify-center text-muted-foreground hover:text-foreground transition-colors"
+          >
+            ×
+          </button>
+        </div>
+      </div>
+
+      {error && <p className="text-xs text-red-500">{error}</p>}
+
+      {loaded && !card && !error && (
+        <p className="text-xs text-muted-foreground text-center py-6">
+          {reviewed > 0 ? "All done for now." : "Nothing to practise. Add words with + 単語 on a translation."}
+        </p>
+      )}
+
+      {card && (
+        <div className="flex flex-col items-center gap-3 p-3 rounded-lg bg-muted/30">
+          <span className="text-2xl font-bold text-foreground">{card.word}</span>
+          <p className="text-xs text-muted-foreground text-center">{card.sentence}</p>
+
+          {revealed ? (
+            <>
+              <div className="flex flex-col items-center gap-1">
+                {card.reading && card.reading !== card.word && (
+                  <span className="text-sm text-ai-light">{card.reading}</span>
+                )}
+                <span className="text-sm text-foreground text-center">{card.gloss ?? "—"}</span>
+                <p className="text-[10px] text-muted-foreground text-center">{card.translation}</p>
+              </div>
+              <div className="flex gap-2">
+                {GRADES.map(({ label, grade }) => (
+                  <button
+                    key={grade}
+                    onClick={() => handleGrade(grade)}
+                    className="px-3 py-1 rounded-md text-xs bg-muted/50 hover:bg-muted text-foreground transition-colors"
+                  >
+                    {label}
+                  </button>
+                ))}
+              </div>
+            </>
+          ) : (
+            <button
+              onClick={() => setRevealed(true)}
+              className="px-3 py-1 rounded-md bg-gradient-to-r from-ai-iro to-ai-light text-cream text-xs font-semibold"
+            >
+              Show answer
+            </button>
+          )}
+        </div>
+      )}
+    </div>
+  )
+}
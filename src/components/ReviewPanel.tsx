@@ -0,0 +1,115 @@
+"use client"
+
+import { useState } from "react"
+import { writeText } from "@tauri-apps/plugin-clipboard-manager"
+import { Copy } from "lucide-react"
+import { cn } from "@/lib/utils"
+
+export interface ReviewResult {
+  original: string
+  politeness: "casual" | "polite" | "honorific" | "mixed"
+  spans: { text: string; issue: number | null }[]
+  issues: { problem: string; suggestion: string | null }[]
+  rewrite: string | null
+  back_translation: string
+  back_translation_lang: string
+  provider: string
+}
+
+const POLITENESS_LABELS: Record<ReviewResult["politeness"], string> = {
+  casual: "Casual (plain form)",
+  polite: "Polite (です/ます)",
+  honorific: "Honorific (敬語)",
+  mixed: "Mixed registers",
+}
+
+interface ReviewPanelProps {
+  review: ReviewResult
+  onClose: () => void
+}
+
+export function ReviewPanel({ review, onClose }: ReviewPanelProps) {
+  const [selectedIssue, setSelectedIssue] = useState<number | null>(null)
+
+  return (
+    <div className="flex flex-col gap-3" style={{ userSelect: "text" }}>
+      {/* Header */}
+      <div className="flex items-center justify-between">
+        <div className="flex items-center gap-2">
+          <span className="text-sm font-bold text-foreground">Draft review</span>
+          <span
+            className={cn(
+              "px-2 py-0.5 rounded-full text-xs font-bold",
+              review.politeness === "mixed" ? "bg-kincha/20 text-kincha" : "bg-ai-light/20 text-ai-light",
+            )}
+          >
+            {POLITENESS_LABELS[review.politeness]}
+          </span>
+        </div>
+        <button
+          onClick={onClose}
+          className="w-6 h-6 rounded-full bg-muted/50 hover:bg-muted flex items-center justify-center text-muted-foreground hover:text-foreground transition-colors"
+        >
+          ×
+        </button>
+      </div>
+
+      {/* Draft with flagged phrasing underlined */}
+      <p className="text-sm text-foreground leading-relaxed whitespace-pre-wrap">
+        {review.spans.map((span, i) =>
+          span.issue === null ? (
+            <span key={i}>{span.text}</span>
+          ) : (
+            <span
+              key={i}
+              onClick={() => setSelectedIssue(span.issue)}
+              title={review.issues[span.issue].problem}
+              className={cn(
+                "underline decoration-wavy decoration-kincha cursor-pointer",
+                selectedIssue === span.issue && "bg-kincha/20 rounded",
+              )}
+            >
+              {span.text}
+            </span>
+          ),
+        )}
+      </p>
+
+      {/* Issues */}
+      {review.issues.length > 0 ? (
+        <ol className="flex flex-col gap-1.5 text-xs list-decimal pl-4">
+          {review.issues.map((issue, i) => (
+            <li
+              key={i}
+              onClick={() => setSelectedIssue(i)}
+              className={cn("cursor-pointer", selectedIssue === i ? "text-foreground" : "text-muted-foreground")}
+            >
+              {issue.problem}
+              {issue.suggestion && <span className="block text-foreground">→ {issue.suggestion}</span>}
+            </li>
+          ))}
+        </ol>
+      ) : (
+        <p className="text-xs text-muted-foreground">No problems found</p>
+      )}
+
+      {/* Suggested rewrite */}
+      {review.rewrite && (
+        <div className="flex items-start gap-2 p-2 rounded-lg bg-muted/30 text-sm">
+          <p className="flex-1 text-foreground">{review.rewrite}</p>
+          <button onClick={() => writeText(review.rewrite ?? "")} title="Copy rewrite">
+            <Copy className="w-3.5 h-3.5 text-muted-foreground hover:text-foreground" />
+          </button>
+        </div>
+      )}
+
+      {/* Back-translation */}
+      <div className="flex flex-col gap-0.5 pt-2 border-t border-border">
+        <span className="text-[10px] text-muted-foreground">
+          Back-translation ({review.back_translation_lang.toUpperCase()})
+        </span>
+        <p className="text-xs text-foreground">{review.back_translation}</p>
+      </div>
+    </div>
+  )
+}
@@ -1,6 +1,7 @@
 "use client"
 
 import { useState, useEffect, useRef } from "react"
+import { invoke } from "@tauri-apps/api/core"
 import { listen } from "@tauri-apps/api/event"
 import { writeText } from "@tauri-apps/plugin-clipboard-manager"
 import { getCurrentWindow } from "@tauri-apps/api/window"
@@ -9,24 +10,124 @@ import { UmaMascot } from "./UmaMascot"
 import { SpeechBubble } from "./SpeechBubble"
 import { FloatingKanji } from "./FloatingKanji"
 import { Confetti } from "./Confetti"
-import { Copy, Check, Sparkles } from "lucide-react"
+import { HistoryPanel } from "./HistoryPanel"
+import { GlossaryPanel } from "./GlossaryPanel"
+import { VocabularyPanel } from "./VocabularyPanel"
+import { PracticePanel } from "./PracticePanel"
+import { ReviewPanel, type ReviewResult } from "./ReviewPanel"
+import { ExplainPanel, type ExplainResult } from "./ExplainPanel"
+import { Copy, Check, Sparkles, ArrowLeftRight } from "lucide-react"
 
-type OverlayState = "idle" | "loading" | "result"
+type OverlayState = "idle" | "loading" | "result" | "history" | "glossary" | "review" | "explain" | "vocabulary" | "practice"
 
 interface TranslationResult {
   original: string
   translated: string
   source_lang: string
   target_lang: string
+  confidence: number
+  low_confidence: boolean
+  provider: string
+  source_app: string | null
+  latency_ms: number
+  formality: Formality | null
+  glossary_warnings: string[]
+  quantity_warnings: string[]
+  markup_warnings: string[]
+  memory_matches: MemoryMatch[]
+  back_translation: BackTranslationCheck | null
+  readings: Readings | null
+  difficulty: Difficulty | null
+  dictionary_entries: DictionaryEntry[]
+  alternatives: Alternative[]
+  chosen_alternative: number | null
+  history_id: number | null
+}
+
+type JlptLevel = "N5" | "N4" | "N3" | "N2" | "N1"
+
+interface Difficulty {
+  level: JlptLevel | null
+  score: number
+  spans: { text: string; base_form: string | null; level: JlptLevel | null }[]
+  counts: number[]
+  unlisted: number
+}
+
+interface Alternative {
+  style: string
+  text: string
+  note: string
+}
+
+interface DictionaryEntry {
+  id: number
+  kanji: string[]
+  readings: string[]
+  senses: { pos: string[]; glosses: string[]; misc: string[] }[]
+  common: boolean
+}
+
+interface Readings {
+  tokens: { surface: string; reading: string | null; romaji: string; ruby: { text: string; reading: string | null }[] }[]
+  romaji: string
+}
+
+interface KanjiInfo {
+  literal: string
+  on_readings: string[]
+  kun_readings: string[]
+  nanori: string[]
+  meanings: string[]
+  stroke_count: number | null
+  jlpt: number | null
+  grade: number | null
+  frequency: number | null
+  radicals: { kind: string; number: number; character: string | null }[]
+}
+
+type Formality = "casual" | "polite" | "honorific"
+
+interface MemoryMatch {
+  source: string
+  target: string
+  score: number
+}
+
+interface BackTranslationCheck {
+  back_translation: string
+  similarity: number
+  issues: string[]
+  suspicious: boolean
+}
+
+interface TranslationProgress {
+  done: number
+  total: number
+}
+
+interface LanguageSettings {
+  home_lang: string
+  working_langs: string[]
 }
 
 export function TranslationOverlay() {
   const [state, setState] = useState<OverlayState>("idle")
   const [result, setResult] = useState<TranslationResult | null>(null)
+  const [review, setReview] = useState<ReviewResult | null>(null)
+  const [explanation, setExplanation] = useState<ExplainResult | null>(null)
   const [copied, setCopied] = useState(false)
   const [confettiTrigger, setConfettiTrigger] = useState(false)
+  const [languages, setLanguages] = useState<string[]>([])
+  const [progress, setProgress] = useState<TranslationProgress | null>(null)
   const hideTimeoutRef = useRef<ReturnType<typeof setTimeout> | null>(null)
 
+  useEffect(() => {
+    invoke<LanguageSettings>("get_language_settings")
+      .then((settings) => setLanguages([settings.home_lang, ...settings.working_langs]))
+      .catch((e) => console.error("Failed to load language settings:", e))
+  }, [])
+
   useEffect(() => {
     // Listen for loading state
     const unlistenLoading = listen<boolean>("translation-loading", () => {
@@ -37,9 +138,15 @@ export function TranslationOverlay() {
 
       setState("loading")
       setResult(null)
+      setProgress(null)
       getCurrentWindow().show()
     })
 
+    // Listen for progress on long texts translated in parts
+    const unlistenProgress = listen<TranslationProgress>("translation-progress", (event) => {
+      setProgress(event.payload)
+    })
+
     // Listen for translation results
     const unlisten = listen<TranslationResult>("show-translation", (event) => {
       if (hideTimeoutRef.current !== null) {
@@ -59,13 +166,88 @@ export function TranslationOverlay() {
       }, 10000)
     })
 
+    // Listen for the history view being opened from a hotkey or the tray
+    const unlistenHistory = listen("open-history", () => {
+      if (hideTimeoutRef.current !== null) {
+        clearTimeout(hideTimeoutRef.current)
+        hideTimeoutRef.current = null
+      }
+
+      setState("history")
+      getCurrentWindow().show()
+    })
+
+    // Listen for draft reviews; stay open until closed, as reviews take time to read
+    const unlistenReview = listen<ReviewResult>("show-review", (event) => {
+      if (hideTimeoutRef.current !== null) {
+        clearTimeout(hideTimeoutRef.current)
+        hideTimeoutRef.current = null
+      }
+
+      setReview(event.payload)
+      setState("review")
+      getCurrentWindow().show()
+    })
+
+    // Listen for grammar explanations; like reviews, they stay open until closed
+    const unlistenExplain = listen<ExplainResult>("show-explanation", (event) => {
+      if (hideTimeoutRef.current !== null) {
+        clearTimeout(hideTimeoutRef.current)
+        hideTimeoutRef.current = null
+      }
+
+      setExplanation(event.payload)
+      setState("explain")
+      getCurrentWindow().show()
+    })
+
+    // Listen for the glossary view being opened from the tray
+    const unlistenGlossary = listen("open-glossary", () => {
+      if (hideTimeoutRef.current !== null) {
+        clearTimeout(hideTimeoutRef.current)
+        hideTimeoutRef.current = null
+      }
+
+      setState("glossary")
+      getCurrentWindow().show()
+    })
+
+    // Listen for the word list being opened from the tray
+    const unlistenVocabulary = listen("open-vocabulary", () => {
+      if (hideTimeoutRef.current !== null) {
+        clearTimeout(hideTimeoutRef.current)
+        hideTimeoutRef.current = null
+      }
+
+      setState("vocabulary")
+      getCurrentWindow().show()
+    })
+
+    // Listen for a practice session being started from the tray
+    const unlistenPractice = listen("open-practice", () => {
+      if (hideTimeoutRef.current !== null) {
+        clearTimeout(hideTimeoutRef.current)
+        hideTimeoutRef.current = null
+      }
+
+      setState("practice")
+      getCurrentWindow().show()
+    })
+
     // Cleanup on unmount
     return () => {
       if (hideTimeoutRef.current !== null) {
         clearTimeout(hideTimeoutRef.current)
       }
       unlistenLoading.then((fn) => fn())
+      unlistenProgress.then((fn) => fn())
       unlisten.then((fn) => fn())
+      unlistenHistory.then((fn) => fn())
+      unlistenGlossary.then((fn) => fn())
+      unlistenVocabulary.then((fn) => fn())
+      unlistenPractice.then((fn) => fn())
+      unlistenReview.then((fn) => fn())
+      unlistenExplain.then((fn) => fn())
     }
   }, [])
 
@@ -81,9 +263,45 @@ export function TranslationOverlay() {
     }
   }
 
+  // Copy an alternative translation and log the choice to history
+  const handleChooseAlternative = async (index: number) => {
+    if (!result) return
+    await writeText(result.alternatives[index].text)
+    setResult({ ...result, chosen_alternative: index })
+    try {
+      await invoke("choose_alternative", { index })
+    } catch (e) {
+      console.error("Failed to record chosen alternative:", e)
+    }
+  }
+
+  // Retranslate the already-captured text with an explicit direction
+  const handleRetranslate = async (sourceLang: string, targetLang?: string, formality?: Formality) => {
+    if (!result) return
+    try {
+      await invoke("translate_text", { text: result.original, sourceLang, targetLang, formality })
+    } catch (e) {
+      console.error("Retranslation failed:", e)
+      setState("result")
+    }
+  }
+
+  // Translate the result back into the source language to catch mistranslations
+  const handleVerify = async () => {
+    if (!result) return
+    try {
+      const check = await invoke<BackTranslationCheck>("verify_translation")
+      setResult({ ...result, back_translation: check })
+    } catch (e) {
+      console.error("Back-translation check failed:", e)
+    }
+  }
+
   const handleClose = async () => {
     setState("idle")
     setResult(null)
+    setReview(null)
+    setExplanation(null)
     await getCurrentWindow().hide()
   }
 
@@ -104,8 +322,24 @@ export function TranslationOverlay() {
       {/* Main content */}
       <SpeechBubble isVisible={true} className="w-full max-w-lg">
         {state === "idle" && <IdleContent />}
-        {state === "loading" && <LoadingContent />}
-        {state === "result" && result && <ResultContent result={result} onCopy={handleCopy} copied={copied} />}
+        {state === "loading" && <LoadingContent progress={progress} />}
+        {state === "history" && <HistoryPanel onClose={handleClose} />}
+        {state === "glossary" && <GlossaryPanel onClose={handleClose} />}
+        {state === "vocabulary" && <VocabularyPanel onClose={handleClose} />}
+        {state === "practice" && <PracticePanel onClose={handleClose} />}
+        {state === "review" && review && <ReviewPanel review={review} onClose={handleClose} />}
+        {state === "explain" && explanation && <ExplainPanel result={explanation} onClose={handleClose} />}
+        {state === "result" && result && (
+          <ResultContent
+            result={result}
+            languages={languages}
+            onCopy={handleCopy}
+            onRetranslate={handleRetranslate}
+            onVerify={handleVerify}
+            onChooseAlternative={handleChooseAlternative}
+            copied={copied}
+          />
+        )}
       </SpeechBubble>
 
       {/* Mascot */}
@@ -142,7 +376,7 @@ function IdleContent() {
   )
 }
 
-function LoadingContent() {
+function LoadingContent({ progress }: { progress: TranslationProgress | null }) {
   return (
     <div className="flex flex-col items-center gap-4 py-4">
       {/* Custom loading spinner */}
@@ -167,26 +401,115 @@ function LoadingContent() {
         </span>
       </div>
 
-      <span className="text-sm text-muted-foreground">Becoming one with your message...</span>
+      <span className="text-sm text-muted-foreground">
+        {progress ? `Part ${progress.done} of ${progress.total} done...` : "Becoming one with your message..."}
+      </span>
     </div>
   )
 }
 
 interface ResultContentProps {
   result: TranslationResult
+  languages: string[]
   onCopy: () => void
+  onRetranslate: (sourceLang: string, targetLang?: string, formality?: Formality) => void
+  onVerify: () => Promise<void>
+  onChooseAlternative: (index: number) => void
   copied: boolean
 }
 
-function ResultContent({ result, onCopy, copied }: ResultContentProps) {
-  const [fromLang, toLang] = result.source_lang === "ja" ? ["JA", "EN"] : ["EN", "JA"]
+function ResultContent({
+  result,
+  languages,
+  onCopy,
+  onRetranslate,
+  onVerify,
+  onChooseAlternative,
+  copied,
+}: ResultContentProps) {
+  const [verifying, setVerifying] = useState(false)
+  const [readings, setReadings] = useState<Readings | null>(result.readings)
+  const [showReadings, setShowReadings] = useState(result.readings !== null)
+  const [kanji, setKanji] = useState<KanjiInfo[] | null>(null)
+  const [kanjiError, setKanjiError] = useState<string | null>(null)
+  const [wordsMessage, setWordsMessage] = useState<string | null>(null)
+  const [showLevels, setShowLevels] = useState(false)
+  const warnings = [...result.glossary_warnings, ...result.quantity_warnings, ...result.markup_warnings]
+
+  // Furigana go on whichever side is Japanese
+  const readingsOnOriginal = result.source_lang.toLowerCase().startsWith("ja")
+  const japaneseText = readingsOnOriginal
+    ? result.original
+    : result.target_lang.toLowerCase().startsWith("ja")
+      ? result.translated
+      : null
+
+  useEffect(() => {
+    setReadings(result.readings)
+    setShowReadings(result.readings !== null)
+    setKanji(null)
+    setKanjiError(null)
+    setWordsMessage(null)
+    setShowLevels(false)
+  }, [result])
+
+  const toggleReadings = async () => {
+    if (!showReadings && !readings && japaneseText) {
+      try {
+        setReadings(await invoke<Readings>("get_readings", { text: japaneseText }))
+      } catch (e) {
+        console.error("Failed to load readings:", e)
+        return
+      }
+    }
+    setShowReadings(!showReadings)
+  }
+
+  // Save notable words from the Japanese side to the word list
+  const handleAddWords = async () => {
+    try {
+      const added = await invoke<{ word: string }[]>("extract_vocabulary")
+      setWordsMessage(
+        added.length > 0
+          ? `Added ${added.length} word${added.length === 1 ? "" : "s"}: ${added.map((item) => item.word).join("、")}`
+          : "No new words",
+      )
+    } catch (e) {
+      setWordsMessage(String(e))
+    }
+  }
+
+  const toggleKanji = async () => {
+    if (kanji || kanjiError) {
+      setKanji(null)
+      setKanjiError(null)
+      return
+    }
+    try {
+      setKanji(await invoke<KanjiInfo[]>("get_kanji_breakdown", { text: japaneseText }))
+    } catch (e) {
+      setKanjiError(String(e))
+    }
+  }
+  const fromLang = result.source_lang.toUpperCase()
+  const toLang = result.target_lang.toUpperCase()
+  const sourceChoices = Array.from(new Set([result.source_lang, ...languages]))
 
   return (
     <div className="flex flex-col gap-4">
       {/* Language direction header */}
       <div className="flex items-center justify-between">
         <div className="flex items-center gap-2">
-          <span className="px-2 py-1 rounded-full bg-ai-iro/20 text-ai-light text-xs font-bold">{fromLang}</span>
+          <span
+            className={cn(
+              "px-2 py-1 rounded-full bg-ai-iro/20 text-ai-light text-xs font-bold",
+              result.low_confidence && "border border-dashed border-kincha",
+            )}
+            title={`Detected with ${Math.round(result.confidence * 100)}% confidence`}
+          >
+            {fromLang}
+            {result.low_confidence && "?"}
+          </span>
           {/* Animated arrow with brush stroke effect */}
           <svg className="w-6 h-6 text-kincha" viewBox="0 0 24 24" fill="none">
             <path
@@ -198,6 +521,97 @@ function ResultContent({ result, onCopy, copied }: ResultContentProps) {
             />
           </svg>
           <span className="px-2 py-1 rounded-full bg-ai-light/20 text-ai-light text-xs font-bold">{toLang}</span>
+
+          {/* Swap direction */}
+          <button
+            onClick={() => onRetranslate(result.target_lang, result.source_lang)}
+            title="Swap direction"
+            className="w-6 h-6 rounded-full bg-muted/50 hover:bg-muted flex items-center justify-center text-muted-foreground hover:text-foreground transition-colors"
+          >
+            <ArrowLeftRight className="w-3.5 h-3.5" />
+          </button>
+
+          {/* Retranslate as another source language */}
+          <select
+            value={result.source_lang}
+            onChange={(e) => onRetranslate(e.target.value)}
+            title="Retranslate as..."
+            className="px-1.5 py-0.5 rounded-md bg-muted/50 border border-border text-xs text-muted-foreground"
+          >
+            {sourceChoices.map((lang) => (
+              <option key={lang} value={lang}>
+                as {lang.toUpperCase()}
+              </option>
+            ))}
+          </select>
+
+          {/* Japanese formality */}
+          {result.target_lang.toLowerCase().startsWith("ja") && (
+            <select
+              value={result.formality ?? ""}
+              onChange={(e) => onRetranslate(result.source_lang, result.target_lang, e.target.value as Formality)}
+              title="Formality"
+              className="px-1.5 py-0.5 rounded-md bg-muted/50 border border-border text-xs text-muted-foreground"
+            >
+              {result.formality === null && <option value="">formality</option>}
+              <option value="casual">casual</option>
+              <option value="polite">です/ます</option>
+              <option value="honorific">敬語</option>
+            </select>
+          )}
+
+          {/* Furigana and romaji */}
+          {japaneseText && (
+            <button
+              onClick={toggleReadings}
+              title="Show readings"
+              className={cn(
+                "px-1.5 py-0.5 rounded-md border border-border text-xs transition-colors",
+                showReadings ? "bg-ai-light/20 text-ai-light" : "bg-muted/50 text-muted-foreground hover:text-foreground",
+              )}
+            >
+              ふりがな
+            </button>
+          )}
+
+          {/* Kanji breakdown */}
+          {japaneseText && (
+            <button
+              onClick={toggleKanji}
+              title="Kanji breakdown"
+              className={cn(
+                "px-1.5 py-0.5 rounded-md border border-border text-xs transition-colors",
+                kanji || kanjiError ? "bg-ai-light/20 text-ai-light" : "bg-muted/50 text-muted-foreground hover:text-foreground",
+              )}
+            >
+              漢字
+            </button>
+          )}
+
+          {/* Save words to the word list */}
+          {japaneseText && (
+            <button
+              onClick={handleAddWords}
+              title="Add words to word list"
+              className="px-1.5 py-0.5 rounded-md border border-border text-xs bg-muted/50 text-muted-foreground hover:text-foreground transition-colors"
+            >
+              + 単語
+            </button>
+          )}
+
+          {/* Estimated JLPT level; shows the level of each word */}
+          {result.difficulty?.level && (
+            <button
+              onClick={() => setShowLevels(!showLevels)}
+              title={`Difficulty ${result.difficulty.score}/100 · 90% of words at ${result.difficulty.level} or easier`}
+              className={cn(
+                "px-1.5 py-0.5 rounded-md border border-border text-xs transition-colors",
+                showLevels ? "bg-ai-light/20 text-ai-light" : "bg-muted/50 text-muted-foreground hover:text-foreground",
+              )}
+            >
+              {result.difficulty.level}
+            </button>
+          )}
         </div>
 
         {/* Close button */}
@@ -212,8 +626,120 @@ function ResultContent({ result, onCopy, copied }: ResultContentProps) {
         </button>
       </div>
 
-      {/* Translated text */}
-      <p className="text-foreground leading-relaxed text-sm">{result.translated}</p>
+      {/* Uncertain language detection warning */}
+      {result.low_confidence && (
+        <p className="text-xs text-kincha">
+          Not sure this is {fromLang} ({Math.round(result.confidence * 100)}% confidence) — double-check the translation.
+        </p>
+      )}
+
+      {/* Glossary terms the translation did not use, changed numbers or dates, and lost markup */}
+      {warnings.length > 0 && (
+        <ul className="text-xs text-kincha list-disc pl-4">
+          {warnings.map((warning) => (
+            <li key={warning}>{warning}</li>
+          ))}
+        </ul>
+      )}
+
+      {/* Readings for the Japanese original */}
+      {showReadings && readings && readingsOnOriginal && <RubyText readings={readings} />}
+
+      {/* Translated text, or dictionary entries for a single word */}
+      {result.dictionary_entries.length > 0 ? (
+        <DictionaryEntries entries={result.dictionary_entries} />
+      ) : showReadings && readings && !readingsOnOriginal ? (
+        <RubyText readings={readings} />
+      ) : (
+        <p className="text-foreground leading-relaxed text-sm">{result.translated}</p>
+      )}
+      {result.provider === "translation-memory" && (
+        <p className="text-[10px] text-muted-foreground">From translation memory</p>
+      )}
+
+      {/* Alternative translations, best first */}
+      {result.alternatives.length > 0 && (
+        <div className="flex flex-col gap-1.5 pt-2 border-t border-border">
+          <span className="text-[10px] text-muted-foreground">Alternatives</span>
+          {result.alternatives.map((alternative, i) => (
+            <div
+              key={i}
+              className={cn(
+                "group flex items-start gap-2 p-1.5 rounded-md text-xs",
+                result.chosen_alternative === i ? "bg-ai-light/20" : "bg-muted/30",
+              )}
+            >
+              <span className="px-1.5 py-0.5 rounded-full bg-ai-iro/20 text-ai-light text-[10px] font-bold shrink-0">
+                {alternative.style}
+              </span>
+              <div className="flex-1 flex flex-col gap-0.5">
+                <p className="text-foreground">{alternative.text}</p>
+                {alternative.note && <p className="text-[10px] text-muted-foreground">{alternative.note}</p>}
+              </div>
+              <button onClick={() => onChooseAlternative(i)} title="Use this translation">
+                {result.chosen_alternative === i ? (
+                  <Check className="w-3.5 h-3.5 text-ai-light" />
+                ) : (
+                  <Copy className="w-3.5 h-3.5 text-muted-foreground hover:text-foreground" />
+                )}
+              </button>
+            </div>
+          ))}
+        </div>
+      )}
+
+      {wordsMessage && <p className="text-[10px] text-muted-foreground">{wordsMessage}</p>}
+
+      {/* Kanji breakdown */}
+      {kanjiError && <p className="text-xs text-kincha">{kanjiError}</p>}
+      {kanji && <KanjiBreakdown kanji={kanji} />}
+
+      {/* Words by JLPT level */}
+      {showLevels && result.difficulty && <LevelledText difficulty={result.difficulty} />}
+
+      {/* Back-translation check */}
+      {result.back_translation ? (
+        <div className="flex flex-col gap-0.5 pt-2 border-t border-border">
+          <span className={cn("text-[10px]", result.back_translation.suspicious ? "text-kincha" : "text-muted-foreground")}>
+            Back-translation ({fromLang}) · {Math.round(result.back_translation.similarity * 100)}% similar
+            {result.back_translation.suspicious && " — double-check the translation"}
+          </span>
+          <p className="text-xs text-foreground">{result.back_translation.back_translation}</p>
+          {result.back_translation.issues.length > 0 && (
+            <ul className="text-xs text-kincha list-disc pl-4">
+              {result.back_translation.issues.map((issue) => (
+                <li key={issue}>{issue}</li>
+              ))}
+            </ul>
+          )}
+        </div>
+      ) : (
+        <button
+          onClick={() => {
+            setVerifying(true)
+            onVerify().finally(() => setVerifying(false))
+          }}
+          disabled={verifying}
+          className="self-start text-[10px] text-muted-foreground hover:text-foreground underline"
+        >
+          {verifying ? "Checking..." : "Check by back-translation"}
+        </button>
+      )}
+
+      {/* Similar earlier translations */}
+      {result.memory_matches.length > 0 && (
+        <div className="flex flex-col gap-1.5 pt-2 border-t border-border">
+          <span className="text-[10px] text-muted-foreground">Similar earlier translations</span>
+          {result.memory_matches.map((match) => (
+            <div key={match.source} className="text-xs">
+              <p className="text-muted-foreground">
+                <span className="font-bold text-kincha">{Math.round(match.score * 100)}%</span> {match.source}
+              </p>
+              <p className="text-foreground">{match.target}</p>
+            </div>
+          ))}
+        </div>
+      )}
 
       {/* Copy button */}
       <button
@@ -243,3 +769,130 @@ function ResultContent({ result, onCopy, copied }: ResultContentProps) {
     </div>
   )
 }
+
+function RubyText({ readings }: { readings: Readings }) {
+  return (
+    <div className="flex flex-col gap-1">
+      <p className="text-foreground leading-loose text-sm">
+        {readings.tokens.flatMap((token, i) =>
+          token.ruby.map((part, j) =>
+            part.reading ? (
+              <ruby key={`${i}-${j}`}>
+                {part.text}
+                <rt className="text-[9px] text-muted-foreground">{part.reading}</rt>
+              </ruby>
+            ) : (
+              <span key={`${i}-${j}`}>{part.text}</span>
+            ),
+          ),
+        )}
+      </p>
+      <p className="text-xs text-muted-foreground italic">{readings.romaji}</p>
+    </div>
+  )
+}
+
+// Harder words get a more prominent underline
+const LEVEL_STYLES: Record<JlptLevel, string> = {
+  N5: "",
+  N4: "underline decoration-muted-foreground",
+  N3: "underline decoration-ai-light",
+  N2: "underline decoration-kincha",
+  N1: "underline decoration-red-500",
+}
+
+function LevelledText({ difficulty }: { difficulty: Difficulty }) {
+  return (
+    <div className="flex flex-col gap-1 pt-2 border-t border-border">
+      <p className="text-sm text-foreground leading-relaxed">
+        {difficulty.spans.map((span, i) =>
+          span.base_form === null ? (
+            <span key={i}>{span.text}</span>
+          ) : (
+            <span
+              key={i}
+              title={`${span.base_form} · ${span.level ?? "not on the JLPT list"}`}
+              className={cn(
+                "decoration-2 underline-offset-4",
+                span.level ? LEVEL_STYLES[span.level] : "underline decoration-dotted decoration-muted-foreground",
+              )}
+            >
+              {span.text}
+            </span>
+          ),
+        )}
+      </p>
+      <p className="text-[10px] text-muted-foreground">
+        {(["N5", "N4", "N3", "N2", "N1"] as JlptLevel[]).map((level, i) => `${level} ${difficulty.counts[i]}`).join(" · ")}
+        {difficulty.unlisted > 0 && ` · unlisted ${difficulty.unlisted}`} · difficulty {difficulty.score}/100
+      </p>
+    </div>
+  )
+}
+
+function DictionaryEntries({ entries }: { entries: DictionaryEntry[] }) {
+  return (
+    <div className="flex flex-col gap-3 max-h-64 overflow-y-auto pr-1">
+      {entries.map((entry) => (
+        <div key={entry.id} className="flex flex-col gap-1">
+          <div className="flex items-baseline gap-2">
+            <span className="text-base font-bold text-foreground">{entry.kanji[0] ?? entry.readings[0]}</span>
+            {entry.kanji.length > 0 && <span className="text-sm text-ai-light">{entry.readings.join("、")}</span>}
+            {entry.common && (
+              <span className="px-1.5 py-0.5 rounded-full bg-ai-light/20 text-ai-light text-[10px] font-bold">common</span>
+            )}
+          </div>
+          <ol className="flex flex-col gap-0.5 text-xs list-decimal pl-4">
+            {entry.senses.map((sense, i) => (
+              <li key={i} className="text-foreground">
+                {sense.pos.length > 0 && (
+                  <span className="block text-[10px] text-muted-foreground italic">{sense.pos.join(", ")}</span>
+                )}
+                {sense.glosses.join("; ")}
+                {sense.misc.length > 0 && <span className="text-muted-foreground"> ({sense.misc.join(", ")})</span>}
+              </li>
+            ))}
+          </ol>
+          {entry.kanji.length > 1 && (
+            <span className="text-[10px] text-muted-foreground">Also written {entry.kanji.slice(1).join("、")}</span>
+          )}
+        </div>
+      ))}
+      <span className="text-[10px] text-muted-foreground">From JMdict (EDRDG), offline</span>
+    </div>
+  )
+}
+
+function KanjiBreakdown({ kanji }: { kanji: KanjiInfo[] }) {
+  if (kanji.length === 0) {
+    return <p className="text-xs text-muted-foreground">No kanji found</p>
+  }
+  return (
+    <div className="flex flex-col gap-2 max-h-64 overflow-y-auto pr-1 pt-2 border-t border-border">
+      {kanji.map((k) => {
+        const radical = k.radicals.find((r) => r.kind === "classical")
+        return (
+          <div key={k.literal} className="flex gap-3">
+            <span className="text-3xl leading-none text-foreground">{k.literal}</span>
+            <div className="flex flex-col gap-0.5 text-xs">
+              <span className="text-foreground">{k.meanings.join(", ")}</span>
+              {k.on_readings.length > 0 && (
+                <span className="text-muted-foreground">音 {k.on_readings.join("、")}</span>
+              )}
+              {k.kun_readings.length > 0 && (
+                <span className="text-muted-foreground">訓 {k.kun_readings.join("、")}</span>
+              )}
+              <span className="text-[10px] text-muted-foreground">
+                {k.stroke_count !== null && `${k.stroke_count} strokes`}
+                {radical && ` · radical ${radical.character ?? ""} (${radical.number})`}
+                {k.jlpt !== null && ` · JLPT ${k.jlpt} (old)`}
+                {k.grade !== null && k.grade <= 6 && ` · grade ${k.grade}`}
+              </span>
+            </div>
+          </div>
+        )
+      })}
+      <span className="text-[10px] text-muted-foreground">From KANJIDIC2 (EDRDG), offline</span>
+    </div>
+  )
+}
@@ -0,0 +1,117 @@
+"use client"
+
+import { useState, useEffect } from "react"
+import { invoke } from "@tauri-apps/api/core"
+import { Download, Trash2 } from "lucide-react"
+
+export interface VocabularyItem {
+  id: number
+  word: string
+  reading: string
+  gloss: string | null
+  pos: string
+  surface: string
+  sentence: string
+  translation: string
+  added_at: number
+}
+
+type DeckFormat = "apkg" | "tsv"
+
+interface ExportSummary {
+  path: string
+  count: number
+}
+
+interface VocabularyPanelProps {
+  onClose: () => void
+}
+
+export function VocabularyPanel({ onClose }: VocabularyPanelProps) {
+  const [items, setItems] = useState<VocabularyItem[]>([])
+  const [format, setFormat] = useState<DeckFormat>("apkg")
+  const [message, setMessage] = useState<string | null>(null)
+
+  useEffect(() => {
+    invoke<VocabularyItem[]>("list_vocabulary")
+      .then(setItems)
+      .catch((e) => setMessage(String(e)))
+  }, [])
+
+  const handleDelete = async (id: number) => {
+    await invoke("delete_vocabulary_word", { id })
+    setItems((current) => current.filter((item) => item.id !== id))
+  }
+
+  const handleExport = async () => {
+    try {
+      const summary = await invoke<ExportSummary>("export_vocabulary", { format })
+      setMessage(`Exported ${summary.count} words to ${summary.path}`)
+    } catch (e) {
+      setMessage(String(e))
+    }
+  }
+
+  return (
+    <div className="flex flex-col gap-3" style={{ userSelect: "text" }}>
+      {/* Header */}
+      <div className="flex items-center justify-between">
+        <span className="text-sm font-bold text-foreground">Word list</span>
+        <div className="flex items-center gap-2">
+          <select
+            value={format}
+            onChange={(e) => setFormat(e.target.value as DeckFormat)}
+            className="px-1.5 py-0.5 rounded-md bg-muted/50 border border-border text-xs text-muted-foreground"
+          >
+            <option value="apkg">Anki (.apkg)</option>
+            <option value="tsv">Tab-separated</option>
+          </select>
+          <button
+            onClick={handleExport}
+            disabled={items.length === 0}
+            title="Export deck"
+            className="px-2 py-1 rounded-md text-xs text-muted-foreground hover:text-foreground hover:bg-muted disabled:opacity-40 transition-colors"
+          >
+            <Download className="w-3.5 h-3.5" />
+          </button>
+          <button
+            onClick={onClose}
+            className="w-6 h-6 rounded-full bg-muted/50 hover:bg-muted flex items-center justify-center text-muted-foreground hover:text-foreground transition-colors"
+          >
+            ×
+          </button>
+        </div>
+      </div>
+
+      {message && <p className="text-xs text-muted-foreground break-all">{message}</p>}
+
+      {/* Words */}
+      <ul className="flex flex-col gap-1.5 max-h-72 overflow-y-auto pr-1">
+        {items.length === 0 && (
+          <li className="text-xs text-muted-foreground text-center py-4">
+            No words yet. Use + 単語 on a translation to add its words.
+          </li>
+        )}
+        {items.map((item) => (
+          <li key={item.id} className="group flex flex-col gap-0.5 p-2 rounded-lg bg-muted/30">
+            <div className="flex items-baseline gap-2">
+              <span className="text-sm font-bold text-foreground">{item.word}</span>
+              {item.reading && item.reading !== item.word && (
+                <span className="text-xs text-ai-light">{item.reading}</span>
+              )}
+              <span className="flex-1 text-xs text-foreground">{item.gloss}</span>
+              <button
+                onClick={() => handleDelete(item.id)}
+                title="Delete"
+                className="opacity-0 group-hover:opacity-100 transition-opacity"
+              >
+                <Trash2 className="w-3 h-3 text-muted-foreground hover:text-foreground" />
+              </button>
+            </div>
+            <p className="text-[10px] text-muted-foreground line-clamp-2">{item.sentence}</p>
+          </li>
+        ))}
+      </ul>
+    </div>
+  )
+}